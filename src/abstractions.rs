@@ -1,2 +1,8 @@
+pub mod float_interval_abstraction;
 pub mod interval_abstraction;
+#[cfg(test)]
+mod laws;
+pub mod parity_abstraction;
+pub mod product_abstraction;
 pub mod sign_abstraction;
+pub mod value_set_abstraction;
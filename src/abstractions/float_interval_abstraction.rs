@@ -0,0 +1,713 @@
+use std::{cmp::Ordering, ops};
+
+use crate::abstractions::interval_abstraction::{Interval, IntervalAbstraction};
+use crate::command_parser::{BExpr, Const};
+use crate::interpreter::{AbstractProperties, Bottom, Top};
+
+/// Results below this magnitude may lose precision to underflow, so the error terms of their
+/// operations are not exact.
+const TINY: f64 = f64::MIN_POSITIVE * 18014398509481984.0; // 2^-968
+
+/// An interval of IEEE-754 doubles together with flags for the special values. Every operation
+/// rounds its bounds outwards unless the result is exact, so the abstraction is sound for the
+/// real result as well as for every rounding mode. Zero stands for both `+0` and `-0`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct FloatIntervalAbstraction {
+    /// The finite values as an interval with finite bounds, `None` if there are none.
+    pub finite: Option<(f64, f64)>,
+    pub neg_inf: bool,
+    pub pos_inf: bool,
+    pub nan: bool,
+}
+
+impl FloatIntervalAbstraction {
+    const BOTTOM: Self = FloatIntervalAbstraction {
+        finite: None,
+        neg_inf: false,
+        pos_inf: false,
+        nan: false,
+    };
+
+    const TOP: Self = FloatIntervalAbstraction {
+        finite: Some((-f64::MAX, f64::MAX)),
+        neg_inf: true,
+        pos_inf: true,
+        nan: true,
+    };
+
+    /// The values between the bounds `a` and `b`, where an infinite bound is an overflow.
+    fn between(a: f64, b: f64) -> Self {
+        let (lo, hi) = (f64::max(a, -f64::MAX), f64::min(b, f64::MAX));
+        FloatIntervalAbstraction {
+            finite: if lo <= hi { Some((lo, hi)) } else { None },
+            neg_inf: a == f64::NEG_INFINITY,
+            pos_inf: b == f64::INFINITY,
+            nan: false,
+        }
+    }
+
+    /// The values of `self` between the bounds `a` and `b`, which may be infinite.
+    fn clamp(self, a: f64, b: f64) -> Self {
+        let finite = self.finite.and_then(|(lo, hi)| {
+            let (lo, hi) = (f64::max(lo, a), f64::min(hi, b));
+            if lo <= hi {
+                Some((lo, hi))
+            } else {
+                None
+            }
+        });
+        FloatIntervalAbstraction {
+            finite,
+            neg_inf: self.neg_inf && a == f64::NEG_INFINITY,
+            pos_inf: self.pos_inf && b == f64::INFINITY,
+            nan: false,
+        }
+    }
+
+    /// The only value of `self`, if it has exactly one.
+    fn single(self) -> Option<f64> {
+        match self.finite {
+            Some((lo, hi)) if lo == hi && !(self.neg_inf || self.pos_inf || self.nan) => Some(lo),
+            Some(_) => None,
+            None => match self.samples()[..] {
+                [x] => Some(x),
+                _ => None,
+            },
+        }
+    }
+
+    /// The values of `self` except NaN.
+    fn numbers(self) -> Self {
+        FloatIntervalAbstraction { nan: false, ..self }
+    }
+
+    fn lowest(self) -> Option<f64> {
+        match self {
+            _ if self.neg_inf => Some(f64::NEG_INFINITY),
+            FloatIntervalAbstraction {
+                finite: Some((lo, _)),
+                ..
+            } => Some(lo),
+            _ if self.pos_inf => Some(f64::INFINITY),
+            _ => None,
+        }
+    }
+
+    fn highest(self) -> Option<f64> {
+        (-self).lowest().map(|lo| -lo)
+    }
+
+    /// The bounds of the finite values, one of each sign class they contain, and the special
+    /// values. The results of operations with a special operand only depend on the sign class
+    /// of the other operand.
+    fn samples(self) -> Vec<f64> {
+        let mut samples = Vec::new();
+        if let Some((lo, hi)) = self.finite {
+            samples.extend([lo, hi]);
+            if lo <= 0.0 && 0.0 <= hi {
+                samples.extend([0.0, -0.0]);
+            }
+        }
+        if self.neg_inf {
+            samples.push(f64::NEG_INFINITY);
+        }
+        if self.pos_inf {
+            samples.push(f64::INFINITY);
+        }
+        if self.nan {
+            samples.push(f64::NAN);
+        }
+        samples
+    }
+
+    /// Applies `finite_op` to the finite values and `op` to the samples of all other
+    /// combinations of values.
+    fn lift(
+        self,
+        rhs: Self,
+        op: fn(f64, f64) -> f64,
+        finite_op: fn((f64, f64), (f64, f64)) -> Self,
+    ) -> Self {
+        let mut result = match (self.finite, rhs.finite) {
+            (Some(f0), Some(f1)) => finite_op(f0, f1),
+            _ => Self::BOTTOM,
+        };
+        for x0 in self.samples() {
+            for x1 in rhs.samples() {
+                if !(x0.is_finite() && x1.is_finite()) {
+                    result = Self::join(&result, &op(x0, x1).into());
+                }
+            }
+        }
+        result
+    }
+
+    fn from_interval(a: IntervalAbstraction) -> Self {
+        match a {
+            IntervalAbstraction::Bottom => Self::BOTTOM,
+            IntervalAbstraction::Interval(Interval { a, b }) => Self::between(a, b),
+            IntervalAbstraction::Top => Self::between(-f64::MAX, f64::MAX),
+        }
+    }
+
+    /// Applies an operation on the integer parts of the finite values. A divisor whose integer
+    /// part may be zero and special operands may give infinities and NaN.
+    fn integer_op(
+        self,
+        rhs: Self,
+        op: fn(&IntervalAbstraction, &IntervalAbstraction) -> IntervalAbstraction,
+    ) -> Self {
+        if self == Self::BOTTOM || rhs == Self::BOTTOM {
+            return Self::BOTTOM;
+        }
+
+        let interval = |(a, b)| IntervalAbstraction::Interval(Interval::new(a, b));
+        let mut result = match (self.finite, rhs.finite) {
+            (Some(f0), Some(f1)) => Self::from_interval(op(&interval(f0), &interval(f1))),
+            _ => Self::BOTTOM,
+        };
+
+        let special = |a: Self| a.neg_inf || a.pos_inf || a.nan;
+        let zero_divisor = rhs.finite.is_some_and(|(a, b)| a < 1.0 && b > -1.0);
+        if special(self) || special(rhs) || zero_divisor {
+            result = Self::join(&result, &Self::between(f64::NEG_INFINITY, f64::INFINITY));
+            result.nan = true;
+        }
+        result
+    }
+}
+
+/// The bounds of an exact result that was rounded to `rounded`, given the sign of the rounding
+/// error `exact - rounded` if it is known.
+fn round_outward(rounded: f64, error: Option<Ordering>) -> (f64, f64) {
+    match error {
+        _ if rounded == f64::INFINITY => (f64::MAX, f64::INFINITY),
+        _ if rounded == f64::NEG_INFINITY => (f64::NEG_INFINITY, -f64::MAX),
+        Some(Ordering::Equal) => (rounded, rounded),
+        Some(Ordering::Greater) => (rounded, rounded.next_up()),
+        Some(Ordering::Less) => (rounded.next_down(), rounded),
+        None => (rounded.next_down(), rounded.next_up()),
+    }
+}
+
+fn is_tiny(x: f64) -> bool {
+    x != 0.0 && x.abs() < TINY
+}
+
+/// The bounds of `x + y` for finite numbers, whose rounding error is computed exactly by the
+/// TwoSum algorithm.
+fn add_bounds(x: f64, y: f64) -> (f64, f64) {
+    let sum = x + y;
+    if !sum.is_finite() {
+        return round_outward(sum, None);
+    }
+    let y_rounded = sum - x;
+    let error = (x - (sum - y_rounded)) + (y - y_rounded);
+    round_outward(sum, error.partial_cmp(&0.0))
+}
+
+/// The bounds of `x * y` for finite numbers, whose rounding error is computed exactly by a fused
+/// multiply-add unless the product is tiny.
+fn mul_bounds(x: f64, y: f64) -> (f64, f64) {
+    let product = x * y;
+    if !product.is_finite() || is_tiny(product) || (product == 0.0 && x != 0.0 && y != 0.0) {
+        return round_outward(product, None);
+    }
+    round_outward(product, x.mul_add(y, -product).partial_cmp(&0.0))
+}
+
+/// The bounds of `x / y` for finite numbers and `y != 0`. The remainder `x - q * y` of the
+/// rounded quotient `q` is exact and has the sign of the rounding error times the sign of `y`.
+fn div_bounds(x: f64, y: f64) -> (f64, f64) {
+    let quotient = x / y;
+    if !quotient.is_finite()
+        || is_tiny(quotient)
+        || is_tiny(x)
+        || is_tiny(y)
+        || (quotient == 0.0 && x != 0.0)
+    {
+        return round_outward(quotient, None);
+    }
+    let remainder = (-quotient).mul_add(y, x);
+    round_outward(quotient, (remainder * y.signum()).partial_cmp(&0.0))
+}
+
+/// The hull of the bounds of `op` for all combinations of bounds.
+fn hull(
+    (a0, b0): (f64, f64),
+    (a1, b1): (f64, f64),
+    op: fn(f64, f64) -> (f64, f64),
+) -> FloatIntervalAbstraction {
+    let bounds = [op(a0, a1), op(a0, b1), op(b0, a1), op(b0, b1)];
+    let lo = bounds
+        .iter()
+        .map(|(lo, _)| *lo)
+        .fold(f64::INFINITY, f64::min);
+    let hi = bounds
+        .iter()
+        .map(|(_, hi)| *hi)
+        .fold(f64::NEG_INFINITY, f64::max);
+    FloatIntervalAbstraction::between(lo, hi)
+}
+
+impl ops::Add for FloatIntervalAbstraction {
+    type Output = FloatIntervalAbstraction;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.lift(
+            rhs,
+            |x, y| x + y,
+            |(a0, b0), (a1, b1)| Self::between(add_bounds(a0, a1).0, add_bounds(b0, b1).1),
+        )
+    }
+}
+
+impl ops::Neg for FloatIntervalAbstraction {
+    type Output = FloatIntervalAbstraction;
+
+    fn neg(self) -> Self::Output {
+        FloatIntervalAbstraction {
+            finite: self.finite.map(|(a, b)| (-b, -a)),
+            neg_inf: self.pos_inf,
+            pos_inf: self.neg_inf,
+            nan: self.nan,
+        }
+    }
+}
+
+impl ops::Sub for FloatIntervalAbstraction {
+    type Output = FloatIntervalAbstraction;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl ops::Mul for FloatIntervalAbstraction {
+    type Output = FloatIntervalAbstraction;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, |x, y| x * y, |f0, f1| hull(f0, f1, mul_bounds))
+    }
+}
+
+impl ops::Div for FloatIntervalAbstraction {
+    type Output = FloatIntervalAbstraction;
+
+    /// Divides by the negative and the positive part of the divisor separately. A division of a
+    /// non-zero number by zero gives both infinities and `0 / 0` is NaN.
+    fn div(self, rhs: Self) -> Self::Output {
+        self.lift(
+            rhs,
+            |x, y| x / y,
+            |(a0, b0), (a1, b1)| {
+                let smallest = f64::from_bits(1);
+                let mut result = Self::BOTTOM;
+                if a1 < 0.0 {
+                    let negative = hull((a0, b0), (a1, f64::min(b1, -smallest)), div_bounds);
+                    result = Self::join(&result, &negative);
+                }
+                if b1 > 0.0 {
+                    let positive = hull((a0, b0), (f64::max(a1, smallest), b1), div_bounds);
+                    result = Self::join(&result, &positive);
+                }
+                if a1 <= 0.0 && 0.0 <= b1 {
+                    if a0 < 0.0 || b0 > 0.0 {
+                        result.neg_inf = true;
+                        result.pos_inf = true;
+                    }
+                    if a0 <= 0.0 && 0.0 <= b0 {
+                        result.nan = true;
+                    }
+                }
+                result
+            },
+        )
+    }
+}
+
+impl From<f64> for FloatIntervalAbstraction {
+    fn from(value: f64) -> Self {
+        match value {
+            _ if value.is_nan() => FloatIntervalAbstraction {
+                nan: true,
+                ..Self::BOTTOM
+            },
+            _ => Self::between(value, value),
+        }
+    }
+}
+
+impl From<Top> for FloatIntervalAbstraction {
+    fn from(_: Top) -> Self {
+        Self::TOP
+    }
+}
+
+impl From<Bottom> for FloatIntervalAbstraction {
+    fn from(_: Bottom) -> Self {
+        Self::BOTTOM
+    }
+}
+
+impl PartialOrd for FloatIntervalAbstraction {
+    /// Compares the numbers represented by both abstractions like `IntervalAbstraction`. NaN is
+    /// not comparable to anything.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            _ if self == other => Some(Ordering::Equal),
+            _ if *self == Self::BOTTOM => Some(Ordering::Less),
+            _ if *other == Self::BOTTOM => Some(Ordering::Greater),
+            _ if self.nan || other.nan => None,
+            _ if self.highest() <= other.lowest() => Some(Ordering::Less),
+            _ if other.highest() <= self.lowest() => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
+
+impl AbstractProperties<FloatIntervalAbstraction> for FloatIntervalAbstraction {
+    fn bottom() -> Bottom {
+        Bottom
+    }
+
+    fn top() -> Top {
+        Top
+    }
+
+    fn sat(a: &Self, bexpr: &BExpr) -> bool {
+        Self::refine(a, bexpr) != Self::BOTTOM
+    }
+
+    fn first_includes_second(a0: &Self, a1: &Self) -> bool {
+        let finite = match (a0.finite, a1.finite) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => a0_a <= a1_a && a1_b <= a0_b,
+        };
+        finite && (a0.neg_inf || !a1.neg_inf) && (a0.pos_inf || !a1.pos_inf) && (a0.nan || !a1.nan)
+    }
+
+    fn join(a0: &Self, a1: &Self) -> Self {
+        let finite = match (a0.finite, a1.finite) {
+            (None, finite) | (finite, None) => finite,
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => {
+                Some((f64::min(a0_a, a1_a), f64::max(a0_b, a1_b)))
+            }
+        };
+        FloatIntervalAbstraction {
+            finite,
+            neg_inf: a0.neg_inf || a1.neg_inf,
+            pos_inf: a0.pos_inf || a1.pos_inf,
+            nan: a0.nan || a1.nan,
+        }
+    }
+
+    fn meet(a0: &Self, a1: &Self) -> Self {
+        let finite = match (a0.finite, a1.finite) {
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => {
+                let (lo, hi) = (f64::max(a0_a, a1_a), f64::min(a0_b, a1_b));
+                if lo <= hi {
+                    Some((lo, hi))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        FloatIntervalAbstraction {
+            finite,
+            neg_inf: a0.neg_inf && a1.neg_inf,
+            pos_inf: a0.pos_inf && a1.pos_inf,
+            nan: a0.nan && a1.nan,
+        }
+    }
+
+    /// Strict comparisons exclude the constant exactly by moving to the next float. Only `!=`
+    /// holds for NaN.
+    fn refine(a: &Self, bexpr: &BExpr) -> Self {
+        match bexpr {
+            BExpr::EQ(_, Const::Const(number)) => a.clamp(*number, *number),
+            BExpr::NE(_, Const::Const(number)) => match a.finite {
+                Some((lo, hi)) if lo == *number && hi == *number => {
+                    FloatIntervalAbstraction { finite: None, ..*a }
+                }
+                _ => *a,
+            },
+            BExpr::LE(_, Const::Const(number)) => a.clamp(f64::NEG_INFINITY, *number),
+            BExpr::LT(_, Const::Const(number)) => a.clamp(f64::NEG_INFINITY, number.next_down()),
+            BExpr::GE(_, Const::Const(number)) => a.clamp(*number, f64::INFINITY),
+            BExpr::GT(_, Const::Const(number)) => a.clamp(number.next_up(), f64::INFINITY),
+        }
+    }
+
+    /// Widens the finite bounds to the bounds of the treshold, or to the largest finite numbers
+    /// if the treshold does not cover them. The flags can only change finitely often.
+    fn widen(a0: &Self, a1: &Self, widening_treshold: &Self) -> Self {
+        let (t_a, t_b) = widening_treshold.finite.unwrap_or((-f64::MAX, f64::MAX));
+        let finite = match (a0.finite, a1.finite) {
+            (None, finite) | (finite, None) => finite,
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => {
+                let a = match a1_a < a0_a {
+                    true if t_a <= a1_a => t_a,
+                    true => -f64::MAX,
+                    false => a0_a,
+                };
+                let b = match a1_b > a0_b {
+                    true if a1_b <= t_b => t_b,
+                    true => f64::MAX,
+                    false => a0_b,
+                };
+                Some((a, b))
+            }
+        };
+        FloatIntervalAbstraction {
+            finite,
+            ..Self::join(a0, a1)
+        }
+    }
+
+    fn rem(a0: &Self, a1: &Self) -> Self {
+        a0.integer_op(*a1, IntervalAbstraction::rem)
+    }
+
+    fn int_div(a0: &Self, a1: &Self) -> Self {
+        a0.integer_op(*a1, IntervalAbstraction::int_div)
+    }
+
+    /// The minimum is between the smallest lower and the smallest upper bound of the numbers.
+    /// The minimum of NaN and a number is the number.
+    fn min(a0: &Self, a1: &Self) -> Self {
+        let (n0, n1) = (a0.numbers(), a1.numbers());
+        let mut result = match (n0.lowest(), n1.lowest(), n0.highest(), n1.highest()) {
+            (Some(lo0), Some(lo1), Some(hi0), Some(hi1)) => {
+                Self::join(&n0, &n1).clamp(f64::min(lo0, lo1), f64::min(hi0, hi1))
+            }
+            _ => Self::BOTTOM,
+        };
+        if a0.nan {
+            result = Self::join(&result, &n1);
+        }
+        if a1.nan {
+            result = Self::join(&result, &n0);
+        }
+        result.nan = a0.nan && a1.nan;
+        result
+    }
+
+    fn max(a0: &Self, a1: &Self) -> Self {
+        -Self::min(&-*a0, &-*a1)
+    }
+
+    fn abs(a: &Self) -> Self {
+        let finite = a.finite.map(|(lo, hi)| match (lo, hi) {
+            _ if lo >= 0.0 => (lo, hi),
+            _ if hi <= 0.0 => (-hi, -lo),
+            _ => (0.0, f64::max(-lo, hi)),
+        });
+        FloatIntervalAbstraction {
+            finite,
+            neg_inf: false,
+            pos_inf: a.neg_inf || a.pos_inf,
+            nan: a.nan,
+        }
+    }
+
+    /// Infinities do not wrap around into the range, they become NaN.
+    fn wrap(a: &Self, min: f64, max: f64) -> Self {
+        let finite = match a.finite {
+            Some((lo, hi)) => IntervalAbstraction::Interval(Interval::new(lo, hi)),
+            None => IntervalAbstraction::Bottom,
+        };
+        FloatIntervalAbstraction {
+            nan: a.nan || a.neg_inf || a.pos_inf,
+            ..Self::from_interval(IntervalAbstraction::wrap(&finite, min, max))
+        }
+    }
+
+    fn may_be_nan(a: &Self) -> bool {
+        a.nan
+    }
+
+    /// The finite values widened by one ulp, which covers every real number that rounds to them.
+    /// An overflow or NaN tells nothing about the exact result.
+    fn unround(a: &Self) -> Self {
+        match a.finite {
+            _ if a.neg_inf || a.pos_inf || a.nan => Self::TOP,
+            Some((lo, hi)) if -f64::MAX < lo && hi < f64::MAX => {
+                Self::between(lo.next_down(), hi.next_up())
+            }
+            Some(_) => Self::TOP,
+            None => Self::BOTTOM,
+        }
+    }
+
+    fn nearest(a0: &Self, a1: &Self, op: fn(f64, f64) -> f64) -> Option<Self> {
+        match (a0.single(), a1.single()) {
+            (Some(x0), Some(x1)) => Some(op(x0, x1).into()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::laws::laws;
+    use crate::command_parser::{parse, SExpr, Var};
+    use crate::interpreter::{Alarm, MemoryState, Params};
+    use proptest::prelude::*;
+
+    fn point(value: f64) -> FloatIntervalAbstraction {
+        value.into()
+    }
+
+    #[test]
+    fn exact_operations_are_not_rounded() {
+        assert_eq!(point(3.0), point(1.0) + point(2.0));
+        assert_eq!(point(0.75), point(1.5) * point(0.5));
+        assert_eq!(point(-0.25), point(1.0) / point(-4.0));
+    }
+
+    #[test]
+    fn inexact_operations_are_rounded_outwards() {
+        let sum = point(0.1) + point(0.2);
+        assert_eq!(Some((0.3, 0.1 + 0.2)), sum.finite);
+
+        let quotient = point(1.0) / point(3.0);
+        let (lo, hi) = quotient.finite.unwrap();
+        assert!(lo == 1.0 / 3.0 || hi == 1.0 / 3.0);
+        assert_eq!(lo.next_up(), hi);
+    }
+
+    #[test]
+    fn overflow_to_infinity() {
+        let product = point(f64::MAX) * point(2.0);
+        assert_eq!(Some((f64::MAX, f64::MAX)), product.finite);
+        assert!(product.pos_inf && !product.neg_inf && !product.nan);
+    }
+
+    #[test]
+    fn special_values() {
+        let inf = point(f64::INFINITY);
+        assert!((inf - inf).nan);
+        assert_eq!(point(f64::INFINITY), inf + point(1.0));
+        assert_eq!(point(0.0), point(1.0) / inf);
+
+        let zero_to_one = FloatIntervalAbstraction::between(0.0, 1.0);
+        let quotient = point(1.0) / zero_to_one;
+        assert_eq!(Some((1.0, f64::MAX)), quotient.finite);
+        assert!(quotient.neg_inf && quotient.pos_inf && !quotient.nan);
+        assert!((zero_to_one / zero_to_one).nan);
+    }
+
+    #[test]
+    fn strict_guards_are_exact() {
+        let x = Var::Var("x".to_string());
+        let a = FloatIntervalAbstraction::between(0.0, 1.0);
+        let refined =
+            FloatIntervalAbstraction::refine(&a, &BExpr::LT(x.clone(), Const::Const(1.0)));
+        assert_eq!(Some((0.0, 1.0f64.next_down())), refined.finite);
+
+        let nan = point(f64::NAN);
+        assert!(!FloatIntervalAbstraction::sat(
+            &nan,
+            &BExpr::LE(x.clone(), Const::Const(0.0))
+        ));
+        assert!(FloatIntervalAbstraction::sat(
+            &nan,
+            &BExpr::NE(x, Const::Const(0.0))
+        ));
+    }
+
+    #[test]
+    fn nan_alarms() {
+        let program = "x := 0.0 / 0;\nbig := 1e308 * 10; y := big - big; z := y + 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<FloatIntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let var = |ident: &str| Box::new(SExpr::VExpr(Var::Var(ident.to_string())));
+        let number = |n| Box::new(SExpr::CExpr(Const::Const(n)));
+        assert_eq!(
+            vec![
+                Alarm::NaN(0, SExpr::Div(number(0.0), number(0.0))),
+                Alarm::NaN(33, SExpr::Sub(var("big"), var("big")))
+            ],
+            post_analyzed.alarms()
+        );
+        assert!(post_analyzed.lookup_var("z").unwrap().nan);
+    }
+
+    #[test]
+    fn inputs_are_not_nan() {
+        let program = "input(x); y := x - x; z := x / x";
+        let command = parse(program);
+
+        let mut pre: MemoryState<FloatIntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let x = || Box::new(SExpr::VExpr(Var::Var("x".to_string())));
+        assert_eq!(
+            vec![Alarm::NaN(22, SExpr::Div(x(), x()))],
+            post_analyzed.alarms()
+        );
+        assert!(!post_analyzed.lookup_var("y").unwrap().nan);
+    }
+
+    #[test]
+    fn interpretation_rounds_to_nearest() {
+        let program = "x := 0.1 + 0.2; y := 1 / 3; z := 0 / 0";
+        let command = parse(program);
+
+        let mut pre: MemoryState<FloatIntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(Some(&point(0.1 + 0.2)), post.lookup_var("x"));
+        assert_eq!(Some(&point(1.0 / 3.0)), post.lookup_var("y"));
+        assert!(post.lookup_var("z").unwrap().nan);
+        assert_eq!(1, post.alarms().len());
+    }
+
+    #[test]
+    fn loop_with_float_steps() {
+        let program = "x := 0; while (x < 1) {x := x + 0.1}";
+        let command = parse(program);
+
+        let params = Params::with_widening();
+        let mut pre: MemoryState<FloatIntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &params);
+
+        let x = post_analyzed.lookup_var("x").unwrap();
+        assert_eq!(FloatIntervalAbstraction::between(1.0, f64::MAX), *x);
+
+        // steps of 0.25 are exact, so the interpreter does not lose the value to rounding
+        let command = parse("x := 0; while (x < 1) {x := x + 0.25}");
+        let mut pre: MemoryState<FloatIntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+        assert_eq!(
+            FloatIntervalAbstraction::from(1.0),
+            *post.lookup_var("x").unwrap()
+        );
+    }
+
+    fn float_interval_abstraction() -> impl Strategy<Value = FloatIntervalAbstraction> + Clone {
+        let finite = prop_oneof![
+            Just(None),
+            (-1000i32..1000, 0i32..1000)
+                .prop_map(|(a, width)| Some((a as f64, (a + width) as f64))),
+            (-1e3f64..1e3, 0f64..1e3).prop_map(|(a, width)| Some((a, a + width))),
+        ];
+        (finite, any::<bool>(), any::<bool>(), any::<bool>()).prop_map(
+            |(finite, neg_inf, pos_inf, nan)| FloatIntervalAbstraction {
+                finite,
+                neg_inf,
+                pos_inf,
+                nan,
+            },
+        )
+    }
+
+    laws!(laws, float_interval_abstraction(), 7);
+}
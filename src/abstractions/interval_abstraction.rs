@@ -2,7 +2,7 @@ use core::f64;
 use std::{cmp::Ordering, ops};
 
 use crate::command_parser::{BExpr, Const};
-use crate::interpreter::{AbstractProperties, Bottom, Params, Top};
+use crate::interpreter::{AbstractProperties, Bottom, Top};
 
 const EPS: f64 = 1e-5;
 
@@ -26,6 +26,33 @@ pub enum IntervalAbstraction {
     Top,
 }
 
+impl IntervalAbstraction {
+    /// The bounds of the abstraction, where `Top` is bounded by the smallest and the largest
+    /// float. `Bottom` has no bounds.
+    fn bounds(self) -> Option<(f64, f64)> {
+        match self {
+            Self::Bottom => None,
+            Self::Interval(Interval { a, b }) => Some((a, b)),
+            Self::Top => Some((f64::MIN, f64::MAX)),
+        }
+    }
+
+    /// The smallest interval that contains all `bounds`, which is `Top` if one of them is NaN,
+    /// e.g. the difference of two infinite bounds.
+    fn hull(bounds: &[f64]) -> Self {
+        if bounds.iter().any(|bound| bound.is_nan()) {
+            Self::Top
+        } else {
+            (min_of(bounds), max_of(bounds)).into()
+        }
+    }
+
+    /// The integer parts of the numbers in the abstraction.
+    fn trunc(self) -> Option<(f64, f64)> {
+        self.bounds().map(|(a, b)| (a.trunc(), b.trunc()))
+    }
+}
+
 impl ops::Add for IntervalAbstraction {
     type Output = IntervalAbstraction;
     fn add(self, rhs: Self) -> Self::Output {
@@ -42,10 +69,7 @@ impl ops::Add for IntervalAbstraction {
                 Self::Bottom => Self::Bottom,
                 Self::Top => Self::Top,
                 Self::Interval(Interval { a: rhs_a, b: rhs_b }) => {
-                    let a = self_a + rhs_a;
-                    let b = self_b + rhs_b;
-
-                    (f64::min(a, b), f64::max(a, b)).into()
+                    Self::hull(&[self_a + rhs_a, self_b + rhs_b])
                 }
             },
         }
@@ -68,10 +92,7 @@ impl ops::Sub for IntervalAbstraction {
                 Self::Bottom => Self::Bottom,
                 Self::Top => Self::Top,
                 Self::Interval(Interval { a: rhs_a, b: rhs_b }) => {
-                    let a = self_a - rhs_a;
-                    let b = self_b - rhs_b;
-
-                    (f64::min(a, b), f64::max(a, b)).into()
+                    Self::hull(&[self_a - rhs_b, self_b - rhs_a])
                 }
             },
         }
@@ -94,10 +115,14 @@ impl ops::Mul for IntervalAbstraction {
                 Self::Bottom => Self::Bottom,
                 Self::Top => Self::Top,
                 Self::Interval(Interval { a: rhs_a, b: rhs_b }) => {
-                    let a = self_a * rhs_a;
-                    let b = self_b * rhs_b;
-
-                    (f64::min(a, b), f64::max(a, b)).into()
+                    let products = [
+                        self_a * rhs_a,
+                        self_a * rhs_b,
+                        self_b * rhs_a,
+                        self_b * rhs_b,
+                    ];
+
+                    Self::hull(&products)
                 }
             },
         }
@@ -120,9 +145,19 @@ impl ops::Div for IntervalAbstraction {
                 Self::Bottom => Self::Bottom,
                 Self::Top => Self::Top,
                 Self::Interval(Interval { a: rhs_a, b: rhs_b }) => {
-                    let a = self_a / rhs_a;
-                    let b = self_b / rhs_b;
-                    (f64::min(a, b), f64::max(a, b)).into()
+                    if rhs_a <= 0.0 && 0.0 <= rhs_b {
+                        // the divisor may be zero
+                        return Self::Top;
+                    }
+
+                    let quotients = [
+                        self_a / rhs_a,
+                        self_a / rhs_b,
+                        self_b / rhs_a,
+                        self_b / rhs_b,
+                    ];
+
+                    Self::hull(&quotients)
                 }
             },
         }
@@ -134,13 +169,9 @@ impl ops::Neg for IntervalAbstraction {
 
     fn neg(self) -> Self::Output {
         match self {
-            Self::Bottom => Self::Top,
-            Self::Top => Self::Bottom,
-            Self::Interval(Interval { a, b }) => {
-                let a = -a;
-                let b = -b;
-                (f64::min(a, b), f64::max(a, b)).into()
-            }
+            Self::Bottom => Self::Bottom,
+            Self::Top => Self::Top,
+            Self::Interval(Interval { a, b }) => (-b, -a).into(),
         }
     }
 }
@@ -154,10 +185,7 @@ impl From<f64> for IntervalAbstraction {
 impl PartialEq for IntervalAbstraction {
     fn eq(&self, other: &Self) -> bool {
         match self {
-            Self::Bottom => match other {
-                Self::Bottom => true,
-                _ => false,
-            },
+            Self::Bottom => matches!(other, Self::Bottom),
             Self::Interval(Interval {
                 a: a_self,
                 b: b_self,
@@ -166,19 +194,12 @@ impl PartialEq for IntervalAbstraction {
                 Self::Interval(Interval {
                     a: a_other,
                     b: b_other,
-                }) => (a_self - a_other).abs() < EPS && (b_self - b_other).abs() < EPS,
+                }) => close(*a_self, *a_other) && close(*b_self, *b_other),
                 Self::Top => false,
             },
-            Self::Top => match other {
-                Self::Top => true,
-                _ => false,
-            },
+            Self::Top => matches!(other, Self::Top),
         }
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        !IntervalAbstraction::eq(self, other)
-    }
 }
 
 impl PartialOrd for IntervalAbstraction {
@@ -248,17 +269,14 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
                 BExpr::GT(_, Const::Const(number)) => *number < b,
                 BExpr::LE(_, Const::Const(number)) => a <= *number,
                 BExpr::LT(_, Const::Const(number)) => a < *number,
-                BExpr::NE(_, Const::Const(number)) => *number < a || b < *number,
+                BExpr::NE(_, Const::Const(number)) => a != *number || b != *number,
             },
         }
     }
 
     fn first_includes_second(a0: &IntervalAbstraction, a1: &IntervalAbstraction) -> bool {
         match a0 {
-            Self::Bottom => match a1 {
-                Self::Bottom => true,
-                _ => false,
-            },
+            Self::Bottom => matches!(a1, Self::Bottom),
             Self::Top => true,
             Self::Interval(Interval { a: a0_a, b: a0_b }) => match a1 {
                 Self::Bottom => true,
@@ -276,19 +294,31 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
                 Self::Bottom => *a0,
                 Self::Top => Self::Top,
                 Self::Interval(Interval { a: a1_a, b: a1_b }) => {
-                    let a = f64::min(*a0_a, *a1_a);
-                    let b = f64::max(*a0_b, *a1_b);
-
-                    if a == f64::MIN && b == f64::MAX {
-                        Self::Top
-                    } else {
-                        (a, b).into()
-                    }
+                    (f64::min(*a0_a, *a1_a), f64::max(*a0_b, *a1_b)).into()
                 }
             },
         }
     }
 
+    fn meet(a0: &IntervalAbstraction, a1: &IntervalAbstraction) -> IntervalAbstraction {
+        match (a0, a1) {
+            (_, Self::Top) => return *a0,
+            (Self::Top, _) => return *a1,
+            _ => (),
+        }
+        match (a0.bounds(), a1.bounds()) {
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => {
+                let (a, b) = (f64::max(a0_a, a1_a), f64::min(a0_b, a1_b));
+                if a <= b {
+                    (a, b).into()
+                } else {
+                    Self::Bottom
+                }
+            }
+            _ => Self::Bottom,
+        }
+    }
+
     fn refine(a: &IntervalAbstraction, bexpr: &BExpr) -> IntervalAbstraction {
         if !Self::sat(a, bexpr) {
             return Self::Bottom;
@@ -305,7 +335,7 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
                 BExpr::LT(_, Const::Const(number)) => (f64::MIN, *number - EPS).into(),
                 BExpr::GE(_, Const::Const(number)) => (*number, f64::MAX).into(),
                 BExpr::GT(_, Const::Const(number)) => (*number + EPS, f64::MAX).into(),
-                BExpr::NE(_, Const::Const(_)) => Self::Bottom,
+                BExpr::NE(_, Const::Const(_)) => Self::Top,
             },
             Self::Interval(Interval { a, b }) => match bexpr {
                 BExpr::EQ(_, Const::Const(number)) => {
@@ -341,18 +371,134 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
                         (*a, *b).into()
                     }
                 }
-                BExpr::NE(_, Const::Const(_)) => Self::Bottom,
+                BExpr::NE(_, Const::Const(_)) => (*a, *b).into(),
             },
         }
     }
 
+    /// The remainder is at most as large as the dividend and smaller than the largest divisor in
+    /// magnitude, e.g. `[0, 100] % [8, 8]` is `[0, 7]`. A dividend that is smaller than all
+    /// divisors is the remainder itself.
+    fn rem(a0: &IntervalAbstraction, a1: &IntervalAbstraction) -> IntervalAbstraction {
+        let ((a0_a, a0_b), (a1_a, a1_b)) = match (a0.trunc(), a1.trunc()) {
+            (Some(dividend), Some(divisor)) => (dividend, divisor),
+            _ => return Self::Bottom,
+        };
+
+        let largest = f64::max(a1_a.abs(), a1_b.abs());
+        if largest == 0.0 {
+            // the divisor is zero
+            return Self::Top;
+        }
+        if a0_a == a0_b && a1_a == a1_b {
+            return Self::hull(&[a0_a % a1_a]);
+        }
+
+        let smallest = match a1_a <= 0.0 && 0.0 <= a1_b {
+            true => 1.0,
+            false => f64::min(a1_a.abs(), a1_b.abs()),
+        };
+        if f64::max(a0_a.abs(), a0_b.abs()) < smallest {
+            return (a0_a, a0_b).into();
+        }
+
+        let a = if a0_a >= 0.0 {
+            0.0
+        } else {
+            f64::max(a0_a, 1.0 - largest)
+        };
+        let b = if a0_b <= 0.0 {
+            0.0
+        } else {
+            f64::min(a0_b, largest - 1.0)
+        };
+
+        (a, b).into()
+    }
+
+    /// The quotient of the integer parts is at most as large as the dividend in magnitude, since
+    /// the divisor is at least 1 in magnitude.
+    fn int_div(a0: &IntervalAbstraction, a1: &IntervalAbstraction) -> IntervalAbstraction {
+        let ((a0_a, a0_b), (a1_a, a1_b)) = match (a0.trunc(), a1.trunc()) {
+            (Some(dividend), Some(divisor)) => (dividend, divisor),
+            _ => return Self::Bottom,
+        };
+
+        if a1_a == 0.0 && a1_b == 0.0 {
+            // the divisor is zero
+            return Self::Top;
+        }
+        if a1_a < 0.0 && 0.0 < a1_b {
+            let largest = f64::max(a0_a.abs(), a0_b.abs());
+            return (-largest, largest).into();
+        }
+
+        // a division by zero has no result
+        let divisor: IntervalAbstraction = match (a1_a, a1_b) {
+            (0.0, b) => (1.0, b).into(),
+            (a, 0.0) => (a, -1.0).into(),
+            (a, b) => (a, b).into(),
+        };
+        match (IntervalAbstraction::from((a0_a, a0_b)) / divisor).bounds() {
+            Some((a, b)) => (a.trunc(), b.trunc()).into(),
+            None => Self::Bottom,
+        }
+    }
+
+    fn min(a0: &IntervalAbstraction, a1: &IntervalAbstraction) -> IntervalAbstraction {
+        match (a0.bounds(), a1.bounds()) {
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => {
+                (f64::min(a0_a, a1_a), f64::min(a0_b, a1_b)).into()
+            }
+            _ => Self::Bottom,
+        }
+    }
+
+    fn max(a0: &IntervalAbstraction, a1: &IntervalAbstraction) -> IntervalAbstraction {
+        match (a0.bounds(), a1.bounds()) {
+            (Some((a0_a, a0_b)), Some((a1_a, a1_b))) => {
+                (f64::max(a0_a, a1_a), f64::max(a0_b, a1_b)).into()
+            }
+            _ => Self::Bottom,
+        }
+    }
+
+    fn abs(a: &IntervalAbstraction) -> IntervalAbstraction {
+        match a.bounds() {
+            None => Self::Bottom,
+            Some((a, b)) if a >= 0.0 => (a, b).into(),
+            Some((a, b)) if b <= 0.0 => (-b, -a).into(),
+            Some((a, b)) => (0.0, f64::max(-a, b)).into(),
+        }
+    }
+
+    /// Shifts the interval by a multiple of the number of values in `[min, max]` if it fits into
+    /// the range afterwards, and returns the whole range otherwise.
+    fn wrap(a: &IntervalAbstraction, min: f64, max: f64) -> IntervalAbstraction {
+        let (a, b) = match a.bounds() {
+            Some(bounds) => bounds,
+            None => return Self::Bottom,
+        };
+        if min <= a && b <= max {
+            return (a, b).into();
+        }
+
+        let size = max - min + 1.0;
+        let k = ((a - min) / size).floor();
+        if k == ((b - min) / size).floor() && k.abs() < 2f64.powi(52) {
+            (a - k * size, b - k * size).into()
+        } else {
+            (min, max).into()
+        }
+    }
+
     fn widen(
         a0: &IntervalAbstraction,
         a1: &IntervalAbstraction,
         widening_treshold: &IntervalAbstraction,
     ) -> IntervalAbstraction {
         match a0 {
-            IntervalAbstraction::Bottom => IntervalAbstraction::Top,
+            IntervalAbstraction::Bottom => *a1,
             IntervalAbstraction::Top => IntervalAbstraction::Top,
             IntervalAbstraction::Interval(Interval { a: a0_a, b: a0_b }) => match a1 {
                 IntervalAbstraction::Bottom => *a0,
@@ -363,7 +509,10 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
                             IntervalAbstraction::Top
                         } else {
                             match widening_treshold {
-                                IntervalAbstraction::Top => (f64::MIN, *a0_b).into(),
+                                // an infinite bound stays infinite
+                                IntervalAbstraction::Top => {
+                                    (f64::min(f64::MIN, *a1_a), *a0_b).into()
+                                }
                                 IntervalAbstraction::Bottom => *a0,
                                 IntervalAbstraction::Interval(Interval { a: t_a, b: _ }) => {
                                     (*t_a, *a0_b).into()
@@ -373,7 +522,10 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
                     } else {
                         if *a0_b < *a1_b {
                             match widening_treshold {
-                                IntervalAbstraction::Top => (*a0_a, f64::MAX).into(),
+                                // an infinite bound stays infinite
+                                IntervalAbstraction::Top => {
+                                    (*a0_a, f64::max(f64::MAX, *a1_b)).into()
+                                }
                                 IntervalAbstraction::Bottom => *a0,
                                 IntervalAbstraction::Interval(Interval { a: _, b: t_b }) => {
                                     (*a0_a, *t_b).into()
@@ -389,23 +541,44 @@ impl AbstractProperties<IntervalAbstraction> for IntervalAbstraction {
     }
 }
 
+/// Whether the bounds are equal up to `EPS`, where infinite bounds are only close to themselves.
+fn close(bound0: f64, bound1: f64) -> bool {
+    bound0 == bound1 || (bound0 - bound1).abs() < EPS
+}
+
+fn min_of(bounds: &[f64]) -> f64 {
+    bounds.iter().copied().fold(f64::INFINITY, f64::min)
+}
+
+fn max_of(bounds: &[f64]) -> f64 {
+    bounds.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+}
+
+/// The interval between `a` and `b`, which is `Top` if it covers all floats.
 impl From<(f64, f64)> for IntervalAbstraction {
     fn from((a, b): (f64, f64)) -> Self {
-        IntervalAbstraction::Interval(Interval::new(a, b))
+        if a <= f64::MIN && b >= f64::MAX {
+            IntervalAbstraction::Top
+        } else {
+            IntervalAbstraction::Interval(Interval::new(a, b))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::command_parser::parse;
+    use crate::abstractions::laws::laws;
+    use crate::command_parser::{parse, IntType, Var};
+    use crate::interpreter::{Alarm, Params};
     use crate::MemoryState;
+    use proptest::prelude::*;
     use std::collections::HashMap;
 
     #[test]
     fn example_3_13() {
         let program = "if (x > 7) {y := x - 7} else {y := 7 - x}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -420,7 +593,7 @@ mod tests {
     #[test]
     fn figure_5_4_without_unrolling_with_x_initialized_before() {
         let program = "i := 1; while (i > 0) {if (x < 0) {x := 0} else {x := 1 + x}; if (x > 1000) {x := 0} else {x := x + 1}; input(i)}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> =
             MemoryState::from_state(HashMap::from([("x".to_string(), IntervalAbstraction::Top)]));
@@ -437,7 +610,7 @@ mod tests {
     #[test]
     fn figure_5_4_with_unrolling_with_x_initialized_in_the_loop_body_default_behavior() {
         let program = "i := 1; while (i > 0) {if (x < 0) {x := 0} else {x := 1 + x}; if (x > 1000) {x := 0} else {x := x + 1}; input(i)}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
 
@@ -453,7 +626,7 @@ mod tests {
     #[test]
     fn figure_5_4_with_unrolling() {
         let program = "i := 1; while (i > 0) {if (x < 0) {x := 0} else {x := 1 + x}; if (x > 1000) {x := 0} else {x := x + 1}; input(i)}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
 
@@ -462,6 +635,11 @@ mod tests {
             use_widening: false,
             widening_delays: 0,
             widening_treshold: IntervalAbstraction::Top,
+            partition_branches: false,
+            partition_iterations: 0,
+            call_string_length: 0,
+            array_segmentation: false,
+            strict_initialization: false,
         };
 
         let post_analyzed = pre.analyze_command(&command, &params);
@@ -475,22 +653,19 @@ mod tests {
     fn figure_5_5_b_without_widening() {
         let program = "x := 0; while (x <= 100) {if (x >= 50) {x := 10} else {x := x + 1}}";
 
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
 
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
-        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
-
-        let x_truth: IntervalAbstraction = IntervalAbstraction::Bottom;
-        assert_eq!(x_truth, *x_analyzed);
+        assert!(post_analyzed.is_unreachable());
     }
 
     #[test]
     fn figure_5_5_b_with_widening() {
         let program = "x := 0; while (x <= 100) {if (x >= 50) {x := 10} else {x := x + 1}}";
 
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
 
@@ -499,12 +674,17 @@ mod tests {
             loop_unrollings: 0,
             widening_delays: 0,
             widening_treshold: IntervalAbstraction::Top,
+            partition_branches: false,
+            partition_iterations: 0,
+            call_string_length: 0,
+            array_segmentation: false,
+            strict_initialization: false,
         };
 
         let post_analyzed = pre.analyze_command(&command, &params);
         let x_analyzed = post_analyzed.lookup_var("x").unwrap();
 
-        let x_truth: IntervalAbstraction = (100.0, f64::MAX).into();
+        let x_truth: IntervalAbstraction = (101.0, f64::MAX).into();
         assert_eq!(x_truth, *x_analyzed);
     }
 
@@ -512,7 +692,7 @@ mod tests {
     fn figure_5_5_b_with_delayed_widening() {
         let program = "x := 0; while (x <= 100) {if (x >= 50) {x := 10} else {x := 1 + x}}";
 
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
 
@@ -521,20 +701,22 @@ mod tests {
             loop_unrollings: 0,
             widening_delays: 51,
             widening_treshold: IntervalAbstraction::Top,
+            partition_branches: false,
+            partition_iterations: 0,
+            call_string_length: 0,
+            array_segmentation: false,
+            strict_initialization: false,
         };
 
         let post_analyzed = pre.analyze_command(&command, &params);
-        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
-
-        let x_truth = IntervalAbstraction::Bottom;
-        assert_eq!(x_truth, *x_analyzed);
+        assert!(post_analyzed.is_unreachable());
     }
 
     #[test]
     fn figure_5_5_b_with_widening_treshold() {
         let program = "x := 0; while (x <= 100) {if (x >= 50) {x := 10} else {x := x + 1}}";
 
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
 
@@ -543,19 +725,21 @@ mod tests {
             loop_unrollings: 0,
             widening_delays: 0,
             widening_treshold: (-50.0, 50.0).into(),
+            partition_branches: false,
+            partition_iterations: 0,
+            call_string_length: 0,
+            array_segmentation: false,
+            strict_initialization: false,
         };
 
         let post_analyzed = pre.analyze_command(&command, &params);
-        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
-
-        let x_truth = IntervalAbstraction::Bottom;
-        assert_eq!(x_truth, *x_analyzed);
+        assert!(post_analyzed.is_unreachable());
     }
 
     #[test]
     fn figure_5_2_coalescent_product_domain() {
         let program = "x := 8; y := 1; if (x < 0) {y := 0} else {skip}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -566,4 +750,267 @@ mod tests {
         ]));
         assert_eq!(post_truth, *post_analyzed);
     }
+
+    #[test]
+    fn strict_guards_on_integers() {
+        let program = "input(x); if (x < 10) {y := x} else {y := 0}; input(z); z := z + 0.5; if (z < 10) {w := z} else {w := 0}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        // the guard is `x <= 9` for the integer variable x, but not for the float variable z
+        assert_eq!(Some(&(f64::MIN, 9.0).into()), post_analyzed.lookup_var("y"));
+        assert_eq!(
+            Some(&(f64::MIN, 10.0).into()),
+            post_analyzed.lookup_var("w")
+        );
+    }
+
+    #[test]
+    fn declared_types() {
+        let program = "var b: bool; var f: float; input(b); input(f); if (f < 10) {y := f} else {y := 0}; if (!b) {z := b} else {z := 2}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        // booleans are 0 or 1, the guard `f < 10` is not strengthened for the float f
+        assert_eq!(Some(&(0.0, 1.0).into()), post_analyzed.lookup_var("b"));
+        assert_eq!(
+            Some(&(f64::MIN, 10.0).into()),
+            post_analyzed.lookup_var("y")
+        );
+        assert_eq!(Some(&(0.0, 2.0).into()), post_analyzed.lookup_var("z"));
+    }
+
+    #[test]
+    fn undefined_operations_on_infinite_bounds() {
+        let program = "x := 1e308 * 10; y := x - x; z := 0 * x; q := x / x";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            Some(&(f64::INFINITY, f64::INFINITY).into()),
+            post_analyzed.lookup_var("x")
+        );
+        assert_eq!(
+            Some(&IntervalAbstraction::Top),
+            post_analyzed.lookup_var("y")
+        );
+        assert_eq!(
+            Some(&IntervalAbstraction::Top),
+            post_analyzed.lookup_var("z")
+        );
+        assert_eq!(
+            Some(&IntervalAbstraction::Top),
+            post_analyzed.lookup_var("q")
+        );
+    }
+
+    #[test]
+    fn division_yields_floats() {
+        let program = "x := 7 / 2; if (x > 3) {y := 1} else {y := 0}; i := 4; if (i > 3.5) {j := i} else {j := 0}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(Some(&(3.5, 3.5).into()), post_analyzed.lookup_var("x"));
+        assert_eq!(Some(&(1.0, 1.0).into()), post_analyzed.lookup_var("y"));
+        assert_eq!(Some(&(4.0, 4.0).into()), post_analyzed.lookup_var("j"));
+    }
+
+    #[test]
+    fn integer_operators() {
+        let program = "input(x); if (x >= 0) {r := x % 8; q := x div 8} else {r := 0; q := 0}; input(y, -5, 3); a := abs(y); m := min(y, 0); n := max(y, 2); s := 3 % (0 - 5); t := (0 - 7) div 2";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(Some(&(0.0, 7.0).into()), post_analyzed.lookup_var("r"));
+        assert_eq!(
+            Some(&(0.0, f64::MAX / 8.0).into()),
+            post_analyzed.lookup_var("q")
+        );
+        assert_eq!(Some(&(0.0, 5.0).into()), post_analyzed.lookup_var("a"));
+        assert_eq!(Some(&(-5.0, 0.0).into()), post_analyzed.lookup_var("m"));
+        assert_eq!(Some(&(2.0, 3.0).into()), post_analyzed.lookup_var("n"));
+        assert_eq!(Some(&(3.0, 3.0).into()), post_analyzed.lookup_var("s"));
+        assert_eq!(Some(&(-3.0, -3.0).into()), post_analyzed.lookup_var("t"));
+    }
+
+    #[test]
+    fn remainder_of_floats() {
+        let program = "x := 7.5 % 2; y := 7.5 div 2";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(Some(&(1.0, 1.0).into()), post.lookup_var("x"));
+        assert_eq!(Some(&(3.0, 3.0).into()), post.lookup_var("y"));
+    }
+
+    #[test]
+    fn assertions() {
+        let program = "input(x, 0, 10); assert(x <= 10);\nassert(x < 5); assert(x < 5)";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        // the analysis continues with the states that satisfy the assertion
+        assert_eq!(Some(&(0.0, 4.0).into()), post_analyzed.lookup_var("x"));
+        assert_eq!(
+            vec![Alarm::Assertion(
+                34,
+                BExpr::LT(Var::Var("x".to_string()), Const::Const(5.0))
+            )],
+            post_analyzed.alarms()
+        );
+    }
+
+    #[test]
+    fn wrapping_integers() {
+        let program = "var x: wrapping u8; input(y, 250, 260); x := y; var z: wrapping i8; z := 127;\nz := z + 1; input(w, 0, 255); var v: wrapping u8; v := w + 256";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(Some(&(0.0, 255.0).into()), post_analyzed.lookup_var("x"));
+        assert_eq!(
+            Some(&(-128.0, -128.0).into()),
+            post_analyzed.lookup_var("z")
+        );
+        assert_eq!(Some(&(0.0, 255.0).into()), post_analyzed.lookup_var("v"));
+
+        let u8 = IntType {
+            signed: false,
+            bits: 8,
+            wrapping: true,
+        };
+        let i8 = IntType { signed: true, ..u8 };
+        assert_eq!(
+            vec![
+                Alarm::Overflow(40, "x".to_string(), u8),
+                Alarm::Overflow(78, "z".to_string(), i8),
+                Alarm::Overflow(128, "v".to_string(), u8)
+            ],
+            post_analyzed.alarms()
+        );
+    }
+
+    #[test]
+    fn trapping_integers() {
+        let program = "var x: u8; input(x); x := x + 1; y := x";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(Some(&(1.0, 255.0).into()), post_analyzed.lookup_var("y"));
+        assert_eq!(1, post_analyzed.alarms().len());
+
+        let program = "var c: i16; c := 32767; c := c + 1; d := 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert!(post_analyzed.is_unreachable());
+        assert_eq!(None, post_analyzed.lookup_var("d"));
+    }
+
+    #[test]
+    fn interprete_wrapping_integers() {
+        let program = "var x: wrapping u8; x := 255; x := x + 1; var y: wrapping i32; y := 0 - 2147483648 - 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(Some(&(0.0, 0.0).into()), post.lookup_var("x"));
+        assert_eq!(
+            Some(&(2147483647.0, 2147483647.0).into()),
+            post.lookup_var("y")
+        );
+    }
+
+    #[test]
+    fn interprete_trapping_integers() {
+        let program = "var x: u8; x := 0; while (x < 5) {x := x - 1}; y := 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        // the execution stops at the overflow
+        assert!(post.is_unreachable());
+        assert_eq!(None, post.lookup_var("y"));
+        let u8 = IntType {
+            signed: false,
+            bits: 8,
+            wrapping: false,
+        };
+        assert_eq!(
+            vec![Alarm::Overflow(34, "x".to_string(), u8)],
+            post.alarms()
+        );
+    }
+
+    #[test]
+    fn bounded_input() {
+        let program = "input(x, 0, 10); y := x * 2";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), (0.0, 10.0).into()),
+            ("y".to_string(), (0.0, 20.0).into()),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+
+        for _ in 0..20 {
+            let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+            let post = pre.interprete_command(&command);
+
+            let x = post.lookup_var("x").unwrap();
+            assert!(IntervalAbstraction::first_includes_second(
+                &(0.0, 10.0).into(),
+                x
+            ));
+        }
+    }
+
+    #[test]
+    fn choose_joins_both_branches() {
+        let program = "choose {x := 1} or {x := 5}; y := x - 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), (1.0, 5.0).into()),
+            ("y".to_string(), (0.0, 4.0).into()),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    fn interval_abstraction() -> impl Strategy<Value = IntervalAbstraction> + Clone {
+        prop_oneof![
+            Just(IntervalAbstraction::Bottom),
+            Just(IntervalAbstraction::Top),
+            crate::abstractions::laws::bounds().prop_map(IntervalAbstraction::from),
+        ]
+    }
+
+    laws!(laws, interval_abstraction(), 4);
 }
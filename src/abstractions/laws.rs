@@ -0,0 +1,323 @@
+//! Property tests that every abstraction has to satisfy. Each abstraction instantiates them in its
+//! own test module with `laws!` and a strategy that generates arbitrary abstract values.
+//!
+//! A concrete number `x` is contained in an abstract value `a` if `a` includes the abstraction of
+//! `x`. The concrete samples are integers, since the interval abstraction approximates strict
+//! bounds by `EPS`. The abstract values may have bounds that are the largest floats in magnitude
+//! or infinite, whose operations may be NaN.
+
+use std::fmt::Debug;
+use std::{cmp, ops};
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+
+use crate::command_parser::{BExpr, Const, Var};
+use crate::interpreter::{int_div, rem, wrap, AbstractProperties, Bottom, Top};
+
+/// Instantiates every law as a test in the module `$module` for the abstract values of
+/// `$strategy`, whose widening may change at most `$max_widenings` times along a chain.
+macro_rules! laws {
+    ($module:ident, $strategy:expr, $max_widenings:expr) => {
+        mod $module {
+            use super::*;
+            use $crate::abstractions::laws;
+
+            #[test]
+            fn partial_order() {
+                laws::partial_order($strategy);
+            }
+
+            #[test]
+            fn join_is_upper_bound() {
+                laws::join_is_upper_bound($strategy);
+            }
+
+            #[test]
+            fn meet_is_lower_bound() {
+                laws::meet_is_lower_bound($strategy);
+            }
+
+            #[test]
+            fn widening_terminates() {
+                laws::widening_terminates($strategy, $max_widenings);
+            }
+
+            #[test]
+            fn operators_are_sound() {
+                laws::operators_are_sound($strategy);
+            }
+
+            #[test]
+            fn refinement_is_sound() {
+                laws::refinement_is_sound($strategy);
+            }
+
+            #[test]
+            fn comparison_is_consistent() {
+                laws::comparison_is_consistent($strategy);
+            }
+        }
+    };
+}
+pub(crate) use laws;
+
+fn includes<A: AbstractProperties<A>>(a0: &A, a1: &A) -> bool {
+    A::first_includes_second(a0, a1)
+}
+
+fn contains<A: AbstractProperties<A> + From<f64>>(a: &A, number: f64) -> bool {
+    A::first_includes_second(a, &number.into())
+}
+
+fn bound() -> impl Strategy<Value = f64> + Clone {
+    prop_oneof![
+        (-1000i32..1000).prop_map(f64::from),
+        proptest::sample::select(vec![f64::MIN, f64::MAX, f64::NEG_INFINITY, f64::INFINITY]),
+    ]
+}
+
+/// Generates the bounds of an interval, which may be the largest floats in magnitude or
+/// infinite.
+pub fn bounds() -> impl Strategy<Value = (f64, f64)> + Clone {
+    prop_oneof![
+        (-1000i32..1000, 0i32..1000).prop_map(|(a, width)| (a as f64, (a + width) as f64)),
+        (bound(), bound()).prop_map(|(a, b)| (f64::min(a, b), f64::max(a, b))),
+    ]
+}
+
+/// Generates an abstract value together with a concrete number it contains.
+fn with_sample<A: AbstractProperties<A> + From<f64> + Debug + Clone>(
+    strategy: impl Strategy<Value = A>,
+) -> impl Strategy<Value = (A, f64)> {
+    (strategy, -1000i32..1000).prop_map(|(a, number)| {
+        let number = number as f64;
+        (A::join(&a, &number.into()), number)
+    })
+}
+
+pub fn partial_order<A>(strategy: impl Strategy<Value = A> + Clone)
+where
+    A: AbstractProperties<A> + From<Top> + From<Bottom> + PartialEq + Debug,
+{
+    let mut runner = TestRunner::default();
+    runner
+        .run(
+            &(strategy.clone(), strategy.clone(), strategy),
+            |(a0, a1, a2)| {
+                prop_assert!(includes(&a0, &a0));
+                prop_assert!(includes(&a0, &A::bottom().into()));
+                prop_assert!(includes(&A::top().into(), &a0));
+
+                if includes(&a0, &a1) && includes(&a1, &a0) {
+                    prop_assert_eq!(&a0, &a1);
+                }
+
+                if includes(&a0, &a1) && includes(&a1, &a2) {
+                    prop_assert!(includes(&a0, &a2));
+                }
+
+                Ok(())
+            },
+        )
+        .unwrap();
+}
+
+pub fn join_is_upper_bound<A>(strategy: impl Strategy<Value = A> + Clone)
+where
+    A: AbstractProperties<A> + From<Bottom> + PartialEq + Debug,
+{
+    let mut runner = TestRunner::default();
+    runner
+        .run(
+            &(strategy.clone(), strategy.clone(), strategy),
+            |(a0, a1, a2)| {
+                let joined = A::join(&a0, &a1);
+
+                prop_assert!(includes(&joined, &a0));
+                prop_assert!(includes(&joined, &a1));
+                prop_assert_eq!(&joined, &A::join(&a1, &a0));
+                prop_assert_eq!(&a0, &A::join(&a0, &a0));
+                prop_assert_eq!(&a0, &A::join(&a0, &A::bottom().into()));
+
+                if includes(&a2, &a0) && includes(&a2, &a1) {
+                    prop_assert!(includes(&a2, &joined));
+                }
+
+                Ok(())
+            },
+        )
+        .unwrap();
+}
+
+pub fn meet_is_lower_bound<A>(strategy: impl Strategy<Value = A> + Clone)
+where
+    A: AbstractProperties<A> + From<f64> + From<Top> + PartialEq + Copy + Debug,
+{
+    let mut runner = TestRunner::default();
+    runner
+        .run(
+            &(strategy.clone(), strategy, -1000i32..1000),
+            |(a0, a1, number)| {
+                let met = A::meet(&a0, &a1);
+
+                prop_assert!(includes(&a0, &met));
+                prop_assert!(includes(&a1, &met));
+                prop_assert_eq!(&a0, &A::meet(&a0, &A::top().into()));
+
+                // a number of both values is a number of their meet
+                let number = number as f64;
+                let (a0, a1) = (A::join(&a0, &number.into()), A::join(&a1, &number.into()));
+                prop_assert!(contains(&A::meet(&a0, &a1), number));
+
+                Ok(())
+            },
+        )
+        .unwrap();
+}
+
+/// Widens along arbitrary ascending chains and checks that the widened sequence is an upper
+/// bound of the chain that changes at most `max_widenings` times.
+pub fn widening_terminates<A>(strategy: impl Strategy<Value = A>, max_widenings: usize)
+where
+    A: AbstractProperties<A> + From<Top> + From<Bottom> + PartialEq + Debug + Clone,
+{
+    let mut runner = TestRunner::default();
+    runner
+        .run(&vec(strategy, 1..30), |increments| {
+            let treshold: A = A::top().into();
+            let mut chain: A = A::bottom().into();
+            let mut widened: A = A::bottom().into();
+            let mut nr_of_changes = 0;
+
+            for increment in &increments {
+                chain = A::join(&chain, increment);
+                let next = A::widen(&widened, &chain, &treshold);
+
+                prop_assert!(includes(&next, &widened));
+                prop_assert!(includes(&next, &chain));
+
+                if next != widened {
+                    nr_of_changes += 1;
+                }
+                widened = next;
+            }
+
+            prop_assert!(nr_of_changes <= max_widenings);
+
+            Ok(())
+        })
+        .unwrap();
+}
+
+pub fn operators_are_sound<A>(strategy: impl Strategy<Value = A> + Clone)
+where
+    A: ops::Add<Output = A>
+        + ops::Neg<Output = A>
+        + ops::Sub<Output = A>
+        + ops::Mul<Output = A>
+        + ops::Div<Output = A>
+        + AbstractProperties<A>
+        + From<f64>
+        + Copy
+        + Debug,
+{
+    let mut runner = TestRunner::default();
+    runner
+        .run(
+            &(with_sample(strategy.clone()), with_sample(strategy)),
+            |((a0, x0), (a1, x1))| {
+                prop_assert!(contains(&-a0, -x0));
+                prop_assert!(contains(&(a0 + a1), x0 + x1));
+                prop_assert!(contains(&(a0 - a1), x0 - x1));
+                prop_assert!(contains(&(a0 * a1), x0 * x1));
+
+                if x1 != 0.0 {
+                    prop_assert!(contains(&(a0 / a1), x0 / x1));
+                    prop_assert!(contains(&A::rem(&a0, &a1), rem(x0, x1)));
+                    prop_assert!(contains(&A::int_div(&a0, &a1), int_div(x0, x1)));
+                }
+
+                prop_assert!(contains(&A::min(&a0, &a1), f64::min(x0, x1)));
+                prop_assert!(contains(&A::max(&a0, &a1), f64::max(x0, x1)));
+                prop_assert!(contains(&A::abs(&a0), x0.abs()));
+                prop_assert!(contains(&A::unround(&a0), x0));
+
+                for (min, max) in [(-128.0, 127.0), (0.0, 255.0)] {
+                    prop_assert!(contains(&A::wrap(&a0, min, max), wrap(x0, min, max)));
+                }
+
+                Ok(())
+            },
+        )
+        .unwrap();
+}
+
+pub fn refinement_is_sound<A>(strategy: impl Strategy<Value = A>)
+where
+    A: AbstractProperties<A> + From<f64> + Copy + Debug,
+{
+    let guards = (0..6usize, -1000i32..1000).prop_map(|(op, number)| {
+        let var = Var::Var("x".to_string());
+        let number = Const::Const(number as f64);
+        match op {
+            0 => BExpr::EQ(var, number),
+            1 => BExpr::NE(var, number),
+            2 => BExpr::GE(var, number),
+            3 => BExpr::GT(var, number),
+            4 => BExpr::LE(var, number),
+            _ => BExpr::LT(var, number),
+        }
+    });
+
+    let mut runner = TestRunner::default();
+    runner
+        .run(&(with_sample(strategy), guards), |((a, x), bexpr)| {
+            let refined = A::refine(&a, &bexpr);
+            prop_assert!(includes(&a, &refined));
+
+            let satisfied = match &bexpr {
+                BExpr::EQ(_, Const::Const(number)) => x == *number,
+                BExpr::NE(_, Const::Const(number)) => x != *number,
+                BExpr::GE(_, Const::Const(number)) => x >= *number,
+                BExpr::GT(_, Const::Const(number)) => x > *number,
+                BExpr::LE(_, Const::Const(number)) => x <= *number,
+                BExpr::LT(_, Const::Const(number)) => x < *number,
+            };
+
+            if satisfied {
+                prop_assert!(A::sat(&a, &bexpr));
+                prop_assert!(contains(&refined, x));
+            }
+
+            Ok(())
+        })
+        .unwrap();
+}
+
+/// Checks that `PartialOrd` compares the represented numbers consistently: comparing in the
+/// other direction gives the reversed result and `a0 < a1` implies that no number of `a0` is
+/// greater than a number of `a1`.
+pub fn comparison_is_consistent<A>(strategy: impl Strategy<Value = A> + Clone)
+where
+    A: AbstractProperties<A> + From<f64> + From<Top> + cmp::PartialOrd + Copy + Debug,
+{
+    let mut runner = TestRunner::default();
+    runner
+        .run(
+            &(with_sample(strategy.clone()), with_sample(strategy)),
+            |((a0, x0), (a1, x1))| {
+                let ordering = a0.partial_cmp(&a1);
+                prop_assert_eq!(ordering.map(cmp::Ordering::reverse), a1.partial_cmp(&a0));
+
+                let top: A = A::top().into();
+                if ordering == Some(cmp::Ordering::Less) && a0 != top && a1 != top {
+                    prop_assert!(x0 <= x1);
+                }
+
+                Ok(())
+            },
+        )
+        .unwrap();
+}
@@ -0,0 +1,274 @@
+use std::{cmp::Ordering, ops};
+
+use crate::command_parser::{BExpr, Const};
+use crate::interpreter::{AbstractProperties, Bottom, Top};
+
+/// The parity of integer values. Numbers that are not integers are only represented by `Top`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ParityAbstraction {
+    Bottom,
+    Even,
+    Odd,
+    Top,
+}
+
+impl ParityAbstraction {
+    /// Combines two parities if both are known and returns `Top` otherwise.
+    fn combine(self, rhs: Self, op: fn(bool, bool) -> bool) -> Self {
+        match (self, rhs) {
+            (Self::Bottom, _) | (_, Self::Bottom) => Self::Bottom,
+            (Self::Top, _) | (_, Self::Top) => Self::Top,
+            _ => {
+                if op(self == Self::Odd, rhs == Self::Odd) {
+                    Self::Odd
+                } else {
+                    Self::Even
+                }
+            }
+        }
+    }
+}
+
+impl ops::Add for ParityAbstraction {
+    type Output = ParityAbstraction;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.combine(rhs, |odd0, odd1| odd0 != odd1)
+    }
+}
+
+impl ops::Sub for ParityAbstraction {
+    type Output = ParityAbstraction;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.combine(rhs, |odd0, odd1| odd0 != odd1)
+    }
+}
+
+impl ops::Mul for ParityAbstraction {
+    type Output = ParityAbstraction;
+
+    /// An even factor makes the product even only if the other factor is an integer as well,
+    /// e.g. `2 * 0.5` is odd, so a factor `Top` makes the product `Top`.
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.combine(rhs, |odd0, odd1| odd0 && odd1)
+    }
+}
+
+impl ops::Div for ParityAbstraction {
+    type Output = ParityAbstraction;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        // a quotient of integers is in general not an integer
+        match (self, rhs) {
+            (Self::Bottom, _) | (_, Self::Bottom) => Self::Bottom,
+            _ => Self::Top,
+        }
+    }
+}
+
+impl ops::Neg for ParityAbstraction {
+    type Output = ParityAbstraction;
+
+    fn neg(self) -> Self::Output {
+        self
+    }
+}
+
+impl From<f64> for ParityAbstraction {
+    fn from(number: f64) -> Self {
+        if number.fract() != 0.0 || !number.is_finite() {
+            Self::Top
+        } else if number % 2.0 == 0.0 {
+            Self::Even
+        } else {
+            Self::Odd
+        }
+    }
+}
+
+impl From<Top> for ParityAbstraction {
+    fn from(_: Top) -> Self {
+        Self::Top
+    }
+}
+
+impl From<Bottom> for ParityAbstraction {
+    fn from(_: Bottom) -> Self {
+        Self::Bottom
+    }
+}
+
+impl PartialOrd for ParityAbstraction {
+    /// The parity does not order numbers, so only `Bottom` and `Top` are comparable to other
+    /// abstractions, as the least and the greatest element.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            _ if self == other => Some(Ordering::Equal),
+            (Self::Bottom, _) | (_, Self::Top) => Some(Ordering::Less),
+            (Self::Top, _) | (_, Self::Bottom) => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+}
+
+impl AbstractProperties<ParityAbstraction> for ParityAbstraction {
+    fn bottom() -> Bottom {
+        Bottom
+    }
+
+    fn top() -> Top {
+        Top
+    }
+
+    fn meet(a0: &Self, a1: &Self) -> Self {
+        match (a0, a1) {
+            (Self::Top, _) => *a1,
+            (_, Self::Top) => *a0,
+            _ if a0 == a1 => *a0,
+            _ => Self::Bottom,
+        }
+    }
+
+    fn sat(a: &ParityAbstraction, bexpr: &BExpr) -> bool {
+        Self::refine(a, bexpr) != Self::Bottom
+    }
+
+    fn first_includes_second(a0: &ParityAbstraction, a1: &ParityAbstraction) -> bool {
+        Self::meet(a0, a1) == *a1
+    }
+
+    fn join(a0: &ParityAbstraction, a1: &ParityAbstraction) -> ParityAbstraction {
+        match (a0, a1) {
+            (Self::Bottom, _) => *a1,
+            (_, Self::Bottom) => *a0,
+            _ if a0 == a1 => *a0,
+            _ => Self::Top,
+        }
+    }
+
+    fn refine(a: &ParityAbstraction, bexpr: &BExpr) -> ParityAbstraction {
+        match bexpr {
+            BExpr::EQ(_, Const::Const(number)) => Self::meet(a, &(*number).into()),
+            _ => *a,
+        }
+    }
+
+    /// `x % y` differs from `x` by a multiple of `y`, so it has the parity of `x` if `y` is even.
+    fn rem(a0: &ParityAbstraction, a1: &ParityAbstraction) -> ParityAbstraction {
+        match (a0, a1) {
+            (Self::Bottom, _) | (_, Self::Bottom) => Self::Bottom,
+            (_, Self::Even) => *a0,
+            _ => Self::Top,
+        }
+    }
+
+    fn int_div(a0: &ParityAbstraction, a1: &ParityAbstraction) -> ParityAbstraction {
+        match (a0, a1) {
+            (Self::Bottom, _) | (_, Self::Bottom) => Self::Bottom,
+            _ => Self::Top,
+        }
+    }
+
+    fn min(a0: &ParityAbstraction, a1: &ParityAbstraction) -> ParityAbstraction {
+        match (a0, a1) {
+            (Self::Bottom, _) | (_, Self::Bottom) => Self::Bottom,
+            _ => Self::join(a0, a1),
+        }
+    }
+
+    fn max(a0: &ParityAbstraction, a1: &ParityAbstraction) -> ParityAbstraction {
+        Self::min(a0, a1)
+    }
+
+    fn abs(a: &ParityAbstraction) -> ParityAbstraction {
+        *a
+    }
+
+    /// Wrapping around subtracts a multiple of an even number of values, which keeps the parity.
+    fn wrap(a: &ParityAbstraction, _min: f64, _max: f64) -> ParityAbstraction {
+        *a
+    }
+
+    fn widen(
+        a0: &ParityAbstraction,
+        a1: &ParityAbstraction,
+        _widening_treshold: &ParityAbstraction,
+    ) -> ParityAbstraction {
+        Self::join(a0, a1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::laws::laws;
+    use crate::command_parser::parse;
+    use crate::interpreter::{MemoryState, Params};
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn loop_keeps_parity() {
+        let program = "x := 0; input(i); while (i > 0) {x := x + 2; input(i)}; y := x + 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), ParityAbstraction::Even),
+            ("y".to_string(), ParityAbstraction::Odd),
+            ("i".to_string(), ParityAbstraction::Top),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    #[test]
+    fn equality_guard() {
+        let program = "input(x); if (x == 3) {y := x * x} else {y := 2 * x}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        assert_eq!(ParityAbstraction::Top, *y_analyzed);
+
+        let program = "x := 4; if (x == 3) {y := 1} else {y := 2}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        assert_eq!(ParityAbstraction::Even, *y_analyzed);
+    }
+
+    #[test]
+    fn even_factor_of_a_non_integer() {
+        let program = "x := 2 * 0.5; y := (3 / 2) * 2; z := 2 * 3";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), ParityAbstraction::Top),
+            ("y".to_string(), ParityAbstraction::Top),
+            ("z".to_string(), ParityAbstraction::Even),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    fn parity_abstraction() -> impl Strategy<Value = ParityAbstraction> + Clone {
+        proptest::sample::select(vec![
+            ParityAbstraction::Bottom,
+            ParityAbstraction::Even,
+            ParityAbstraction::Odd,
+            ParityAbstraction::Top,
+        ])
+    }
+
+    laws!(laws, parity_abstraction(), 2);
+}
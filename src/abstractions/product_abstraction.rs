@@ -0,0 +1,388 @@
+use std::{cmp::Ordering, ops};
+
+use crate::abstractions::interval_abstraction::{Interval, IntervalAbstraction};
+use crate::abstractions::parity_abstraction::ParityAbstraction;
+use crate::abstractions::sign_abstraction::SignAbstraction;
+use crate::command_parser::BExpr;
+use crate::interpreter::{AbstractProperties, Bottom, Top};
+
+/// Sharpens the components of a product with the information of the respective other component.
+pub trait Reduction<A1>: Sized {
+    fn reduce(a0: &Self, a1: &A1) -> (Self, A1);
+}
+
+/// The reduced product of two abstractions: a value is represented if it is represented by both
+/// components. All operations are applied componentwise and the result is reduced afterwards.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ProductAbstraction<A0, A1>(pub A0, pub A1);
+
+pub type IntervalParityAbstraction = ProductAbstraction<IntervalAbstraction, ParityAbstraction>;
+
+pub type SignParityAbstraction = ProductAbstraction<SignAbstraction, ParityAbstraction>;
+
+impl<A0: Reduction<A1>, A1> ProductAbstraction<A0, A1> {
+    pub fn reduced(a0: A0, a1: A1) -> Self {
+        let (a0, a1) = A0::reduce(&a0, &a1);
+        ProductAbstraction(a0, a1)
+    }
+}
+
+impl<A0, A1> ops::Add for ProductAbstraction<A0, A1>
+where
+    A0: ops::Add<Output = A0> + Reduction<A1>,
+    A1: ops::Add<Output = A1>,
+{
+    type Output = ProductAbstraction<A0, A1>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::reduced(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl<A0, A1> ops::Sub for ProductAbstraction<A0, A1>
+where
+    A0: ops::Sub<Output = A0> + Reduction<A1>,
+    A1: ops::Sub<Output = A1>,
+{
+    type Output = ProductAbstraction<A0, A1>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::reduced(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl<A0, A1> ops::Mul for ProductAbstraction<A0, A1>
+where
+    A0: ops::Mul<Output = A0> + Reduction<A1>,
+    A1: ops::Mul<Output = A1>,
+{
+    type Output = ProductAbstraction<A0, A1>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::reduced(self.0 * rhs.0, self.1 * rhs.1)
+    }
+}
+
+impl<A0, A1> ops::Div for ProductAbstraction<A0, A1>
+where
+    A0: ops::Div<Output = A0> + Reduction<A1>,
+    A1: ops::Div<Output = A1>,
+{
+    type Output = ProductAbstraction<A0, A1>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self::reduced(self.0 / rhs.0, self.1 / rhs.1)
+    }
+}
+
+impl<A0, A1> ops::Neg for ProductAbstraction<A0, A1>
+where
+    A0: ops::Neg<Output = A0> + Reduction<A1>,
+    A1: ops::Neg<Output = A1>,
+{
+    type Output = ProductAbstraction<A0, A1>;
+
+    fn neg(self) -> Self::Output {
+        Self::reduced(-self.0, -self.1)
+    }
+}
+
+impl<A0: From<f64> + Reduction<A1>, A1: From<f64>> From<f64> for ProductAbstraction<A0, A1> {
+    fn from(number: f64) -> Self {
+        Self::reduced(number.into(), number.into())
+    }
+}
+
+impl<A0: From<Top>, A1: From<Top>> From<Top> for ProductAbstraction<A0, A1> {
+    fn from(_: Top) -> Self {
+        ProductAbstraction(Top.into(), Top.into())
+    }
+}
+
+impl<A0: From<Bottom>, A1: From<Bottom>> From<Bottom> for ProductAbstraction<A0, A1> {
+    fn from(_: Bottom) -> Self {
+        ProductAbstraction(Bottom.into(), Bottom.into())
+    }
+}
+
+impl<A0, A1> PartialOrd for ProductAbstraction<A0, A1>
+where
+    A0: PartialOrd + From<Top> + From<Bottom>,
+    A1: PartialEq + From<Top> + From<Bottom>,
+    Self: From<Top> + From<Bottom>,
+{
+    /// Compares the numbers represented by the first component, unless it is `Bottom` or `Top`
+    /// while the product is not.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let bottom: Self = Bottom.into();
+        let top: Self = Top.into();
+        let component_is_bounded = |a: &Self| {
+            let (bottom, top): (A0, A0) = (Bottom.into(), Top.into());
+            a.0 != bottom && a.0 != top
+        };
+
+        match (self, other) {
+            _ if self == other => Some(Ordering::Equal),
+            _ if *self == bottom || *other == top => Some(Ordering::Less),
+            _ if *self == top || *other == bottom => Some(Ordering::Greater),
+            _ if component_is_bounded(self) && component_is_bounded(other) => {
+                match self.0.partial_cmp(&other.0) {
+                    Some(Ordering::Equal) => None,
+                    ordering => ordering,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<A0, A1> AbstractProperties<ProductAbstraction<A0, A1>> for ProductAbstraction<A0, A1>
+where
+    A0: AbstractProperties<A0> + Reduction<A1>,
+    A1: AbstractProperties<A1>,
+{
+    fn bottom() -> Bottom {
+        Bottom
+    }
+
+    fn top() -> Top {
+        Top
+    }
+
+    fn sat(a: &Self, bexpr: &BExpr) -> bool {
+        A0::sat(&a.0, bexpr) && A1::sat(&a.1, bexpr)
+    }
+
+    fn first_includes_second(a0: &Self, a1: &Self) -> bool {
+        A0::first_includes_second(&a0.0, &a1.0) && A1::first_includes_second(&a0.1, &a1.1)
+    }
+
+    fn join(a0: &Self, a1: &Self) -> Self {
+        Self::reduced(A0::join(&a0.0, &a1.0), A1::join(&a0.1, &a1.1))
+    }
+
+    fn meet(a0: &Self, a1: &Self) -> Self {
+        Self::reduced(A0::meet(&a0.0, &a1.0), A1::meet(&a0.1, &a1.1))
+    }
+
+    fn refine(a: &Self, bexpr: &BExpr) -> Self {
+        Self::reduced(A0::refine(&a.0, bexpr), A1::refine(&a.1, bexpr))
+    }
+
+    fn rem(a0: &Self, a1: &Self) -> Self {
+        Self::reduced(A0::rem(&a0.0, &a1.0), A1::rem(&a0.1, &a1.1))
+    }
+
+    fn int_div(a0: &Self, a1: &Self) -> Self {
+        Self::reduced(A0::int_div(&a0.0, &a1.0), A1::int_div(&a0.1, &a1.1))
+    }
+
+    fn min(a0: &Self, a1: &Self) -> Self {
+        Self::reduced(A0::min(&a0.0, &a1.0), A1::min(&a0.1, &a1.1))
+    }
+
+    fn max(a0: &Self, a1: &Self) -> Self {
+        Self::reduced(A0::max(&a0.0, &a1.0), A1::max(&a0.1, &a1.1))
+    }
+
+    fn abs(a: &Self) -> Self {
+        Self::reduced(A0::abs(&a.0), A1::abs(&a.1))
+    }
+
+    fn wrap(a: &Self, min: f64, max: f64) -> Self {
+        Self::reduced(A0::wrap(&a.0, min, max), A1::wrap(&a.1, min, max))
+    }
+
+    /// Widens componentwise without reducing the result, since the reduction could undo the
+    /// widening and prevent termination.
+    fn widen(a0: &Self, a1: &Self, widening_treshold: &Self) -> Self {
+        ProductAbstraction(
+            A0::widen(&a0.0, &a1.0, &widening_treshold.0),
+            A1::widen(&a0.1, &a1.1, &widening_treshold.1),
+        )
+    }
+}
+
+/// The largest integer with parity `parity` that is at most `bound`, if the bound is small enough
+/// to be represented exactly.
+fn round_down_to_parity(bound: f64, parity: ParityAbstraction) -> f64 {
+    if bound.abs() >= 2f64.powi(52) {
+        return bound;
+    }
+
+    let bound = bound.floor();
+    if ParityAbstraction::from(bound) == parity {
+        bound
+    } else {
+        bound - 1.0
+    }
+}
+
+impl Reduction<ParityAbstraction> for IntervalAbstraction {
+    fn reduce(a0: &Self, a1: &ParityAbstraction) -> (Self, ParityAbstraction) {
+        match (a0, a1) {
+            (Self::Bottom, _) | (_, ParityAbstraction::Bottom) => {
+                (Self::Bottom, ParityAbstraction::Bottom)
+            }
+            (Self::Interval(Interval { a, b }), ParityAbstraction::Top) => {
+                if a == b {
+                    (*a0, (*a).into())
+                } else {
+                    (*a0, *a1)
+                }
+            }
+            (Self::Interval(Interval { a, b }), _) => {
+                let a = -round_down_to_parity(-a, *a1);
+                let b = round_down_to_parity(*b, *a1);
+
+                if a > b {
+                    (Self::Bottom, ParityAbstraction::Bottom)
+                } else {
+                    ((a, b).into(), *a1)
+                }
+            }
+            (Self::Top, _) => (*a0, *a1),
+        }
+    }
+}
+
+impl Reduction<ParityAbstraction> for SignAbstraction {
+    fn reduce(a0: &Self, a1: &ParityAbstraction) -> (Self, ParityAbstraction) {
+        match (a0, a1) {
+            (Self::Bottom, _)
+            | (_, ParityAbstraction::Bottom)
+            | (Self::Zero, ParityAbstraction::Odd) => (Self::Bottom, ParityAbstraction::Bottom),
+            (Self::Zero, _) => (Self::Zero, ParityAbstraction::Even),
+            // odd numbers are never zero
+            (Self::NonPos, ParityAbstraction::Odd) => (Self::Neg, *a1),
+            (Self::NonNeg, ParityAbstraction::Odd) => (Self::Pos, *a1),
+            (Self::Top, ParityAbstraction::Odd) => (Self::NonZero, *a1),
+            _ => (*a0, *a1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::laws::laws;
+    use crate::command_parser::parse;
+    use crate::interpreter::{MemoryState, Params};
+    use proptest::prelude::*;
+
+    #[test]
+    fn parity_sharpens_interval_bounds() {
+        assert_eq!(
+            ProductAbstraction((2.0, 8.0).into(), ParityAbstraction::Even),
+            IntervalParityAbstraction::reduced((1.0, 9.0).into(), ParityAbstraction::Even)
+        );
+        assert_eq!(
+            ProductAbstraction(IntervalAbstraction::Bottom, ParityAbstraction::Bottom),
+            IntervalParityAbstraction::reduced((3.0, 3.0).into(), ParityAbstraction::Even)
+        );
+        assert_eq!(
+            ProductAbstraction((3.0, 3.0).into(), ParityAbstraction::Odd),
+            IntervalParityAbstraction::reduced((3.0, 3.0).into(), ParityAbstraction::Top)
+        );
+    }
+
+    #[test]
+    fn loop_with_even_steps() {
+        let program = "x := 0; while (x < 9) {x := x + 2}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
+        let x_truth: IntervalAbstraction = (9.0, 10.0).into();
+        assert_eq!(x_truth, *x_analyzed);
+
+        let mut pre: MemoryState<IntervalParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
+        let x_truth = ProductAbstraction((10.0, 10.0).into(), ParityAbstraction::Even);
+        assert_eq!(x_truth, *x_analyzed);
+    }
+
+    #[test]
+    fn undefined_operations_on_infinite_bounds() {
+        let program = "x := 1e308 * 10; y := x - x; z := 0 * x";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        assert_eq!(IntervalAbstraction::Top, y_analyzed.0);
+        let z_analyzed = post_analyzed.lookup_var("z").unwrap();
+        assert_eq!(IntervalAbstraction::Top, z_analyzed.0);
+    }
+
+    #[test]
+    fn even_factor_of_a_non_integer() {
+        let program = "x := 3 / 2; y := x * 2; if (y == 3) {z := 1} else {z := 2}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_truth = ProductAbstraction((3.0, 3.0).into(), ParityAbstraction::Odd);
+        assert_eq!(y_truth, *post_analyzed.lookup_var("y").unwrap());
+        let z_truth = ProductAbstraction((1.0, 1.0).into(), ParityAbstraction::Odd);
+        assert_eq!(z_truth, *post_analyzed.lookup_var("z").unwrap());
+    }
+
+    #[test]
+    fn odd_numbers_are_not_zero() {
+        let program = "input(i); if (i > 0) {x := 1} else {x := 0 - 3}; y := 2 * x + 1";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignParityAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        let y_truth = ProductAbstraction(SignAbstraction::NonZero, ParityAbstraction::Odd);
+        assert_eq!(y_truth, *y_analyzed);
+    }
+
+    fn parity_abstraction() -> impl Strategy<Value = ParityAbstraction> + Clone {
+        proptest::sample::select(vec![
+            ParityAbstraction::Bottom,
+            ParityAbstraction::Even,
+            ParityAbstraction::Odd,
+            ParityAbstraction::Top,
+        ])
+    }
+
+    fn interval_parity_abstraction() -> impl Strategy<Value = IntervalParityAbstraction> + Clone {
+        let interval = prop_oneof![
+            Just(IntervalAbstraction::Bottom),
+            Just(IntervalAbstraction::Top),
+            crate::abstractions::laws::bounds().prop_map(IntervalAbstraction::from),
+        ];
+
+        (interval, parity_abstraction())
+            .prop_map(|(a0, a1)| IntervalParityAbstraction::reduced(a0, a1))
+    }
+
+    fn sign_parity_abstraction() -> impl Strategy<Value = SignParityAbstraction> + Clone {
+        let sign = proptest::sample::select(vec![
+            SignAbstraction::Bottom,
+            SignAbstraction::Neg,
+            SignAbstraction::Zero,
+            SignAbstraction::Pos,
+            SignAbstraction::NonPos,
+            SignAbstraction::NonZero,
+            SignAbstraction::NonNeg,
+            SignAbstraction::Top,
+        ]);
+
+        (sign, parity_abstraction()).prop_map(|(a0, a1)| SignParityAbstraction::reduced(a0, a1))
+    }
+
+    laws!(interval_parity_laws, interval_parity_abstraction(), 6);
+
+    laws!(sign_parity_laws, sign_parity_abstraction(), 5);
+}
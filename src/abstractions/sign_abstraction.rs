@@ -1,7 +1,7 @@
 use std::{cmp::Ordering, ops};
 
-use crate::command_parser::{BExpr, Const};
-use crate::interpreter::{AbstractProperties, Bottom, Params, Top};
+use crate::command_parser::{BExpr, Const, Var};
+use crate::interpreter::{AbstractProperties, Bottom, Top};
 
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum SignAbstraction {
@@ -9,33 +9,184 @@ pub enum SignAbstraction {
     Neg,
     Zero,
     Pos,
+    NonPos,
+    NonZero,
+    NonNeg,
     Top,
 }
 
+impl SignAbstraction {
+    /// Whether the abstraction contains negative numbers, zero and positive numbers.
+    fn signs(self) -> (bool, bool, bool) {
+        match self {
+            Self::Bottom => (false, false, false),
+            Self::Neg => (true, false, false),
+            Self::Zero => (false, true, false),
+            Self::Pos => (false, false, true),
+            Self::NonPos => (true, true, false),
+            Self::NonZero => (true, false, true),
+            Self::NonNeg => (false, true, true),
+            Self::Top => (true, true, true),
+        }
+    }
+
+    fn from_signs((neg, zero, pos): (bool, bool, bool)) -> Self {
+        match (neg, zero, pos) {
+            (false, false, false) => Self::Bottom,
+            (true, false, false) => Self::Neg,
+            (false, true, false) => Self::Zero,
+            (false, false, true) => Self::Pos,
+            (true, true, false) => Self::NonPos,
+            (true, false, true) => Self::NonZero,
+            (false, true, true) => Self::NonNeg,
+            (true, true, true) => Self::Top,
+        }
+    }
+
+    /// The signs `Neg`, `Zero` and `Pos` the abstraction is made of.
+    fn atoms(self) -> impl Iterator<Item = Self> {
+        let (neg, zero, pos) = self.signs();
+        [(neg, Self::Neg), (zero, Self::Zero), (pos, Self::Pos)]
+            .into_iter()
+            .filter(|(contained, _)| *contained)
+            .map(|(_, atom)| atom)
+    }
+
+    /// Lifts an operation on `Neg`, `Zero` and `Pos` to the whole lattice by joining the results
+    /// for all combinations of signs.
+    fn lift(self, rhs: Self, op: fn(Self, Self) -> Self) -> Self {
+        self.atoms()
+            .flat_map(|a0| rhs.atoms().map(move |a1| op(a0, a1)))
+            .fold(Self::Bottom, |acc, a| Self::join(&acc, &a))
+    }
+
+    /// The most precise abstraction of all numbers that satisfy `bexpr`.
+    fn from_bexpr(bexpr: &BExpr) -> Self {
+        match bexpr {
+            BExpr::EQ(_, Const::Const(number)) => (*number).into(),
+            BExpr::NE(_, Const::Const(number)) => {
+                if *number == 0.0 {
+                    Self::NonZero
+                } else {
+                    Self::Top
+                }
+            }
+            BExpr::GE(_, Const::Const(number)) => {
+                if *number > 0.0 {
+                    Self::Pos
+                } else if *number == 0.0 {
+                    Self::NonNeg
+                } else {
+                    Self::Top
+                }
+            }
+            BExpr::GT(_, Const::Const(number)) => {
+                if *number >= 0.0 {
+                    Self::Pos
+                } else {
+                    Self::Top
+                }
+            }
+            BExpr::LE(_, Const::Const(number)) => {
+                if *number < 0.0 {
+                    Self::Neg
+                } else if *number == 0.0 {
+                    Self::NonPos
+                } else {
+                    Self::Top
+                }
+            }
+            BExpr::LT(_, Const::Const(number)) => {
+                if *number <= 0.0 {
+                    Self::Neg
+                } else {
+                    Self::Top
+                }
+            }
+        }
+    }
+
+    /// Whether every number of `self` is less than or equal to every number of `other`.
+    fn at_most(self, other: Self) -> bool {
+        let rank = |atom: Self| match atom {
+            Self::Neg => -1,
+            Self::Zero => 0,
+            _ => 1,
+        };
+
+        match (self.atoms().last(), other.atoms().next()) {
+            (Some(highest), Some(lowest)) => {
+                rank(highest) < rank(lowest) || (highest == Self::Zero && lowest == Self::Zero)
+            }
+            _ => true,
+        }
+    }
+
+    fn add_atoms(a0: Self, a1: Self) -> Self {
+        match (a0, a1) {
+            (Self::Zero, _) => a1,
+            (_, Self::Zero) => a0,
+            (Self::Neg, Self::Neg) => Self::Neg,
+            (Self::Pos, Self::Pos) => Self::Pos,
+            _ => Self::Top,
+        }
+    }
+
+    fn mul_atoms(a0: Self, a1: Self) -> Self {
+        match (a0, a1) {
+            (Self::Zero, _) | (_, Self::Zero) => Self::Zero,
+            (Self::Neg, Self::Neg) | (Self::Pos, Self::Pos) => Self::Pos,
+            _ => Self::Neg,
+        }
+    }
+
+    fn div_atoms(a0: Self, a1: Self) -> Self {
+        match (a0, a1) {
+            // a division by zero has no result
+            (_, Self::Zero) => Self::Bottom,
+            _ => Self::mul_atoms(a0, a1),
+        }
+    }
+
+    /// The remainder has the sign of the dividend, but may be zero. The integer part of a
+    /// positive or negative number may be zero as well.
+    fn rem_atoms(a0: Self, a1: Self) -> Self {
+        match (a0, a1) {
+            (_, Self::Zero) => Self::Bottom,
+            (Self::Zero, _) => Self::Zero,
+            (Self::Pos, _) => Self::NonNeg,
+            _ => Self::NonPos,
+        }
+    }
+
+    /// The quotient is zero if the dividend is smaller than the divisor in magnitude.
+    fn int_div_atoms(a0: Self, a1: Self) -> Self {
+        match (a0, a1) {
+            (_, Self::Zero) => Self::Bottom,
+            (Self::Zero, _) => Self::Zero,
+            _ if a0 == a1 => Self::NonNeg,
+            _ => Self::NonPos,
+        }
+    }
+
+    fn min_atoms(a0: Self, a1: Self) -> Self {
+        match (a0, a1) {
+            (Self::Neg, _) | (_, Self::Neg) => Self::Neg,
+            (Self::Zero, _) | (_, Self::Zero) => Self::Zero,
+            _ => Self::Pos,
+        }
+    }
+
+    fn max_atoms(a0: Self, a1: Self) -> Self {
+        -Self::min_atoms(-a0, -a1)
+    }
+}
+
 impl ops::Add for SignAbstraction {
     type Output = SignAbstraction;
 
     fn add(self, rhs: Self) -> Self::Output {
-        match self {
-            Self::Bottom => Self::Bottom,
-            Self::Neg => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Neg => Self::Neg,
-                Self::Zero => self,
-                _ => Self::Top,
-            },
-            Self::Pos => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Pos => Self::Pos,
-                Self::Zero => self,
-                _ => Self::Top,
-            },
-            Self::Top => match rhs {
-                Self::Bottom => Self::Bottom,
-                _ => Self::Top,
-            },
-            Self::Zero => rhs,
-        }
+        self.lift(rhs, Self::add_atoms)
     }
 }
 
@@ -43,13 +194,8 @@ impl ops::Neg for SignAbstraction {
     type Output = SignAbstraction;
 
     fn neg(self) -> Self::Output {
-        match self {
-            Self::Bottom => Self::Bottom,
-            Self::Neg => Self::Pos,
-            Self::Pos => Self::Neg,
-            Self::Top => Self::Top,
-            Self::Zero => Self::Zero,
-        }
+        let (neg, zero, pos) = self.signs();
+        Self::from_signs((pos, zero, neg))
     }
 }
 
@@ -57,26 +203,7 @@ impl ops::Sub for SignAbstraction {
     type Output = SignAbstraction;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match self {
-            Self::Bottom => Self::Bottom,
-            Self::Neg => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Pos => Self::Neg,
-                Self::Zero => self,
-                _ => Self::Top,
-            },
-            Self::Pos => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Neg => Self::Pos,
-                Self::Zero => self,
-                _ => Self::Top,
-            },
-            Self::Top => match rhs {
-                Self::Bottom => Self::Bottom,
-                _ => Self::Top,
-            },
-            Self::Zero => rhs,
-        }
+        self.lift(-rhs, Self::add_atoms)
     }
 }
 
@@ -84,29 +211,7 @@ impl ops::Mul for SignAbstraction {
     type Output = SignAbstraction;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        match self {
-            Self::Bottom => Self::Bottom,
-            Self::Neg => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Neg => Self::Pos,
-                Self::Pos => Self::Neg,
-                Self::Top => Self::Top,
-                Self::Zero => Self::Zero,
-            },
-            Self::Pos => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Neg => Self::Neg,
-                Self::Pos => Self::Pos,
-                Self::Top => Self::Top,
-                Self::Zero => Self::Zero,
-            },
-            Self::Top => match rhs {
-                Self::Bottom => Self::Bottom,
-                Self::Zero => Self::Zero,
-                _ => Self::Top,
-            },
-            Self::Zero => Self::Zero,
-        }
+        self.lift(rhs, Self::mul_atoms)
     }
 }
 
@@ -114,7 +219,7 @@ impl ops::Div for SignAbstraction {
     type Output = SignAbstraction;
 
     fn div(self, rhs: Self) -> Self::Output {
-        self * rhs
+        self.lift(rhs, Self::div_atoms)
     }
 }
 
@@ -131,40 +236,21 @@ impl From<f64> for SignAbstraction {
 }
 
 impl PartialOrd for SignAbstraction {
+    /// Compares the numbers represented by both abstractions: `a0 < a1` if every number of `a0` is
+    /// less than or equal to every number of `a1`. `Bottom` and `Top` are the least and the
+    /// greatest element.
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self {
-            Self::Bottom => match other {
-                Self::Bottom => Some(Ordering::Equal),
-                _ => Some(Ordering::Less),
-            },
-            Self::Neg => match other {
-                Self::Bottom => Some(Ordering::Greater),
-                Self::Neg => Some(Ordering::Equal),
-                Self::Zero => Some(Ordering::Less),
-                Self::Pos => Some(Ordering::Less),
-                Self::Top => Some(Ordering::Less),
-            },
-            Self::Pos => match other {
-                Self::Bottom => Some(Ordering::Greater),
-                Self::Neg => Some(Ordering::Greater),
-                Self::Zero => Some(Ordering::Greater),
-                Self::Pos => Some(Ordering::Equal),
-                Self::Top => Some(Ordering::Less),
-            },
-            Self::Top => match other {
-                Self::Top => Some(Ordering::Equal),
-                _ => Some(Ordering::Greater),
-            },
-            Self::Zero => match other {
-                Self::Bottom => Some(Ordering::Greater),
-                Self::Neg => Some(Ordering::Greater),
-                Self::Zero => Some(Ordering::Equal),
-                Self::Pos => Some(Ordering::Less),
-                Self::Top => Some(Ordering::Less),
-            },
+        match (self, other) {
+            _ if self == other => Some(Ordering::Equal),
+            (Self::Bottom, _) | (_, Self::Top) => Some(Ordering::Less),
+            (Self::Top, _) | (_, Self::Bottom) => Some(Ordering::Greater),
+            _ if self.at_most(*other) => Some(Ordering::Less),
+            _ if other.at_most(*self) => Some(Ordering::Greater),
+            _ => None,
         }
     }
 }
+
 impl AbstractProperties<SignAbstraction> for SignAbstraction {
     fn bottom() -> Bottom {
         Bottom
@@ -174,145 +260,66 @@ impl AbstractProperties<SignAbstraction> for SignAbstraction {
         Top
     }
 
-    fn sat(a: &SignAbstraction, bexpr: &BExpr) -> bool {
-        let result = match a {
-            Self::Bottom => false,
-            Self::Top => true,
-            _ => match bexpr {
-                BExpr::EQ(_, Const::Const(number)) => {
-                    *a == <f64 as Into<SignAbstraction>>::into(*number)
-                }
-                BExpr::GE(_, Const::Const(number)) => {
-                    *a >= <f64 as Into<SignAbstraction>>::into(*number)
-                }
-                BExpr::GT(_, Const::Const(number)) => {
-                    *a > <f64 as Into<SignAbstraction>>::into(*number)
-                }
-                BExpr::LE(_, Const::Const(number)) => {
-                    *a <= <f64 as Into<SignAbstraction>>::into(*number)
-                }
-                BExpr::LT(_, Const::Const(number)) => {
-                    *a < <f64 as Into<SignAbstraction>>::into(*number)
-                }
-                BExpr::NE(_, Const::Const(number)) => {
-                    *a != <f64 as Into<SignAbstraction>>::into(*number)
-                }
-            },
-        };
+    fn meet(a0: &Self, a1: &Self) -> Self {
+        let (neg0, zero0, pos0) = a0.signs();
+        let (neg1, zero1, pos1) = a1.signs();
+        Self::from_signs((neg0 && neg1, zero0 && zero1, pos0 && pos1))
+    }
 
-        result
+    fn sat(a: &SignAbstraction, bexpr: &BExpr) -> bool {
+        Self::meet(a, &Self::from_bexpr(bexpr)) != Self::Bottom
     }
+
     fn first_includes_second(a0: &Self, a1: &Self) -> bool {
-        match a0 {
-            Self::Bottom => true,
-
-            Self::Neg => match a1 {
-                Self::Bottom => false,
-                Self::Neg => true,
-                Self::Zero => false,
-                Self::Pos => false,
-                Self::Top => true,
-            },
-            Self::Zero => match a1 {
-                Self::Bottom => false,
-                Self::Neg => true,
-                Self::Zero => true,
-                Self::Pos => true,
-                Self::Top => true,
-            },
-            Self::Pos => match a1 {
-                Self::Bottom => false,
-                Self::Neg => false,
-                Self::Zero => false,
-                Self::Pos => true,
-                Self::Top => true,
-            },
-            Self::Top => match a1 {
-                Self::Top => true,
-                _ => false,
-            },
-        }
+        Self::meet(a0, a1) == *a1
     }
 
     fn join(a0: &Self, a1: &Self) -> Self {
-        match a0 {
-            Self::Bottom => *a1,
-            Self::Neg => match a1 {
-                Self::Bottom => *a0,
-                Self::Neg => *a0,
-                Self::Zero => *a0,
-                Self::Pos => Self::Top,
-                Self::Top => Self::Top,
-            },
-            Self::Zero => match a1 {
-                Self::Bottom => *a0,
-                Self::Neg => Self::Neg,
-                Self::Zero => *a0,
-                Self::Pos => Self::Pos,
-                Self::Top => Self::Top,
-            },
-            Self::Pos => match a1 {
-                Self::Bottom => *a0,
-                Self::Neg => Self::Top,
-                Self::Zero => *a0,
-                Self::Pos => Self::Pos,
-                Self::Top => Self::Top,
-            },
-            Self::Top => Self::Top,
-        }
+        let (neg0, zero0, pos0) = a0.signs();
+        let (neg1, zero1, pos1) = a1.signs();
+        Self::from_signs((neg0 || neg1, zero0 || zero1, pos0 || pos1))
     }
 
     fn refine(a: &Self, bexpr: &BExpr) -> Self {
-        if !Self::sat(a, bexpr) {
-            return Self::Bottom;
-        }
+        Self::meet(a, &Self::from_bexpr(bexpr))
+    }
 
-        if *a == Self::Top {
-            match bexpr {
-                BExpr::EQ(_, Const::Const(number)) => {
-                    return <f64 as Into<SignAbstraction>>::into(*number);
-                }
-                BExpr::GE(_, Const::Const(number)) => {
-                    if <f64 as Into<SignAbstraction>>::into(*number) == Self::Neg {
-                        return *a;
-                    } else {
-                        return Self::Pos;
-                    }
-                }
-                BExpr::GT(_, Const::Const(number)) => {
-                    if <f64 as Into<SignAbstraction>>::into(*number) == Self::Pos {
-                        return *a;
-                    } else {
-                        return Self::Pos;
-                    }
-                }
-                BExpr::LE(_, Const::Const(number)) => {
-                    if <f64 as Into<SignAbstraction>>::into(*number) == Self::Pos {
-                        return *a;
-                    } else {
-                        return Self::Neg;
-                    }
-                }
-                BExpr::LT(_, Const::Const(number)) => {
-                    if <f64 as Into<SignAbstraction>>::into(*number) == Self::Neg {
-                        return *a;
-                    } else {
-                        return Self::Neg;
-                    }
-                }
-                BExpr::NE(_, Const::Const(_)) => {
-                    return *a;
-                }
-            }
-        } else {
-            return *a;
+    fn rem(a0: &Self, a1: &Self) -> Self {
+        a0.lift(*a1, Self::rem_atoms)
+    }
+
+    fn int_div(a0: &Self, a1: &Self) -> Self {
+        a0.lift(*a1, Self::int_div_atoms)
+    }
+
+    fn min(a0: &Self, a1: &Self) -> Self {
+        a0.lift(*a1, Self::min_atoms)
+    }
+
+    fn max(a0: &Self, a1: &Self) -> Self {
+        a0.lift(*a1, Self::max_atoms)
+    }
+
+    fn abs(a: &Self) -> Self {
+        let (neg, zero, pos) = a.signs();
+        Self::from_signs((false, zero, neg || pos))
+    }
+
+    /// The signs of the whole range if some values may not fit into it.
+    fn wrap(a: &Self, min: f64, max: f64) -> Self {
+        let x = Var::Var("wrapped".to_string());
+        let below = Self::sat(a, &BExpr::LT(x.clone(), Const::Const(min)));
+        let above = Self::sat(a, &BExpr::GT(x, Const::Const(max)));
+        match below || above {
+            true => Self::from_signs((min < 0.0, true, max > 0.0)),
+            false => *a,
         }
     }
 
     fn widen(
         a0: &SignAbstraction,
         a1: &SignAbstraction,
-        widening_treshold: &SignAbstraction,
+        _widening_treshold: &SignAbstraction,
     ) -> SignAbstraction {
         SignAbstraction::join(a0, a1)
     }
@@ -333,14 +340,17 @@ impl From<Bottom> for SignAbstraction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::command_parser::parse;
+    use crate::abstractions::laws::laws;
+    use crate::command_parser::{parse, Var};
+    use crate::interpreter::Params;
     use crate::MemoryState;
+    use proptest::prelude::*;
     use std::collections::HashMap;
 
     #[test]
     fn skip() {
         let program = "skip";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -352,7 +362,7 @@ mod tests {
     #[test]
     fn assign() {
         let program = "x := 50";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -365,7 +375,7 @@ mod tests {
     #[test]
     fn input() {
         let program = "input(x)";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -378,7 +388,7 @@ mod tests {
     #[test]
     fn cif() {
         let program = "if (x < 0) {y := x} else {skip}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -393,7 +403,7 @@ mod tests {
     #[test]
     fn figure_3_9_a_with_pre_condition() {
         let program = "x := 0; while (x >= 0) {x := x + 1}";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> =
             MemoryState::from_state(HashMap::from([("x".to_string(), SignAbstraction::Pos)]));
@@ -407,7 +417,7 @@ mod tests {
     #[test]
     fn seq() {
         let program = "skip;skip";
-        let command = parse(&program);
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
@@ -419,15 +429,240 @@ mod tests {
     #[test]
     fn example_presentation() {
         let program = "x := 0; y := 0; while (x < 10) {x := x + 1; y := x}";
-        let command = parse(&program);
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), SignAbstraction::Pos),
+            ("y".to_string(), SignAbstraction::NonNeg),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    #[test]
+    fn guard_with_non_strict_bound() {
+        let program = "input(x); if (x >= 0) {y := x} else {y := -x}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), SignAbstraction::Top),
+            ("y".to_string(), SignAbstraction::NonNeg),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    #[test]
+    fn booleans() {
+        let program = "var b: bool; input(b); if (b) {x := b} else {x := 1}";
+        let command = parse(program);
 
         let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
         let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
 
         let post_truth = MemoryState::from_state(HashMap::from([
+            ("b".to_string(), SignAbstraction::NonNeg),
             ("x".to_string(), SignAbstraction::Pos),
-            ("y".to_string(), SignAbstraction::Pos),
         ]));
         assert_eq!(post_truth, *post_analyzed);
     }
+
+    #[test]
+    fn integer_operators() {
+        let program = "input(x, 1, 5); input(y, -3, 0); r := x % y; q := y div x; a := abs(y); m := min(x, y); n := max(x, y)";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            Some(&SignAbstraction::NonNeg),
+            post_analyzed.lookup_var("r")
+        );
+        assert_eq!(
+            Some(&SignAbstraction::NonPos),
+            post_analyzed.lookup_var("q")
+        );
+        assert_eq!(
+            Some(&SignAbstraction::NonNeg),
+            post_analyzed.lookup_var("a")
+        );
+        assert_eq!(
+            Some(&SignAbstraction::NonPos),
+            post_analyzed.lookup_var("m")
+        );
+        assert_eq!(Some(&SignAbstraction::Pos), post_analyzed.lookup_var("n"));
+    }
+
+    #[test]
+    fn machine_integers() {
+        let program = "var x: u8; input(x); var y: wrapping i8; y := 100; y := y + 100";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            Some(&SignAbstraction::NonNeg),
+            post_analyzed.lookup_var("x")
+        );
+        assert_eq!(Some(&SignAbstraction::Top), post_analyzed.lookup_var("y"));
+        // the signs cannot show that `100` fits into an `i8`
+        assert_eq!(2, post_analyzed.alarms().len());
+    }
+
+    #[test]
+    fn bounded_input() {
+        let program = "input(x, 1, 5); input(y, -3, 0)";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), SignAbstraction::Pos),
+            ("y".to_string(), SignAbstraction::NonPos),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    const ALL: [SignAbstraction; 8] = [
+        SignAbstraction::Bottom,
+        SignAbstraction::Neg,
+        SignAbstraction::Zero,
+        SignAbstraction::Pos,
+        SignAbstraction::NonPos,
+        SignAbstraction::NonZero,
+        SignAbstraction::NonNeg,
+        SignAbstraction::Top,
+    ];
+
+    fn includes(a0: &SignAbstraction, a1: &SignAbstraction) -> bool {
+        SignAbstraction::first_includes_second(a0, a1)
+    }
+
+    #[test]
+    fn join_of_zero_and_pos() {
+        assert_eq!(
+            SignAbstraction::NonNeg,
+            SignAbstraction::join(&SignAbstraction::Zero, &SignAbstraction::Pos)
+        );
+        assert_eq!(
+            SignAbstraction::NonZero,
+            SignAbstraction::join(&SignAbstraction::Neg, &SignAbstraction::Pos)
+        );
+        assert!(!includes(&SignAbstraction::Zero, &SignAbstraction::Neg));
+    }
+
+    #[test]
+    fn inclusion_is_a_partial_order() {
+        for a0 in ALL {
+            assert!(includes(&a0, &a0));
+            assert!(includes(&a0, &SignAbstraction::Bottom));
+            assert!(includes(&SignAbstraction::Top, &a0));
+
+            for a1 in ALL {
+                if includes(&a0, &a1) && includes(&a1, &a0) {
+                    assert_eq!(a0, a1);
+                }
+
+                for a2 in ALL {
+                    if includes(&a0, &a1) && includes(&a1, &a2) {
+                        assert!(includes(&a0, &a2));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn join_is_the_least_upper_bound() {
+        for a0 in ALL {
+            assert_eq!(a0, SignAbstraction::join(&a0, &a0));
+
+            for a1 in ALL {
+                let joined = SignAbstraction::join(&a0, &a1);
+                assert_eq!(joined, SignAbstraction::join(&a1, &a0));
+                assert!(includes(&joined, &a0));
+                assert!(includes(&joined, &a1));
+
+                for a2 in ALL {
+                    assert_eq!(
+                        SignAbstraction::join(&joined, &a2),
+                        SignAbstraction::join(&a0, &SignAbstraction::join(&a1, &a2))
+                    );
+
+                    if includes(&a2, &a0) && includes(&a2, &a1) {
+                        assert!(includes(&a2, &joined));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn operators_are_monotone() {
+        let operators: [fn(SignAbstraction, SignAbstraction) -> SignAbstraction; 4] = [
+            |a0, a1| a0 + a1,
+            |a0, a1| a0 - a1,
+            |a0, a1| a0 * a1,
+            |a0, a1| a0 / a1,
+        ];
+
+        for a0 in ALL {
+            for a0_larger in ALL.into_iter().filter(|a| includes(a, &a0)) {
+                assert!(includes(&-a0_larger, &-a0));
+
+                for a1 in ALL {
+                    for op in operators {
+                        assert!(includes(&op(a0_larger, a1), &op(a0, a1)));
+                        assert!(includes(&op(a1, a0_larger), &op(a1, a0)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn refinement_is_reductive_and_monotone() {
+        let guards = [-1.0, 0.0, 1.0].into_iter().flat_map(|number| {
+            let var = || Var::Var("x".to_string());
+            [
+                BExpr::EQ(var(), Const::Const(number)),
+                BExpr::NE(var(), Const::Const(number)),
+                BExpr::GE(var(), Const::Const(number)),
+                BExpr::GT(var(), Const::Const(number)),
+                BExpr::LE(var(), Const::Const(number)),
+                BExpr::LT(var(), Const::Const(number)),
+            ]
+        });
+
+        for bexpr in guards {
+            for a0 in ALL {
+                let refined = SignAbstraction::refine(&a0, &bexpr);
+                assert!(includes(&a0, &refined));
+                assert_eq!(
+                    refined != SignAbstraction::Bottom,
+                    SignAbstraction::sat(&a0, &bexpr)
+                );
+
+                for a0_larger in ALL.into_iter().filter(|a| includes(a, &a0)) {
+                    assert!(includes(
+                        &SignAbstraction::refine(&a0_larger, &bexpr),
+                        &refined
+                    ));
+                }
+            }
+        }
+    }
+
+    fn sign_abstraction() -> impl Strategy<Value = SignAbstraction> + Clone {
+        proptest::sample::select(ALL.to_vec())
+    }
+
+    laws!(laws, sign_abstraction(), 3);
 }
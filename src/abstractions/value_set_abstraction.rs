@@ -0,0 +1,515 @@
+use std::{cmp::Ordering, fmt, ops};
+
+use crate::abstractions::interval_abstraction::{Interval, IntervalAbstraction};
+use crate::command_parser::{BExpr, Const};
+use crate::interpreter::{int_div, rem, wrap, AbstractProperties, Bottom, Top};
+
+/// The maximal number of concrete values that are tracked before falling back to an interval.
+pub const MAX_VALUES: usize = 8;
+
+/// A sorted set of at most `MAX_VALUES` distinct values.
+#[derive(Copy, Clone)]
+pub struct ValueSet {
+    values: [f64; MAX_VALUES],
+    len: usize,
+}
+
+impl ValueSet {
+    /// Returns `None` if there are more than `MAX_VALUES` distinct values.
+    pub fn new(values: impl IntoIterator<Item = f64>) -> Option<ValueSet> {
+        let mut set = ValueSet {
+            values: [0.0; MAX_VALUES],
+            len: 0,
+        };
+
+        for value in values {
+            if set.contains(value) {
+                continue;
+            }
+            if set.len == MAX_VALUES {
+                return None;
+            }
+
+            let index = set.as_slice().partition_point(|v| *v < value);
+            set.values.copy_within(index..set.len, index + 1);
+            set.values[index] = value;
+            set.len += 1;
+        }
+
+        Some(set)
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.as_slice().contains(&value)
+    }
+
+    fn hull(&self) -> Interval {
+        Interval::new(self.values[0], self.values[self.len - 1])
+    }
+}
+
+impl fmt::Debug for ValueSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.as_slice()).finish()
+    }
+}
+
+/// Tracks up to `MAX_VALUES` concrete values of a variable and falls back to an interval beyond
+/// that. `Values` is never empty, the empty set is `Bottom`.
+#[derive(Copy, Clone, Debug)]
+pub enum ValueSetAbstraction {
+    Bottom,
+    Values(ValueSet),
+    Interval(Interval),
+    Top,
+}
+
+impl ValueSetAbstraction {
+    fn from_values(values: impl IntoIterator<Item = f64> + Clone) -> Self {
+        match ValueSet::new(values.clone()) {
+            Some(set) if set.len == 0 => Self::Bottom,
+            Some(set) => Self::Values(set),
+            None => {
+                let values = values.into_iter().filter(|v| !v.is_nan());
+                let (a, b) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), v| {
+                    (f64::min(a, v), f64::max(b, v))
+                });
+                Self::Interval(Interval::new(a, b))
+            }
+        }
+    }
+
+    fn to_interval(self) -> IntervalAbstraction {
+        match self {
+            Self::Bottom => IntervalAbstraction::Bottom,
+            Self::Values(set) => IntervalAbstraction::Interval(set.hull()),
+            Self::Interval(interval) => IntervalAbstraction::Interval(interval),
+            Self::Top => IntervalAbstraction::Top,
+        }
+    }
+
+    fn from_interval(a: IntervalAbstraction) -> Self {
+        match a {
+            IntervalAbstraction::Bottom => Self::Bottom,
+            IntervalAbstraction::Interval(Interval { a, b }) if a == b => Self::from_values([a]),
+            IntervalAbstraction::Interval(interval) => Self::Interval(interval),
+            IntervalAbstraction::Top => Self::Top,
+        }
+    }
+
+    /// Applies `op` to all pairs of values if both sides are value sets, otherwise to the
+    /// intervals that cover them. Pairs for which `op` has no result are skipped.
+    fn lift(
+        self,
+        rhs: Self,
+        op: fn(f64, f64) -> Option<f64>,
+        interval_op: fn(IntervalAbstraction, IntervalAbstraction) -> IntervalAbstraction,
+    ) -> Self {
+        match (self, rhs) {
+            (Self::Values(lhs), Self::Values(rhs)) => {
+                let results = lhs
+                    .as_slice()
+                    .iter()
+                    .flat_map(|v0| rhs.as_slice().iter().filter_map(|v1| op(*v0, *v1)))
+                    .collect::<Vec<_>>();
+                Self::from_values(results)
+            }
+            _ => Self::from_interval(interval_op(self.to_interval(), rhs.to_interval())),
+        }
+    }
+}
+
+impl ops::Add for ValueSetAbstraction {
+    type Output = ValueSetAbstraction;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, |v0, v1| Some(v0 + v1), |a0, a1| a0 + a1)
+    }
+}
+
+impl ops::Sub for ValueSetAbstraction {
+    type Output = ValueSetAbstraction;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, |v0, v1| Some(v0 - v1), |a0, a1| a0 - a1)
+    }
+}
+
+impl ops::Mul for ValueSetAbstraction {
+    type Output = ValueSetAbstraction;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.lift(rhs, |v0, v1| Some(v0 * v1), |a0, a1| a0 * a1)
+    }
+}
+
+impl ops::Div for ValueSetAbstraction {
+    type Output = ValueSetAbstraction;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        // a division by zero has no result
+        self.lift(
+            rhs,
+            |v0, v1| if v1 == 0.0 { None } else { Some(v0 / v1) },
+            |a0, a1| a0 / a1,
+        )
+    }
+}
+
+impl ops::Neg for ValueSetAbstraction {
+    type Output = ValueSetAbstraction;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Self::Values(set) => Self::from_values(set.as_slice().iter().map(|v| -v)),
+            _ => Self::from_interval(-self.to_interval()),
+        }
+    }
+}
+
+impl From<f64> for ValueSetAbstraction {
+    fn from(value: f64) -> Self {
+        Self::from_values([value])
+    }
+}
+
+impl From<Top> for ValueSetAbstraction {
+    fn from(_: Top) -> Self {
+        Self::Top
+    }
+}
+
+impl From<Bottom> for ValueSetAbstraction {
+    fn from(_: Bottom) -> Self {
+        Self::Bottom
+    }
+}
+
+impl PartialEq for ValueSetAbstraction {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Bottom, Self::Bottom) | (Self::Top, Self::Top) => true,
+            (Self::Values(set_self), Self::Values(set_other)) => {
+                set_self.as_slice() == set_other.as_slice()
+            }
+            (Self::Interval(interval_self), Self::Interval(interval_other)) => {
+                interval_self.a == interval_other.a && interval_self.b == interval_other.b
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialOrd for ValueSetAbstraction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.to_interval(), other.to_interval()) {
+            _ if self == other => Some(Ordering::Equal),
+            (IntervalAbstraction::Bottom, _) | (_, IntervalAbstraction::Top) => {
+                Some(Ordering::Less)
+            }
+            (IntervalAbstraction::Top, _) | (_, IntervalAbstraction::Bottom) => {
+                Some(Ordering::Greater)
+            }
+            (
+                IntervalAbstraction::Interval(interval_self),
+                IntervalAbstraction::Interval(interval_other),
+            ) => {
+                if interval_self.b <= interval_other.a {
+                    Some(Ordering::Less)
+                } else if interval_other.b <= interval_self.a {
+                    Some(Ordering::Greater)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl AbstractProperties<ValueSetAbstraction> for ValueSetAbstraction {
+    fn bottom() -> Bottom {
+        Bottom
+    }
+
+    fn top() -> Top {
+        Top
+    }
+
+    fn sat(a: &ValueSetAbstraction, bexpr: &BExpr) -> bool {
+        match a {
+            Self::Values(set) => set.as_slice().iter().any(|v| satisfies(*v, bexpr)),
+            _ => IntervalAbstraction::sat(&a.to_interval(), bexpr),
+        }
+    }
+
+    fn first_includes_second(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> bool {
+        match (a0, a1) {
+            (_, Self::Bottom) | (Self::Top, _) => true,
+            (Self::Bottom, _) | (_, Self::Top) => false,
+            (Self::Values(set0), Self::Values(set1)) => {
+                set1.as_slice().iter().all(|v| set0.contains(*v))
+            }
+            (Self::Values(_), Self::Interval(_)) => false,
+            _ => IntervalAbstraction::first_includes_second(&a0.to_interval(), &a1.to_interval()),
+        }
+    }
+
+    fn join(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> ValueSetAbstraction {
+        match (a0, a1) {
+            (Self::Bottom, _) => *a1,
+            (_, Self::Bottom) => *a0,
+            (Self::Values(set0), Self::Values(set1)) => {
+                Self::from_values(set0.as_slice().iter().chain(set1.as_slice()).copied())
+            }
+            _ => Self::from_interval(IntervalAbstraction::join(
+                &a0.to_interval(),
+                &a1.to_interval(),
+            )),
+        }
+    }
+
+    fn meet(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> ValueSetAbstraction {
+        match (a0, a1) {
+            (Self::Values(set), a) | (a, Self::Values(set)) => Self::from_values(
+                set.as_slice()
+                    .iter()
+                    .copied()
+                    .filter(|v| Self::first_includes_second(a, &Self::from_values([*v]))),
+            ),
+            _ => Self::from_interval(IntervalAbstraction::meet(
+                &a0.to_interval(),
+                &a1.to_interval(),
+            )),
+        }
+    }
+
+    fn refine(a: &ValueSetAbstraction, bexpr: &BExpr) -> ValueSetAbstraction {
+        match a {
+            Self::Values(set) => Self::from_values(
+                set.as_slice()
+                    .iter()
+                    .copied()
+                    .filter(|v| satisfies(*v, bexpr)),
+            ),
+            _ => Self::from_interval(IntervalAbstraction::refine(&a.to_interval(), bexpr)),
+        }
+    }
+
+    fn rem(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> ValueSetAbstraction {
+        // a division by zero has no result
+        a0.lift(
+            *a1,
+            |v0, v1| {
+                if v1.trunc() == 0.0 {
+                    None
+                } else {
+                    Some(rem(v0, v1))
+                }
+            },
+            |a0, a1| IntervalAbstraction::rem(&a0, &a1),
+        )
+    }
+
+    fn int_div(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> ValueSetAbstraction {
+        a0.lift(
+            *a1,
+            |v0, v1| {
+                if v1.trunc() == 0.0 {
+                    None
+                } else {
+                    Some(int_div(v0, v1))
+                }
+            },
+            |a0, a1| IntervalAbstraction::int_div(&a0, &a1),
+        )
+    }
+
+    fn min(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> ValueSetAbstraction {
+        a0.lift(
+            *a1,
+            |v0, v1| Some(f64::min(v0, v1)),
+            |a0, a1| IntervalAbstraction::min(&a0, &a1),
+        )
+    }
+
+    fn max(a0: &ValueSetAbstraction, a1: &ValueSetAbstraction) -> ValueSetAbstraction {
+        a0.lift(
+            *a1,
+            |v0, v1| Some(f64::max(v0, v1)),
+            |a0, a1| IntervalAbstraction::max(&a0, &a1),
+        )
+    }
+
+    /// An interval with at most `MAX_VALUES` integers becomes the set of them, so that guards
+    /// like `x != 2` remove values from it.
+    fn integers(a: &ValueSetAbstraction) -> ValueSetAbstraction {
+        match a {
+            Self::Interval(Interval { a, b }) => {
+                let (lo, hi) = (a.ceil(), b.floor());
+                if lo <= hi && hi - lo < MAX_VALUES as f64 {
+                    Self::from_values((0..=(hi - lo) as usize).map(|i| lo + i as f64))
+                } else {
+                    Self::Interval(Interval { a: *a, b: *b })
+                }
+            }
+            _ => *a,
+        }
+    }
+
+    fn abs(a: &ValueSetAbstraction) -> ValueSetAbstraction {
+        match a {
+            Self::Values(set) => Self::from_values(set.as_slice().iter().map(|v| v.abs())),
+            _ => Self::from_interval(IntervalAbstraction::abs(&a.to_interval())),
+        }
+    }
+
+    fn wrap(a: &ValueSetAbstraction, min: f64, max: f64) -> ValueSetAbstraction {
+        match a {
+            Self::Values(set) => {
+                Self::from_values(set.as_slice().iter().map(|v| wrap(*v, min, max)))
+            }
+            _ => Self::from_interval(IntervalAbstraction::wrap(&a.to_interval(), min, max)),
+        }
+    }
+
+    /// Joins value sets as long as they stay below `MAX_VALUES` values, which happens only
+    /// finitely often, and widens the covering intervals afterwards.
+    fn widen(
+        a0: &ValueSetAbstraction,
+        a1: &ValueSetAbstraction,
+        widening_treshold: &ValueSetAbstraction,
+    ) -> ValueSetAbstraction {
+        match Self::join(a0, a1) {
+            joined @ (Self::Bottom | Self::Values(_)) => joined,
+            _ => Self::from_interval(IntervalAbstraction::widen(
+                &a0.to_interval(),
+                &a1.to_interval(),
+                &widening_treshold.to_interval(),
+            )),
+        }
+    }
+}
+
+fn satisfies(value: f64, bexpr: &BExpr) -> bool {
+    match bexpr {
+        BExpr::EQ(_, Const::Const(number)) => value == *number,
+        BExpr::NE(_, Const::Const(number)) => value != *number,
+        BExpr::GE(_, Const::Const(number)) => value >= *number,
+        BExpr::GT(_, Const::Const(number)) => value > *number,
+        BExpr::LE(_, Const::Const(number)) => value <= *number,
+        BExpr::LT(_, Const::Const(number)) => value < *number,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::laws::laws;
+    use crate::command_parser::parse;
+    use crate::interpreter::{MemoryState, Params};
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    fn values(values: &[f64]) -> ValueSetAbstraction {
+        ValueSetAbstraction::Values(ValueSet::new(values.iter().copied()).unwrap())
+    }
+
+    #[test]
+    fn configuration_flag() {
+        let program =
+            "input(m); if (m == 0) {x := 0} else {if (m == 1) {x := 1} else {x := 2}}; y := x * 10";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ValueSetAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let post_truth = MemoryState::from_state(HashMap::from([
+            ("m".to_string(), ValueSetAbstraction::Top),
+            ("x".to_string(), values(&[0.0, 1.0, 2.0])),
+            ("y".to_string(), values(&[0.0, 10.0, 20.0])),
+        ]));
+        assert_eq!(post_truth, *post_analyzed);
+    }
+
+    #[test]
+    fn not_equal_removes_the_value() {
+        let program = "input(m); if (m == 0) {x := 0} else {if (m == 1) {x := 1} else {x := 2}}; if (x != 1) {y := x} else {y := 5}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ValueSetAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        assert_eq!(values(&[0.0, 2.0, 5.0]), *y_analyzed);
+    }
+
+    #[test]
+    fn not_equal_splits_an_input_range() {
+        let program = "input(x, 1, 3); if (x != 2) {y := x} else {skip}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ValueSetAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        assert_eq!(values(&[1.0, 3.0]), *y_analyzed);
+
+        let program = "input(x, 1, 3); x := x / 2; if (x != 1) {y := x} else {skip}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ValueSetAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let y_analyzed = post_analyzed.lookup_var("y").unwrap();
+        assert_eq!(
+            ValueSetAbstraction::Interval(Interval::new(0.5, 1.5)),
+            *y_analyzed
+        );
+    }
+
+    #[test]
+    fn fall_back_to_interval() {
+        let program = "x := 0.5; while (x < 20) {x := x + 1}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<ValueSetAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
+        assert_eq!(
+            ValueSetAbstraction::Interval(Interval::new(20.0, 21.0)),
+            *x_analyzed
+        );
+    }
+
+    #[test]
+    fn widening_after_too_many_values() {
+        let program = "x := 0; while (x >= 0) {x := x + 1}";
+        let command = parse(program);
+
+        let params = Params::with_widening();
+
+        let mut pre: MemoryState<ValueSetAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &params);
+
+        assert!(post_analyzed.is_unreachable());
+    }
+
+    fn value_set_abstraction() -> impl Strategy<Value = ValueSetAbstraction> + Clone {
+        prop_oneof![
+            Just(ValueSetAbstraction::Bottom),
+            Just(ValueSetAbstraction::Top),
+            proptest::collection::vec(-1000i32..1000, 1..12)
+                .prop_map(|v| ValueSetAbstraction::from_values(v.into_iter().map(f64::from))),
+            (-1000i32..1000, 1i32..1000).prop_map(|(a, width)| {
+                ValueSetAbstraction::Interval(Interval::new(a as f64, (a + width) as f64))
+            }),
+        ]
+    }
+
+    laws!(laws, value_set_abstraction(), MAX_VALUES + 4);
+}
@@ -1,16 +1,18 @@
+use std::fmt;
+
 use chumsky::prelude::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Const {
     Const(f64),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Var {
     Var(String),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum SExpr {
     CExpr(Const),
     VExpr(Var),
@@ -19,9 +21,117 @@ pub enum SExpr {
     Sub(Box<SExpr>, Box<SExpr>),
     Mul(Box<SExpr>, Box<SExpr>),
     Div(Box<SExpr>, Box<SExpr>),
+    /// `x % y`, the remainder of the integer parts of `x` and `y`, which has the sign of `x`
+    Rem(Box<SExpr>, Box<SExpr>),
+    /// `x div y`, the quotient of the integer parts of `x` and `y` rounded towards zero
+    IntDiv(Box<SExpr>, Box<SExpr>),
+    /// `min(x, y)`
+    Min(Box<SExpr>, Box<SExpr>),
+    /// `max(x, y)`
+    Max(Box<SExpr>, Box<SExpr>),
+    /// `abs(x)`
+    Abs(Box<SExpr>),
+    /// `a[i]`
+    Index(Var, Box<SExpr>),
+    /// `len(a)`
+    Len(Var),
+    /// `true` or `false`
+    Bool(bool),
+}
+
+impl fmt::Display for SExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // operands with operators are put in parentheses, e.g. `(a + b) * c`
+        let operand = |sexpr: &SExpr| match sexpr {
+            SExpr::Neg(_)
+            | SExpr::Add(_, _)
+            | SExpr::Sub(_, _)
+            | SExpr::Mul(_, _)
+            | SExpr::Div(_, _)
+            | SExpr::Rem(_, _)
+            | SExpr::IntDiv(_, _) => format!("({})", sexpr),
+            _ => sexpr.to_string(),
+        };
+        match self {
+            SExpr::CExpr(Const::Const(number)) => write!(f, "{}", number),
+            SExpr::VExpr(Var::Var(ident)) => write!(f, "{}", ident),
+            SExpr::Neg(x) => write!(f, "-{}", operand(x)),
+            SExpr::Add(x, y) => write!(f, "{} + {}", operand(x), operand(y)),
+            SExpr::Sub(x, y) => write!(f, "{} - {}", operand(x), operand(y)),
+            SExpr::Mul(x, y) => write!(f, "{} * {}", operand(x), operand(y)),
+            SExpr::Div(x, y) => write!(f, "{} / {}", operand(x), operand(y)),
+            SExpr::Rem(x, y) => write!(f, "{} % {}", operand(x), operand(y)),
+            SExpr::IntDiv(x, y) => write!(f, "{} div {}", operand(x), operand(y)),
+            SExpr::Min(x, y) => write!(f, "min({}, {})", x, y),
+            SExpr::Max(x, y) => write!(f, "max({}, {})", x, y),
+            SExpr::Abs(x) => write!(f, "abs({})", x),
+            SExpr::Index(Var::Var(ident), index) => write!(f, "{}[{}]", ident, index),
+            SExpr::Len(Var::Var(ident)) => write!(f, "len({})", ident),
+            SExpr::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// The type of a declared variable.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Type {
+    Int,
+    Float,
+    /// `true` and `false`, which are represented by 1 and 0.
+    Bool,
+    /// A fixed-width integer like `i8` or `u32`.
+    Machine(IntType),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Bool => write!(f, "bool"),
+            Type::Machine(typ) => write!(f, "{}", typ),
+        }
+    }
+}
+
+/// A fixed-width integer type. Values that do not fit into the type wrap around if the type is
+/// declared as `wrapping`, e.g. `var x: wrapping u8`, and trap otherwise.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct IntType {
+    pub signed: bool,
+    pub bits: u8,
+    pub wrapping: bool,
+}
+
+impl IntType {
+    /// The smallest value of the type.
+    pub fn min(&self) -> f64 {
+        match self.signed {
+            true => -(2f64.powi(self.bits as i32 - 1)),
+            false => 0.0,
+        }
+    }
+
+    /// The largest value of the type, which is rounded to the next float for 64 bits.
+    pub fn max(&self) -> f64 {
+        match self.signed {
+            true => 2f64.powi(self.bits as i32 - 1) - 1.0,
+            false => 2f64.powi(self.bits as i32) - 1.0,
+        }
+    }
+}
+
+impl fmt::Display for IntType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.wrapping {
+            write!(f, "wrapping ")?;
+        }
+        let sign = if self.signed { 'i' } else { 'u' };
+        write!(f, "{}{}", sign, self.bits)
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum BExpr {
     GE(Var, Const),
     GT(Var, Const),
@@ -65,50 +175,169 @@ impl BExpr {
             }
         }
     }
+
+    /// The equivalent guard for an integer variable, whose comparisons are not strict and whose
+    /// bounds are integers, e.g. `x <= 9` for `x < 10` and `x >= 3` for `x > 2.5`.
+    pub fn for_integers(&self) -> Self {
+        match self {
+            BExpr::GT(var, Const::Const(number)) => {
+                BExpr::GE(var.clone(), Const::Const(number.floor() + 1.0))
+            }
+            BExpr::GE(var, Const::Const(number)) => {
+                BExpr::GE(var.clone(), Const::Const(number.ceil()))
+            }
+            BExpr::LT(var, Const::Const(number)) => {
+                BExpr::LE(var.clone(), Const::Const(number.ceil() - 1.0))
+            }
+            BExpr::LE(var, Const::Const(number)) => {
+                BExpr::LE(var.clone(), Const::Const(number.floor()))
+            }
+            BExpr::EQ(_, _) | BExpr::NE(_, _) => self.clone(),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Partitioning {
+    Branches,
+    Iterations(u8),
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Command {
     Skip,
     Seq(Box<Command>, Box<Command>),
     Assign(Var, SExpr),
     Input(Var),
+    /// `var x: int` declares the type of `x`.
+    Declare(Var, Type),
+    /// `input(x, lo, hi)` reads a value between `lo` and `hi`.
+    InputRange(Var, Const, Const),
+    /// `array a[n]` declares an array of `n` cells that are initialized with 0.
+    Array(Var, SExpr),
+    /// `a[i] := e`
+    ArrayAssign(Var, SExpr, SExpr),
     If(BExpr, Box<Command>, Box<Command>),
     While(BExpr, Box<Command>),
+    /// `choose {c1} or {c2}` executes one of the commands nondeterministically.
+    Choose(Box<Command>, Box<Command>),
+    /// `for (init; guard; step) {body}`
+    For(Box<Command>, BExpr, Box<Command>, Box<Command>),
+    /// `do {body} while (guard)`
+    DoWhile(Box<Command>, BExpr),
+    Break,
+    Continue,
+    /// `assert(x < 10)` states that the guard holds whenever the command is reached.
+    Assert(BExpr),
+    Partition(Partitioning, Box<Command>),
+    Proc(Procedure),
+    /// `x := f(e1, ..., en)`, the last field is the offset of the call in the source and identifies
+    /// the call site.
+    Call(Var, String, Vec<SExpr>, usize),
+    /// A command and its offset in the source.
+    Located(usize, Box<Command>),
+}
+
+/// `proc name(params) { body; return ret }`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Procedure {
+    pub name: String,
+    pub params: Vec<Var>,
+    pub body: Box<Command>,
+    pub ret: SExpr,
+}
+
+/// `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32` or `u64`, optionally preceded by `wrapping`.
+fn int_type() -> impl Parser<char, Type, Error = Simple<char>> + Clone {
+    text::keyword("wrapping")
+        .padded()
+        .or_not()
+        .then(text::ident().try_map(|name: String, span| {
+            let (sign, bits) = name.split_at(1);
+            match (sign, bits.parse::<u8>()) {
+                ("i" | "u", Ok(bits @ (8 | 16 | 32 | 64))) => Ok((sign == "i", bits)),
+                _ => Err(Simple::custom(span, format!("unknown type {}", name))),
+            }
+        }))
+        .map(|(wrapping, (signed, bits))| {
+            Type::Machine(IntType {
+                signed,
+                bits,
+                wrapping: wrapping.is_some(),
+            })
+        })
 }
 
+/// The names of the operators that are written like calls.
+const BUILTINS: [&str; 3] = ["min", "max", "abs"];
+
 fn parser() -> impl Parser<char, Command, Error = Simple<char>> {
     let command: Recursive<'_, char, Command, Simple<char>> = recursive(|command| {
+        // a block may be empty and end with a semicolon
+        let block = command
+            .clone()
+            .then_ignore(end_of_sequence())
+            .delimited_by(just('{'), just('}'));
+
         let skip = text::keyword::<_, _, Simple<char>>("skip")
             .padded()
             .map(|_| Command::Skip);
 
-        let cconst_pos = text::int(10)
-            .padded()
-            .map(|s: String| Const::Const(s.parse().unwrap()));
-
-        let cconst_neg = just('-')
-            .padded()
-            .then(cconst_pos)
-            .map(|(_, Const::Const(number))| Const::Const(-number));
+        let cconst = constant();
 
-        let cconst = cconst_pos.or(cconst_neg);
+        let var = variable();
 
-        let var = text::ident::<char, Simple<char>>()
-            .padded()
-            .map(|s: String| Var::Var(s));
-
-        let const_expr = cconst.map(SExpr::CExpr);
+        let const_expr = cconst.clone().map(SExpr::CExpr);
 
         let var_expr = var.map(SExpr::VExpr);
 
-        let op = |s: String| just(s).padded();
+        // comments may appear between the tokens of an expression
+        let op = |s: String| just(s).padded_by(comments());
 
         let s_expr = recursive(|s_expr| {
+            let len = text::keyword("len")
+                .padded()
+                .ignore_then(var.delimited_by(just('('), just(')')))
+                .map(SExpr::Len);
+
+            let index = var
+                .then(s_expr.clone().delimited_by(just('['), just(']')))
+                .map(|(var, index)| SExpr::Index(var, Box::new(index)));
+
+            let binary = |name: &'static str| {
+                text::keyword(name).padded().ignore_then(
+                    s_expr
+                        .clone()
+                        .then_ignore(just(','))
+                        .then(s_expr.clone())
+                        .delimited_by(just('('), just(')')),
+                )
+            };
+
+            let min = binary("min").map(|(x, y)| SExpr::Min(Box::new(x), Box::new(y)));
+
+            let max = binary("max").map(|(x, y)| SExpr::Max(Box::new(x), Box::new(y)));
+
+            let abs = text::keyword("abs")
+                .padded()
+                .ignore_then(s_expr.clone().delimited_by(just('('), just(')')))
+                .map(|x| SExpr::Abs(Box::new(x)));
+
+            let boolean = text::keyword("true")
+                .to(SExpr::Bool(true))
+                .or(text::keyword("false").to(SExpr::Bool(false)))
+                .padded();
+
             let atom = const_expr
+                .or(boolean)
+                .or(len)
+                .or(min)
+                .or(max)
+                .or(abs)
+                .or(index)
                 .or(var_expr)
                 .or(s_expr.delimited_by(just('('), just(')')))
-                .padded();
+                .padded_by(comments());
 
             let unary = op("-".to_string())
                 .repeated()
@@ -121,12 +350,16 @@ fn parser() -> impl Parser<char, Command, Error = Simple<char>> {
                     op("*".to_string())
                         .to(SExpr::Mul as fn(_, _) -> _)
                         .or(op("/".to_string()).to(SExpr::Div as fn(_, _) -> _))
+                        .or(op("%".to_string()).to(SExpr::Rem as fn(_, _) -> _))
+                        .or(text::keyword("div")
+                            .padded()
+                            .to(SExpr::IntDiv as fn(_, _) -> _))
                         .then(unary)
                         .repeated(),
                 )
                 .foldl(|lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)));
 
-            let sum = product
+            product
                 .clone()
                 .then(
                     op("+".to_string())
@@ -135,62 +368,320 @@ fn parser() -> impl Parser<char, Command, Error = Simple<char>> {
                         .then(product)
                         .repeated(),
                 )
-                .foldl(|lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)));
-
-            sum
+                .foldl(|lhs, (op, rhs)| op(Box::new(lhs), Box::new(rhs)))
         });
 
-        let b_expr = var
-            .then(
-                op(">=".to_string())
-                    .or(op(">".to_string()))
-                    .or(op("<=".to_string()))
-                    .or(op("<".to_string()))
-                    .or(op("==".to_string())),
-            )
-            .then(cconst)
-            .map(|((v, o), c)| construct_bexpr(&o, v, c));
+        let b_expr = guard();
 
         let assign = var
             .then_ignore(just(":="))
             .then(s_expr.clone())
             .map(|(var, then)| Command::Assign(var, then));
 
+        let array = text::keyword("array")
+            .padded()
+            .ignore_then(var)
+            .then(s_expr.clone().delimited_by(just('['), just(']')))
+            .padded()
+            .map(|(var, length)| Command::Array(var, length));
+
+        let array_assign = var
+            .then(s_expr.clone().delimited_by(just('['), just(']')))
+            .padded()
+            .then_ignore(just(":="))
+            .then(s_expr.clone())
+            .map(|((var, index), value)| Command::ArrayAssign(var, index, value));
+
         let input = text::keyword("input")
             .ignore_then(var.delimited_by(just('('), just(')')))
             .padded()
             .map(Command::Input);
 
-        let cif = text::keyword("if")
+        let declare = text::keyword("var")
             .padded()
-            .ignore_then(b_expr.clone().delimited_by(just('('), just(')')))
+            .ignore_then(var)
+            .then_ignore(just(':'))
+            .then(
+                text::keyword("int")
+                    .to(Type::Int)
+                    .or(text::keyword("float").to(Type::Float))
+                    .or(text::keyword("bool").to(Type::Bool))
+                    .or(int_type())
+                    .padded(),
+            )
+            .map(|(var, typ)| Command::Declare(var, typ));
+
+        let input_range = text::keyword("input")
+            .ignore_then(
+                var.then_ignore(just(','))
+                    .then(cconst.clone())
+                    .then_ignore(just(','))
+                    .then(cconst.clone())
+                    .delimited_by(just('('), just(')')),
+            )
+            .padded()
+            .map(|((var, lo), hi)| Command::InputRange(var, lo, hi));
+
+        let choose = text::keyword("choose")
             .padded()
-            .then(command.clone().delimited_by(just('{'), just('}')))
+            .ignore_then(block.clone())
             .padded()
-            .then_ignore(text::keyword("else"))
+            .then_ignore(text::keyword("or"))
             .padded()
-            .then(command.clone().delimited_by(just('{'), just('}')))
+            .then(block.clone())
             .padded()
-            .map(|((b_expr, c1), c2)| Command::If(b_expr, Box::new(c1), Box::new(c2)));
+            .map(|(c1, c2)| Command::Choose(Box::new(c1), Box::new(c2)));
+
+        let cif = recursive(|cif| {
+            text::keyword("if")
+                .padded()
+                .ignore_then(b_expr.clone().delimited_by(just('('), just(')')))
+                .padded()
+                .then(block.clone())
+                .padded()
+                .then(
+                    comments()
+                        .ignore_then(text::keyword("else"))
+                        .padded()
+                        .ignore_then(block.clone().or(cif))
+                        .or_not(),
+                )
+                .padded()
+                .map(|((b_expr, c1), c2)| {
+                    Command::If(b_expr, Box::new(c1), Box::new(c2.unwrap_or(Command::Skip)))
+                })
+        });
 
         let cwhile = text::keyword("while")
             .padded()
             .ignore_then(b_expr.clone().delimited_by(just('('), just(')')))
             .padded()
-            .then(command.clone().delimited_by(just('{'), just('}')))
+            .then(block.clone())
             .padded()
             .map(|(b_expr, c)| Command::While(b_expr, Box::new(c)));
 
-        let single_command = input.or(cif).or(skip).or(assign).or(cwhile);
+        let cfor = text::keyword("for")
+            .padded()
+            .ignore_then(
+                command
+                    .clone()
+                    .then_ignore(just(';'))
+                    .then(b_expr.clone())
+                    .then_ignore(just(';'))
+                    .then(command.clone())
+                    .delimited_by(just('('), just(')')),
+            )
+            .padded()
+            .then(block.clone())
+            .padded()
+            .map(|(((init, b_expr), step), body)| {
+                Command::For(Box::new(init), b_expr, Box::new(step), Box::new(body))
+            });
 
-        single_command.separated_by(just(';')).map(|c| {
-            c.into_iter()
-                .reduce(|acc, c| Command::Seq(Box::new(acc), Box::new(c)))
-                .unwrap()
-        })
+        let do_while = text::keyword("do")
+            .padded()
+            .ignore_then(block.clone())
+            .padded()
+            .then_ignore(comments().ignore_then(text::keyword("while")))
+            .padded()
+            .then(b_expr.clone().delimited_by(just('('), just(')')))
+            .padded()
+            .map(|(c, b_expr)| Command::DoWhile(Box::new(c), b_expr));
+
+        let assert = text::keyword("assert")
+            .padded()
+            .ignore_then(b_expr.clone().delimited_by(just('('), just(')')))
+            .padded()
+            .map(Command::Assert);
+
+        let cbreak = text::keyword("break").padded().to(Command::Break);
+
+        let ccontinue = text::keyword("continue").padded().to(Command::Continue);
+
+        let partition = just('@').ignore_then(text::keyword("partition")).padded();
+
+        let partitioned_if = partition
+            .clone()
+            .ignore_then(cif.clone())
+            .map(|c| Command::Partition(Partitioning::Branches, Box::new(c)));
+
+        let partitioned_while = partition
+            .ignore_then(
+                text::int(10)
+                    .try_map(|s: String, span| {
+                        s.parse::<u8>()
+                            .map_err(|err| Simple::custom(span, err.to_string()))
+                    })
+                    .delimited_by(just('('), just(')')),
+            )
+            .padded()
+            .then(cwhile.clone())
+            .map(|(k, c)| Command::Partition(Partitioning::Iterations(k), Box::new(c)));
+
+        let call = var
+            .then_ignore(just(":="))
+            .then(text::ident().padded().try_map(|name: String, span| {
+                // `min`, `max` and `abs` are operators, not procedures
+                match BUILTINS.contains(&name.as_str()) {
+                    true => Err(Simple::custom(span, format!("{} is not a procedure", name))),
+                    false => Ok(name),
+                }
+            }))
+            .then(
+                s_expr
+                    .clone()
+                    .separated_by(just(','))
+                    .delimited_by(just('('), just(')')),
+            )
+            .padded()
+            .map_with_span(|((var, name), args), span: std::ops::Range<usize>| {
+                Command::Call(var, name, args, span.start)
+            });
+
+        let proc = text::keyword("proc")
+            .padded()
+            .ignore_then(text::ident().padded())
+            .then(
+                var.separated_by(just(','))
+                    .delimited_by(just('('), just(')')),
+            )
+            .padded()
+            .then(
+                command
+                    .clone()
+                    .then_ignore(just(';'))
+                    .or_not()
+                    .then(
+                        comments()
+                            .ignore_then(text::keyword("return"))
+                            .padded()
+                            .ignore_then(s_expr.clone()),
+                    )
+                    .delimited_by(just('{'), just('}')),
+            )
+            .padded()
+            .map(|((name, params), (body, ret))| {
+                Command::Proc(Procedure {
+                    name,
+                    params,
+                    body: Box::new(body.unwrap_or(Command::Skip)),
+                    ret,
+                })
+            });
+
+        let single_command = partitioned_if
+            .or(partitioned_while)
+            .or(proc)
+            .or(array)
+            .or(declare)
+            .or(input)
+            .or(input_range)
+            .or(cif)
+            .or(choose)
+            .or(skip)
+            .or(cfor)
+            .or(do_while)
+            .or(cbreak)
+            .or(ccontinue)
+            .or(assert)
+            .or(call)
+            .or(array_assign)
+            .or(assign)
+            .or(cwhile);
+
+        comments()
+            .ignore_then(
+                single_command.map_with_span(|c, span: std::ops::Range<usize>| {
+                    Command::Located(span.start, Box::new(c))
+                }),
+            )
+            .then_ignore(comments())
+            .separated_by(just(';'))
+            .map(|c| {
+                c.into_iter()
+                    .reduce(|acc, c| Command::Seq(Box::new(acc), Box::new(c)))
+                    .unwrap_or(Command::Skip)
+            })
     });
 
-    command.then_ignore(end())
+    command.then_ignore(end_of_sequence()).then_ignore(end())
+}
+
+/// An optional semicolon after the last command of a sequence.
+fn end_of_sequence() -> impl Parser<char, (), Error = Simple<char>> + Clone {
+    comments()
+        .then(just(';').or_not())
+        .then(comments())
+        .ignored()
+}
+
+/// Line comments `// ...` and block comments `/* ... */` together with the whitespace around them.
+fn comments() -> impl Parser<char, (), Error = Simple<char>> + Clone {
+    let line_comment = just("//").then(filter(|c| *c != '\n').repeated());
+    let block_comment = just("/*").then(take_until(just("*/")));
+
+    line_comment
+        .ignored()
+        .or(block_comment.ignored())
+        .padded()
+        .repeated()
+        .padded()
+        .ignored()
+}
+
+/// A number like `2`, `-1.5` or `+1e-3`.
+fn constant() -> impl Parser<char, Const, Error = Simple<char>> + Clone {
+    let fraction = just('.').chain(text::digits(10));
+
+    let exponent = one_of("eE")
+        .chain(one_of("+-").or_not())
+        .chain::<char, _, _>(text::digits(10));
+
+    let cconst_pos = text::int(10)
+        .chain::<char, _, _>(fraction.or_not().flatten())
+        .chain::<char, _, _>(exponent.or_not().flatten())
+        .collect::<String>()
+        .padded()
+        .map(|s: String| Const::Const(s.parse().unwrap()));
+
+    let cconst_neg = just('-')
+        .padded()
+        .then(cconst_pos.clone())
+        .map(|(_, Const::Const(number))| Const::Const(-number));
+
+    cconst_pos
+        .clone()
+        .or(just('+').padded().ignore_then(cconst_pos))
+        .or(cconst_neg)
+}
+
+fn variable() -> impl Parser<char, Var, Error = Simple<char>> + Copy {
+    text::ident::<char, Simple<char>>()
+        .padded()
+        .map(|s: String| Var::Var(s))
+}
+
+/// A comparison of a variable with a constant like `x < 10`, or a boolean variable `b` or `!b`.
+fn guard() -> impl Parser<char, BExpr, Error = Simple<char>> + Clone {
+    let op = |s: &'static str| just(s).padded();
+    let var = variable();
+
+    var.then(
+        op(">=")
+            .or(op(">"))
+            .or(op("<="))
+            .or(op("<"))
+            .or(op("=="))
+            .or(op("!=")),
+    )
+    .then(constant())
+    .map(|((v, o), c)| construct_bexpr(o, v, c))
+    // a boolean variable `b` is the guard `b != 0`, its negation `!b` is `b == 0`
+    .or(just('!')
+        .padded()
+        .ignore_then(var)
+        .map(|v| BExpr::EQ(v, Const::Const(0.0))))
+    .or(var.map(|v| BExpr::NE(v, Const::Const(0.0))))
 }
 
 pub fn parse(src: &str) -> Command {
@@ -200,6 +691,22 @@ pub fn parse(src: &str) -> Command {
     }
 }
 
+/// Parses a single guard like `x >= 10`, e.g. a postcondition given on the command line.
+pub fn parse_guard(src: &str) -> BExpr {
+    match guard().then_ignore(end()).parse(src) {
+        Ok(bexpr) => bexpr,
+        Err(eval_err) => panic!("{:?}", eval_err),
+    }
+}
+
+/// The line and column of `offset` in `src`, both starting at 1.
+pub fn line_and_column(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset.min(src.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (line, column)
+}
+
 fn construct_bexpr(s: &str, v: Var, c: Const) -> BExpr {
     match s {
         "==" => BExpr::EQ(v, c),
@@ -216,17 +723,80 @@ fn construct_bexpr(s: &str, v: Var, c: Const) -> BExpr {
 mod tests {
     use super::*;
 
+    /// Parses `src` and removes the locations, which are only compared in `locations`.
+    fn parse(src: &str) -> Command {
+        without_locations(super::parse(src))
+    }
+
+    fn without_locations(c: Command) -> Command {
+        let strip = |c: Box<Command>| Box::new(without_locations(*c));
+        match c {
+            Command::Located(_, c) => without_locations(*c),
+            Command::Seq(c1, c2) => Command::Seq(strip(c1), strip(c2)),
+            Command::If(b, c1, c2) => Command::If(b, strip(c1), strip(c2)),
+            Command::While(b, c) => Command::While(b, strip(c)),
+            Command::For(init, b, step, c) => Command::For(strip(init), b, strip(step), strip(c)),
+            Command::DoWhile(c, b) => Command::DoWhile(strip(c), b),
+            Command::Choose(c1, c2) => Command::Choose(strip(c1), strip(c2)),
+            Command::Partition(p, c) => Command::Partition(p, strip(c)),
+            Command::Proc(procedure) => Command::Proc(Procedure {
+                body: strip(procedure.body),
+                ..procedure
+            }),
+            c => c,
+        }
+    }
+
+    #[test]
+    fn line_and_column_of_offsets() {
+        let src = "x := 1;\n  y := 2";
+        assert_eq!((1, 1), line_and_column(src, 0));
+        assert_eq!((2, 3), line_and_column(src, 10));
+    }
+
+    #[test]
+    fn locations() {
+        let program = "x := 1;\n  if (x > 0) {y := 2}";
+        let command = super::parse(program);
+        assert_eq!(
+            command,
+            Command::Seq(
+                Box::new(Command::Located(
+                    0,
+                    Box::new(Command::Assign(
+                        Var::Var("x".to_string()),
+                        SExpr::CExpr(Const::Const(1.0))
+                    ))
+                )),
+                Box::new(Command::Located(
+                    10,
+                    Box::new(Command::If(
+                        BExpr::GT(Var::Var("x".to_string()), Const::Const(0.0)),
+                        Box::new(Command::Located(
+                            22,
+                            Box::new(Command::Assign(
+                                Var::Var("y".to_string()),
+                                SExpr::CExpr(Const::Const(2.0))
+                            ))
+                        )),
+                        Box::new(Command::Skip)
+                    ))
+                ))
+            )
+        );
+    }
+
     #[test]
     fn skip() {
         let program = "skip";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(command, Command::Skip);
     }
 
     #[test]
     fn assign() {
         let program = "x := 50";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(
             command,
             Command::Assign(Var::Var("x".to_string()), SExpr::CExpr(Const::Const(50.0)))
@@ -236,14 +806,141 @@ mod tests {
     #[test]
     fn input() {
         let program = "input(x)";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(command, Command::Input(Var::Var("x".to_string())));
     }
 
+    #[test]
+    fn float_literals() {
+        let program = "x := 0.5 + 1e-3 * 2.5E+2 - -1.25 + +3";
+        let command = parse(program);
+
+        let number = |n| Box::new(SExpr::CExpr(Const::Const(n)));
+        assert_eq!(
+            command,
+            Command::Assign(
+                Var::Var("x".to_string()),
+                SExpr::Add(
+                    Box::new(SExpr::Sub(
+                        Box::new(SExpr::Add(
+                            number(0.5),
+                            Box::new(SExpr::Mul(number(0.001), number(250.0)))
+                        )),
+                        Box::new(SExpr::Neg(number(1.25)))
+                    )),
+                    number(3.0)
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn integer_operators() {
+        let program = "x := abs(y % 8) + max(min(y, 0), z div 2)";
+        let command = parse(program);
+
+        let var = |ident: &str| Box::new(SExpr::VExpr(Var::Var(ident.to_string())));
+        let number = |n| Box::new(SExpr::CExpr(Const::Const(n)));
+        assert_eq!(
+            command,
+            Command::Assign(
+                Var::Var("x".to_string()),
+                SExpr::Add(
+                    Box::new(SExpr::Abs(Box::new(SExpr::Rem(var("y"), number(8.0))))),
+                    Box::new(SExpr::Max(
+                        Box::new(SExpr::Min(var("y"), number(0.0))),
+                        Box::new(SExpr::IntDiv(var("z"), number(2.0)))
+                    ))
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn machine_integer_types() {
+        let program = "var x: u8; var y: wrapping i64";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Seq(
+                Box::new(Command::Declare(
+                    Var::Var("x".to_string()),
+                    Type::Machine(IntType {
+                        signed: false,
+                        bits: 8,
+                        wrapping: false
+                    })
+                )),
+                Box::new(Command::Declare(
+                    Var::Var("y".to_string()),
+                    Type::Machine(IntType {
+                        signed: true,
+                        bits: 64,
+                        wrapping: true
+                    })
+                ))
+            )
+        );
+        assert!(parser().parse("var z: i7").is_err());
+    }
+
+    #[test]
+    fn assertions_and_guards() {
+        let program = "assert(x < 10); assert(!b)";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Seq(
+                Box::new(Command::Assert(BExpr::LT(
+                    Var::Var("x".to_string()),
+                    Const::Const(10.0)
+                ))),
+                Box::new(Command::Assert(BExpr::EQ(
+                    Var::Var("b".to_string()),
+                    Const::Const(0.0)
+                )))
+            )
+        );
+        assert_eq!(
+            BExpr::GE(Var::Var("y".to_string()), Const::Const(-2.5)),
+            parse_guard(" y >= -2.5 ")
+        );
+    }
+
+    #[test]
+    fn input_range() {
+        let program = "input(x, -5, 10)";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::InputRange(
+                Var::Var("x".to_string()),
+                Const::Const(-5.0),
+                Const::Const(10.0)
+            )
+        );
+    }
+
+    #[test]
+    fn choose() {
+        let program = "choose {x := 1} or {skip}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Choose(
+                Box::new(Command::Assign(
+                    Var::Var("x".to_string()),
+                    SExpr::CExpr(Const::Const(1.0))
+                )),
+                Box::new(Command::Skip)
+            )
+        );
+    }
+
     #[test]
     fn cif() {
         let program = "if (x == 50) {skip} else {skip}";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(
             command,
             Command::If(
@@ -254,10 +951,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn not_equal() {
+        let program = "if (x != 50) {skip} else {skip}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::If(
+                BExpr::NE(Var::Var("x".to_string()), Const::Const(50.0)),
+                Box::new(Command::Skip),
+                Box::new(Command::Skip)
+            )
+        );
+    }
+
     #[test]
     fn cwhile() {
         let program = "while (x <= 10) {skip}";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(
             command,
             Command::While(
@@ -270,7 +981,7 @@ mod tests {
     #[test]
     fn seq() {
         let program = "skip;skip";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(
             Command::Seq(Box::new(Command::Skip), Box::new(Command::Skip)),
             command
@@ -280,7 +991,7 @@ mod tests {
     #[test]
     fn nested_seq() {
         let program = "while (x < 10) {skip;skip}; skip";
-        let command = parse(&program);
+        let command = parse(program);
         assert_eq!(
             Command::Seq(
                 Box::new(Command::While(
@@ -296,10 +1007,289 @@ mod tests {
         );
     }
 
+    #[test]
+    fn partitioned_if() {
+        let program = "@partition if (x == 50) {skip} else {skip}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Partition(
+                Partitioning::Branches,
+                Box::new(Command::If(
+                    BExpr::EQ(Var::Var("x".to_string()), Const::Const(50.0)),
+                    Box::new(Command::Skip),
+                    Box::new(Command::Skip)
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn partitioned_while() {
+        let program = "@partition(3) while (x <= 10) {skip}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Partition(
+                Partitioning::Iterations(3),
+                Box::new(Command::While(
+                    BExpr::LE(Var::Var("x".to_string()), Const::Const(10.0)),
+                    Box::new(Command::Skip)
+                ))
+            )
+        );
+    }
+
     #[test]
     #[should_panic]
-    fn not_in_language() {
+    fn partition_without_iterations_on_while() {
+        let program = "@partition while (x <= 10) {skip}";
+        parse(program);
+    }
+
+    #[test]
+    fn procedure() {
+        let program = "proc f(a, b) {c := a + b; return c * 2}; x := f(1, y)";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Seq(
+                Box::new(Command::Proc(Procedure {
+                    name: "f".to_string(),
+                    params: vec![Var::Var("a".to_string()), Var::Var("b".to_string())],
+                    body: Box::new(Command::Assign(
+                        Var::Var("c".to_string()),
+                        SExpr::Add(
+                            Box::new(SExpr::VExpr(Var::Var("a".to_string()))),
+                            Box::new(SExpr::VExpr(Var::Var("b".to_string())))
+                        )
+                    )),
+                    ret: SExpr::Mul(
+                        Box::new(SExpr::VExpr(Var::Var("c".to_string()))),
+                        Box::new(SExpr::CExpr(Const::Const(2.0)))
+                    ),
+                })),
+                Box::new(Command::Call(
+                    Var::Var("x".to_string()),
+                    "f".to_string(),
+                    vec![
+                        SExpr::CExpr(Const::Const(1.0)),
+                        SExpr::VExpr(Var::Var("y".to_string()))
+                    ],
+                    41
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn procedure_without_statements() {
+        let program = "proc zero() {return 0}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Proc(Procedure {
+                name: "zero".to_string(),
+                params: vec![],
+                body: Box::new(Command::Skip),
+                ret: SExpr::CExpr(Const::Const(0.0)),
+            })
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let program = "array a[10]; a[i + 1] := len(a); x := a[0]";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::Seq(
+                Box::new(Command::Seq(
+                    Box::new(Command::Array(
+                        Var::Var("a".to_string()),
+                        SExpr::CExpr(Const::Const(10.0))
+                    )),
+                    Box::new(Command::ArrayAssign(
+                        Var::Var("a".to_string()),
+                        SExpr::Add(
+                            Box::new(SExpr::VExpr(Var::Var("i".to_string()))),
+                            Box::new(SExpr::CExpr(Const::Const(1.0)))
+                        ),
+                        SExpr::Len(Var::Var("a".to_string()))
+                    ))
+                )),
+                Box::new(Command::Assign(
+                    Var::Var("x".to_string()),
+                    SExpr::Index(
+                        Var::Var("a".to_string()),
+                        Box::new(SExpr::CExpr(Const::Const(0.0)))
+                    )
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn cfor() {
+        let program = "for (i := 0; i < 10; i := i + 1) {if (i == 5) {break} else {continue}}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::For(
+                Box::new(Command::Assign(
+                    Var::Var("i".to_string()),
+                    SExpr::CExpr(Const::Const(0.0))
+                )),
+                BExpr::LT(Var::Var("i".to_string()), Const::Const(10.0)),
+                Box::new(Command::Assign(
+                    Var::Var("i".to_string()),
+                    SExpr::Add(
+                        Box::new(SExpr::VExpr(Var::Var("i".to_string()))),
+                        Box::new(SExpr::CExpr(Const::Const(1.0)))
+                    )
+                )),
+                Box::new(Command::If(
+                    BExpr::EQ(Var::Var("i".to_string()), Const::Const(5.0)),
+                    Box::new(Command::Break),
+                    Box::new(Command::Continue)
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn do_while() {
+        let program = "do {x := x + 1} while (x < 10)";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::DoWhile(
+                Box::new(Command::Assign(
+                    Var::Var("x".to_string()),
+                    SExpr::Add(
+                        Box::new(SExpr::VExpr(Var::Var("x".to_string()))),
+                        Box::new(SExpr::CExpr(Const::Const(1.0)))
+                    )
+                )),
+                BExpr::LT(Var::Var("x".to_string()), Const::Const(10.0))
+            )
+        );
+    }
+
+    #[test]
+    fn empty_block() {
         let program = "while (x < 10) {}";
-        parse(&program);
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::While(
+                BExpr::LT(Var::Var("x".to_string()), Const::Const(10.0)),
+                Box::new(Command::Skip)
+            )
+        );
+
+        let program = "while (x < 10) { /* nothing */ ; }";
+        assert_eq!(command, parse(program));
+    }
+
+    #[test]
+    fn if_without_else() {
+        let program = "if (x == 50) {x := 0}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::If(
+                BExpr::EQ(Var::Var("x".to_string()), Const::Const(50.0)),
+                Box::new(Command::Assign(
+                    Var::Var("x".to_string()),
+                    SExpr::CExpr(Const::Const(0.0))
+                )),
+                Box::new(Command::Skip)
+            )
+        );
+    }
+
+    #[test]
+    fn else_if() {
+        let program = "if (x < 0) {y := 0} else if (x > 10) {y := 10} else {y := x}";
+        let command = parse(program);
+        assert_eq!(
+            command,
+            Command::If(
+                BExpr::LT(Var::Var("x".to_string()), Const::Const(0.0)),
+                Box::new(Command::Assign(
+                    Var::Var("y".to_string()),
+                    SExpr::CExpr(Const::Const(0.0))
+                )),
+                Box::new(Command::If(
+                    BExpr::GT(Var::Var("x".to_string()), Const::Const(10.0)),
+                    Box::new(Command::Assign(
+                        Var::Var("y".to_string()),
+                        SExpr::CExpr(Const::Const(10.0))
+                    )),
+                    Box::new(Command::Assign(
+                        Var::Var("y".to_string()),
+                        SExpr::VExpr(Var::Var("x".to_string()))
+                    ))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn trailing_semicolons() {
+        let program = "x := 1; while (x < 10) {x := x + 1;};";
+        let command = parse(program);
+        assert_eq!(command, parse("x := 1; while (x < 10) {x := x + 1}"));
+    }
+
+    #[test]
+    fn comments() {
+        let program = "// counts to 10
+            x := 1; /* start */
+            while (x < 10) {
+                x := x / 1 + 1 // step
+            }
+            // done
+        ";
+        let command = parse(program);
+        assert_eq!(command, parse("x := 1; while (x < 10) {x := x / 1 + 1}"));
+
+        let program = "proc f(a) { /* identity */ return a }; /* the end */";
+        let command = parse(program);
+        assert_eq!(command, parse("proc f(a) {return a}"));
+    }
+
+    #[test]
+    fn comments_between_tokens() {
+        let program = "if (x > 0) { y := 1 } // positive
+            else { y := 2 }";
+        let command = parse(program);
+        assert_eq!(command, parse("if (x > 0) {y := 1} else {y := 2}"));
+
+        let program = "if (x > 0) { y := 1 } /* positive */ else { y := 2 }";
+        let command = parse(program);
+        assert_eq!(command, parse("if (x > 0) {y := 1} else {y := 2}"));
+
+        let program = "do { x := x + 1 } /* step */ while (x < 10)";
+        let command = parse(program);
+        assert_eq!(command, parse("do {x := x + 1} while (x < 10)"));
+
+        let program = "x := /* one */ 1; y := x /* twice */ * 2 + // and one
+            1";
+        let command = parse(program);
+        assert_eq!(command, parse("x := 1; y := x * 2 + 1"));
+    }
+
+    #[test]
+    fn empty_program() {
+        assert_eq!(Command::Skip, parse(" // nothing\n"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_in_language() {
+        let program = "while (x < 10) {skip";
+        parse(program);
     }
 }
@@ -0,0 +1,182 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+
+use crate::command_parser::Command;
+
+pub use self::analyses::{
+    AvailableExpressions, DefiniteInitialization, Definition, LiveVariables, ReachingDefinitions,
+    VeryBusyExpressions,
+};
+pub use self::cfg::{Cfg, Expr, Kind, Node};
+
+mod analyses;
+mod cfg;
+
+/// The direction in which the facts flow through the control-flow graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+/// How the facts of several paths are combined where they meet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Confluence {
+    /// A fact holds if it holds on some path, the facts are joined by union.
+    May,
+    /// A fact holds if it holds on all paths, the facts are joined by intersection.
+    Must,
+}
+
+/// A monotone dataflow analysis whose facts are sets.
+pub trait Analysis {
+    type Fact: Ord + Clone + fmt::Debug;
+
+    const DIRECTION: Direction;
+    const CONFLUENCE: Confluence;
+
+    /// The facts at the entry of a forward analysis or at the exit of a backward analysis.
+    fn boundary(&self, _cfg: &Cfg) -> BTreeSet<Self::Fact> {
+        BTreeSet::new()
+    }
+
+    /// All facts of the graph, from which a must analysis starts. A may analysis starts from the
+    /// empty set.
+    fn universe(&self, _cfg: &Cfg) -> BTreeSet<Self::Fact> {
+        BTreeSet::new()
+    }
+
+    /// The facts after `node` in the direction of the analysis, given the facts before it. The
+    /// function has to be monotone for the iteration to terminate.
+    fn transfer(&self, node: &Node, facts: &BTreeSet<Self::Fact>) -> BTreeSet<Self::Fact>;
+}
+
+/// The facts before and after a node in the order of execution, whatever the direction of the
+/// analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facts<F> {
+    pub before: BTreeSet<F>,
+    pub after: BTreeSet<F>,
+}
+
+/// The least fixpoint of `analysis` on `cfg` for a may analysis and the greatest one for a must
+/// analysis, computed with a worklist. The facts are indexed like the nodes.
+pub fn solve<A: Analysis>(analysis: &A, cfg: &Cfg) -> Vec<Facts<A::Fact>> {
+    let (sources, targets, start) = match A::DIRECTION {
+        Direction::Forward => (&cfg.preds, &cfg.succs, cfg.entry),
+        Direction::Backward => (&cfg.succs, &cfg.preds, cfg.exit),
+    };
+    let init = match A::CONFLUENCE {
+        Confluence::May => BTreeSet::new(),
+        Confluence::Must => analysis.universe(cfg),
+    };
+    let mut input = vec![init.clone(); cfg.nodes.len()];
+    let mut output = vec![init.clone(); cfg.nodes.len()];
+
+    let mut worklist: VecDeque<_> = match A::DIRECTION {
+        Direction::Forward => (0..cfg.nodes.len()).collect(),
+        Direction::Backward => (0..cfg.nodes.len()).rev().collect(),
+    };
+    let mut queued = vec![true; cfg.nodes.len()];
+    while let Some(node) = worklist.pop_front() {
+        queued[node] = false;
+        input[node] = match node == start {
+            true => analysis.boundary(cfg),
+            false => {
+                let mut facts = sources[node].iter().map(|source| &output[*source]);
+                let first = facts.next().cloned().unwrap_or_else(|| init.clone());
+                facts.fold(first, |combined, facts| match A::CONFLUENCE {
+                    Confluence::May => &combined | facts,
+                    Confluence::Must => &combined & facts,
+                })
+            }
+        };
+        let facts = analysis.transfer(&cfg.nodes[node], &input[node]);
+        if facts != output[node] {
+            output[node] = facts;
+            for target in &targets[node] {
+                if !queued[*target] {
+                    queued[*target] = true;
+                    worklist.push_back(*target);
+                }
+            }
+        }
+    }
+
+    input
+        .into_iter()
+        .zip(output)
+        .map(|(input, output)| match A::DIRECTION {
+            Direction::Forward => Facts {
+                before: input,
+                after: output,
+            },
+            Direction::Backward => Facts {
+                before: output,
+                after: input,
+            },
+        })
+        .collect()
+}
+
+/// The facts of `analysis` before and after every statement and guard of `c`, including the
+/// statements of procedure bodies, ordered by their offsets.
+pub fn analyze<A: Analysis>(analysis: &A, c: &Command) -> Vec<(usize, Facts<A::Fact>)> {
+    let mut facts: Vec<_> = Cfg::build(c)
+        .iter()
+        .flat_map(|cfg| {
+            cfg.nodes
+                .iter()
+                .zip(solve(analysis, cfg))
+                .filter(|(node, _)| matches!(node.kind, Kind::Statement | Kind::Guard))
+                .map(|(node, facts)| (node.offset, facts))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    facts.sort_by_key(|(offset, _)| *offset);
+    facts
+}
+
+/// The variables that `c` may read before they are initialized, with the offsets of the
+/// statements that read them, ordered by the offsets. Return values are read at the offset of
+/// their procedure.
+pub fn uninitialized_reads(c: &Command) -> Vec<(usize, String)> {
+    let mut reads: Vec<_> = Cfg::build(c)
+        .iter()
+        .flat_map(|cfg| {
+            cfg.nodes
+                .iter()
+                .zip(solve(&DefiniteInitialization, cfg))
+                .flat_map(|(node, facts)| {
+                    node.uses
+                        .difference(&facts.before)
+                        .map(|ident| (node.offset, ident.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    reads.sort();
+    reads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_parser::parse;
+
+    #[test]
+    fn reads_of_uninitialized_variables() {
+        let program = "proc f(a) {return a + b}; input(x); if (x > 0) {y := 1} else {skip}; z := y + x; while (z > 0) {z := w; w := 0}; v := f(x)";
+        let reads = uninitialized_reads(&parse(program));
+
+        assert_eq!(
+            vec![
+                (0, "b".to_string()),
+                (69, "y".to_string()),
+                (96, "w".to_string()),
+            ],
+            reads
+        );
+    }
+}
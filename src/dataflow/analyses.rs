@@ -0,0 +1,256 @@
+use std::collections::BTreeSet;
+
+use super::{Analysis, Cfg, Confluence, Direction, Expr, Node};
+
+/// The variables that may be read before they are assigned again.
+pub struct LiveVariables;
+
+impl Analysis for LiveVariables {
+    type Fact = String;
+
+    const DIRECTION: Direction = Direction::Backward;
+    const CONFLUENCE: Confluence = Confluence::May;
+
+    fn transfer(&self, node: &Node, live: &BTreeSet<String>) -> BTreeSet<String> {
+        let mut live = live.clone();
+        if !node.weak {
+            for ident in &node.defs {
+                live.remove(ident);
+            }
+        }
+        live.extend(node.uses.iter().cloned());
+        live
+    }
+}
+
+/// An assignment of a variable and the offset of the statement that makes it, which is the
+/// offset of the procedure for its parameters.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Definition {
+    pub ident: String,
+    pub offset: usize,
+}
+
+/// The assignments whose values may not have been overwritten yet.
+pub struct ReachingDefinitions;
+
+impl Analysis for ReachingDefinitions {
+    type Fact = Definition;
+
+    const DIRECTION: Direction = Direction::Forward;
+    const CONFLUENCE: Confluence = Confluence::May;
+
+    fn transfer(&self, node: &Node, reaching: &BTreeSet<Definition>) -> BTreeSet<Definition> {
+        let mut reaching = reaching.clone();
+        if !node.weak {
+            reaching.retain(|definition| !node.defs.contains(&definition.ident));
+        }
+        reaching.extend(node.defs.iter().map(|ident| Definition {
+            ident: ident.clone(),
+            offset: node.offset,
+        }));
+        reaching
+    }
+}
+
+/// The expressions that have been evaluated on every path, and whose variables have not been
+/// assigned since.
+pub struct AvailableExpressions;
+
+impl Analysis for AvailableExpressions {
+    type Fact = Expr;
+
+    const DIRECTION: Direction = Direction::Forward;
+    const CONFLUENCE: Confluence = Confluence::Must;
+
+    fn universe(&self, cfg: &Cfg) -> BTreeSet<Expr> {
+        all_exprs(cfg)
+    }
+
+    fn transfer(&self, node: &Node, available: &BTreeSet<Expr>) -> BTreeSet<Expr> {
+        let mut available = available | &node.exprs;
+        available.retain(|expr| !assigns_variable_of(node, expr));
+        available
+    }
+}
+
+/// The expressions that are evaluated on every path before any of their variables is assigned.
+pub struct VeryBusyExpressions;
+
+impl Analysis for VeryBusyExpressions {
+    type Fact = Expr;
+
+    const DIRECTION: Direction = Direction::Backward;
+    const CONFLUENCE: Confluence = Confluence::Must;
+
+    fn universe(&self, cfg: &Cfg) -> BTreeSet<Expr> {
+        all_exprs(cfg)
+    }
+
+    fn transfer(&self, node: &Node, busy: &BTreeSet<Expr>) -> BTreeSet<Expr> {
+        let mut busy = busy.clone();
+        busy.retain(|expr| !assigns_variable_of(node, expr));
+        busy.extend(node.exprs.iter().cloned());
+        busy
+    }
+}
+
+/// The variables that are initialized on every path.
+pub struct DefiniteInitialization;
+
+impl Analysis for DefiniteInitialization {
+    type Fact = String;
+
+    const DIRECTION: Direction = Direction::Forward;
+    const CONFLUENCE: Confluence = Confluence::Must;
+
+    fn universe(&self, cfg: &Cfg) -> BTreeSet<String> {
+        cfg.nodes
+            .iter()
+            .flat_map(|node| node.defs.iter().chain(&node.uses).cloned())
+            .collect()
+    }
+
+    fn transfer(&self, node: &Node, initialized: &BTreeSet<String>) -> BTreeSet<String> {
+        let mut initialized = initialized.clone();
+        initialized.extend(node.defs.iter().cloned());
+        initialized
+    }
+}
+
+fn all_exprs(cfg: &Cfg) -> BTreeSet<Expr> {
+    cfg.nodes
+        .iter()
+        .flat_map(|node| node.exprs.iter().cloned())
+        .collect()
+}
+
+/// Whether `node` assigns a variable of `expr`, also if it only assigns a cell of an array.
+fn assigns_variable_of(node: &Node, expr: &Expr) -> bool {
+    node.defs.iter().any(|ident| expr.reads(ident))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_parser::parse;
+    use crate::dataflow::{analyze, Facts};
+
+    fn idents(idents: &[&str]) -> BTreeSet<String> {
+        idents.iter().map(|ident| ident.to_string()).collect()
+    }
+
+    fn texts<A: Analysis<Fact = Expr>>(analysis: &A, program: &str) -> Vec<(usize, Vec<String>)> {
+        analyze(analysis, &parse(program))
+            .into_iter()
+            .map(|(offset, facts)| {
+                (
+                    offset,
+                    facts.after.iter().map(|expr| expr.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn live_variables() {
+        let program = "x := 1; y := x + 2; while (y > 0) {y := y - x}; z := 3";
+        let facts = analyze(&LiveVariables, &parse(program));
+
+        assert_eq!(
+            vec![
+                (0, idents(&[]), idents(&["x"])),
+                (8, idents(&["x"]), idents(&["x", "y"])),
+                (20, idents(&["x", "y"]), idents(&["x", "y"])),
+                (35, idents(&["x", "y"]), idents(&["x", "y"])),
+                (48, idents(&[]), idents(&[])),
+            ],
+            facts
+                .into_iter()
+                .map(|(offset, Facts { before, after })| (offset, before, after))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn live_arrays_and_procedure_parameters() {
+        let program = "proc f(a, b) {c := a; return c}; array d[2]; d[0] := 1; x := f(d[1], 0)";
+        let facts = analyze(&LiveVariables, &parse(program));
+
+        let before: Vec<_> = facts.into_iter().map(|(_, facts)| facts.before).collect();
+        assert_eq!(
+            vec![idents(&["a"]), idents(&[]), idents(&["d"]), idents(&["d"])],
+            before
+        );
+    }
+
+    #[test]
+    fn reaching_definitions() {
+        let program = "x := 1; if (x > 0) {x := 2} else {y := 3}; while (x > 0) {x := x - 1}";
+        let facts = analyze(&ReachingDefinitions, &parse(program));
+
+        let definition = |ident: &str, offset| Definition {
+            ident: ident.to_string(),
+            offset,
+        };
+        let (offset, facts) = &facts[4];
+        assert_eq!(43, *offset);
+        assert_eq!(
+            BTreeSet::from([
+                definition("x", 0),
+                definition("x", 20),
+                definition("x", 58),
+                definition("y", 34),
+            ]),
+            facts.before
+        );
+    }
+
+    #[test]
+    fn definite_initialization() {
+        let program =
+            "input(x); if (x > 0) {y := 1} else {skip}; while (x > 0) {z := y; x := x - 1}";
+        let facts = analyze(&DefiniteInitialization, &parse(program));
+
+        let after: Vec<_> = facts.into_iter().map(|(_, facts)| facts.after).collect();
+        assert_eq!(
+            vec![
+                idents(&["x"]),
+                idents(&["x"]),
+                idents(&["x", "y"]),
+                idents(&["x"]),
+                idents(&["x"]),
+                idents(&["x", "z"]),
+                idents(&["x", "z"]),
+            ],
+            after
+        );
+    }
+
+    #[test]
+    fn available_expressions() {
+        let program = "x := a + b; y := a * b; while (y > 5) {a := a + 1; x := a + b}";
+        let available = texts(&AvailableExpressions, program);
+
+        assert_eq!(
+            vec![
+                (0, vec!["a + b".to_string()]),
+                (12, vec!["a * b".to_string(), "a + b".to_string()]),
+                (24, vec!["a + b".to_string()]),
+                (39, vec![]),
+                (51, vec!["a + b".to_string()]),
+            ],
+            available
+        );
+    }
+
+    #[test]
+    fn very_busy_expressions() {
+        let program = "input(a); if (a > 0) {x := b - a; y := a * 2} else {y := b - a; x := a * 2}";
+        let busy = texts(&VeryBusyExpressions, program);
+
+        let both = vec!["a * 2".to_string(), "b - a".to_string()];
+        assert_eq!(both, busy[0].1);
+        assert_eq!(both, busy[1].1);
+    }
+}
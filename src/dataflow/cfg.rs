@@ -0,0 +1,430 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::command_parser::{BExpr, Command, Procedure, SExpr, Var};
+
+/// What a node of a control-flow graph stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// The start of the program or of a procedure body, which assigns the parameters.
+    Entry,
+    /// The end of the program or of a procedure body, which evaluates the return value.
+    Exit,
+    Statement,
+    /// The guard of an `if` or of a loop.
+    Guard,
+}
+
+/// A statement or guard with the variables that it reads and assigns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    /// The offset of the statement in the source, the offset of the procedure for the entry and
+    /// exit of its body.
+    pub offset: usize,
+    pub kind: Kind,
+    pub defs: Vec<String>,
+    /// Whether the node assigns a single cell of an array and keeps the other cells.
+    pub weak: bool,
+    pub uses: BTreeSet<String>,
+    /// The expressions with operators that the node evaluates before it assigns `defs`.
+    pub exprs: BTreeSet<Expr>,
+}
+
+impl Node {
+    fn new(offset: usize, kind: Kind) -> Node {
+        Node {
+            offset,
+            kind,
+            defs: vec![],
+            weak: false,
+            uses: BTreeSet::new(),
+            exprs: BTreeSet::new(),
+        }
+    }
+
+    /// Records that the node evaluates `sexpr`.
+    fn read(&mut self, sexpr: &SExpr) {
+        match sexpr {
+            SExpr::CExpr(_) | SExpr::VExpr(_) | SExpr::Len(_) | SExpr::Bool(_) => {
+                self.uses.extend(vars(sexpr));
+                return;
+            }
+            SExpr::Neg(x) | SExpr::Abs(x) | SExpr::Index(_, x) => self.read(x),
+            SExpr::Add(x, y)
+            | SExpr::Sub(x, y)
+            | SExpr::Mul(x, y)
+            | SExpr::Div(x, y)
+            | SExpr::Rem(x, y)
+            | SExpr::IntDiv(x, y)
+            | SExpr::Min(x, y)
+            | SExpr::Max(x, y) => {
+                self.read(x);
+                self.read(y);
+            }
+        }
+        let vars = vars(sexpr);
+        self.uses.extend(vars.iter().cloned());
+        self.exprs.insert(Expr {
+            text: sexpr.to_string(),
+            vars,
+        });
+    }
+}
+
+/// The variables that `sexpr` reads, including the arrays.
+fn vars(sexpr: &SExpr) -> BTreeSet<String> {
+    match sexpr {
+        SExpr::CExpr(_) | SExpr::Bool(_) => BTreeSet::new(),
+        SExpr::VExpr(Var::Var(ident)) | SExpr::Len(Var::Var(ident)) => {
+            BTreeSet::from([ident.clone()])
+        }
+        SExpr::Neg(x) | SExpr::Abs(x) => vars(x),
+        SExpr::Index(Var::Var(ident), x) => {
+            let mut vars = vars(x);
+            vars.insert(ident.clone());
+            vars
+        }
+        SExpr::Add(x, y)
+        | SExpr::Sub(x, y)
+        | SExpr::Mul(x, y)
+        | SExpr::Div(x, y)
+        | SExpr::Rem(x, y)
+        | SExpr::IntDiv(x, y)
+        | SExpr::Min(x, y)
+        | SExpr::Max(x, y) => &vars(x) | &vars(y),
+    }
+}
+
+/// An expression with operators, which is identified by its source text.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Expr {
+    text: String,
+    vars: BTreeSet<String>,
+}
+
+impl Expr {
+    /// Whether the value of the expression depends on `ident`.
+    pub fn reads(&self, ident: &str) -> bool {
+        self.vars.contains(ident)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// The control-flow graph of the program or of a procedure body, whose nodes are the statements
+/// and guards. Procedure definitions are skipped, and a call is a single statement that reads
+/// the arguments and assigns the result, as procedures only see their parameters.
+#[derive(Debug, Clone)]
+pub struct Cfg {
+    pub nodes: Vec<Node>,
+    pub succs: Vec<Vec<usize>>,
+    pub preds: Vec<Vec<usize>>,
+    pub entry: usize,
+    pub exit: usize,
+}
+
+impl Cfg {
+    /// The graph of the program `c` followed by the graphs of the bodies of the procedures that
+    /// it defines.
+    pub fn build(c: &Command) -> Vec<Cfg> {
+        let mut builder = Builder::new(0, vec![]);
+        let exits = builder.command(c, vec![builder.cfg.entry]);
+        let mut procedures = std::mem::take(&mut builder.procedures);
+        let mut cfgs = vec![builder.finish(exits, None)];
+
+        procedures.reverse();
+        while let Some((offset, procedure)) = procedures.pop() {
+            let mut builder = Builder::new(offset, procedure.params.iter().collect());
+            let exits = builder.command(&procedure.body, vec![builder.cfg.entry]);
+            procedures.extend(builder.procedures.drain(..).rev());
+            cfgs.push(builder.finish(exits, Some(&procedure.ret)));
+        }
+        cfgs
+    }
+
+    fn add(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.succs.push(vec![]);
+        self.preds.push(vec![]);
+        self.nodes.len() - 1
+    }
+
+    fn connect(&mut self, preds: &[usize], node: usize) {
+        for pred in preds {
+            self.succs[*pred].push(node);
+            self.preds[node].push(*pred);
+        }
+    }
+}
+
+/// The `break` and `continue` statements of a loop, which are connected to the loop when its
+/// body is built.
+#[derive(Default)]
+struct Loop {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+struct Builder {
+    cfg: Cfg,
+    /// The offset of the innermost located statement.
+    offset: usize,
+    loops: Vec<Loop>,
+    procedures: Vec<(usize, Procedure)>,
+}
+
+impl Builder {
+    fn new(offset: usize, params: Vec<&Var>) -> Builder {
+        let mut cfg = Cfg {
+            nodes: vec![],
+            succs: vec![],
+            preds: vec![],
+            entry: 0,
+            exit: 0,
+        };
+        let mut entry = Node::new(offset, Kind::Entry);
+        entry.defs = params.iter().map(|Var::Var(ident)| ident.clone()).collect();
+        cfg.entry = cfg.add(entry);
+        Builder {
+            cfg,
+            offset,
+            loops: vec![],
+            procedures: vec![],
+        }
+    }
+
+    fn finish(mut self, exits: Vec<usize>, ret: Option<&SExpr>) -> Cfg {
+        let mut exit = Node::new(self.cfg.nodes[self.cfg.entry].offset, Kind::Exit);
+        if let Some(ret) = ret {
+            exit.read(ret);
+        }
+        self.cfg.exit = self.cfg.add(exit);
+        self.cfg.connect(&exits, self.cfg.exit);
+        self.cfg
+    }
+
+    /// Adds the nodes of `c` after `preds` and returns the nodes after which the execution
+    /// continues with the next statement.
+    fn command(&mut self, c: &Command, preds: Vec<usize>) -> Vec<usize> {
+        let node = match c {
+            Command::Located(offset, c) => {
+                self.offset = *offset;
+                match **c {
+                    Command::Skip => self.statement(&[], &[]),
+                    _ => return self.command(c, preds),
+                }
+            }
+            Command::Skip => return preds,
+            Command::Seq(c1, c2) => {
+                let preds = self.command(c1, preds);
+                return self.command(c2, preds);
+            }
+            Command::Assign(var, sexpr) => self.statement(&[var], &[sexpr]),
+            Command::Input(var) | Command::InputRange(var, _, _) => self.statement(&[var], &[]),
+            Command::Declare(_, _) => self.statement(&[], &[]),
+            Command::Array(var, length) => self.statement(&[var], &[length]),
+            Command::ArrayAssign(var, index, value) => {
+                let mut node = self.statement(&[var], &[&SExpr::VExpr(var.clone()), index, value]);
+                node.weak = true;
+                node
+            }
+            Command::Assert(bexpr) => self.statement(&[], &[&guarded(bexpr)]),
+            Command::Call(var, _, args, _) => {
+                self.statement(&[var], &args.iter().collect::<Vec<_>>())
+            }
+            Command::Break | Command::Continue => {
+                let node = self.statement(&[], &[]);
+                let node = self.cfg.add(node);
+                self.cfg.connect(&preds, node);
+                return match self.loops.last_mut() {
+                    Some(jumps) if matches!(c, Command::Break) => {
+                        jumps.breaks.push(node);
+                        vec![]
+                    }
+                    Some(jumps) => {
+                        jumps.continues.push(node);
+                        vec![]
+                    }
+                    None => vec![node],
+                };
+            }
+            Command::If(bexpr, c1, c2) => {
+                let guard = self.guard(bexpr, &preds);
+                let mut exits = self.command(c1, vec![guard]);
+                exits.extend(self.command(c2, vec![guard]));
+                return exits;
+            }
+            Command::Choose(c1, c2) => {
+                let mut exits = self.command(c1, preds.clone());
+                exits.extend(self.command(c2, preds));
+                return exits;
+            }
+            Command::While(bexpr, body) => {
+                let guard = self.guard(bexpr, &preds);
+                let jumps = self.body(body, vec![guard]);
+                self.cfg.connect(&jumps.continues, guard);
+                return [vec![guard], jumps.breaks].concat();
+            }
+            Command::For(init, bexpr, step, body) => {
+                let offset = self.offset;
+                let preds = self.command(init, preds);
+                self.offset = offset;
+                let guard = self.guard(bexpr, &preds);
+                let jumps = self.body(body, vec![guard]);
+                let exits = self.command(step, jumps.continues);
+                self.cfg.connect(&exits, guard);
+                return [vec![guard], jumps.breaks].concat();
+            }
+            Command::DoWhile(body, bexpr) => {
+                // the guard is added first, as it is the target of the back edge into the body
+                let guard = self.guard(bexpr, &[]);
+                let jumps = self.body(body, [preds, vec![guard]].concat());
+                self.cfg.connect(&jumps.continues, guard);
+                return [vec![guard], jumps.breaks].concat();
+            }
+            Command::Partition(_, c) => return self.command(c, preds),
+            Command::Proc(procedure) => {
+                self.procedures.push((self.offset, procedure.clone()));
+                return preds;
+            }
+        };
+        let node = self.cfg.add(node);
+        self.cfg.connect(&preds, node);
+        vec![node]
+    }
+
+    fn statement(&self, defs: &[&Var], sexprs: &[&SExpr]) -> Node {
+        let mut node = Node::new(self.offset, Kind::Statement);
+        node.defs = defs.iter().map(|Var::Var(ident)| ident.clone()).collect();
+        for sexpr in sexprs {
+            node.read(sexpr);
+        }
+        node
+    }
+
+    fn guard(&mut self, bexpr: &BExpr, preds: &[usize]) -> usize {
+        let mut node = Node::new(self.offset, Kind::Guard);
+        node.read(&guarded(bexpr));
+        let node = self.cfg.add(node);
+        self.cfg.connect(preds, node);
+        node
+    }
+
+    /// Adds the body of a loop after `preds`. The returned `continues` also contain the nodes
+    /// after which the body ends normally.
+    fn body(&mut self, body: &Command, preds: Vec<usize>) -> Loop {
+        self.loops.push(Loop::default());
+        let exits = self.command(body, preds);
+        let mut jumps = self.loops.pop().unwrap_or_default();
+        jumps.continues.extend(exits);
+        jumps
+    }
+}
+
+/// The variable that `bexpr` compares.
+fn guarded(bexpr: &BExpr) -> SExpr {
+    SExpr::VExpr(Var::Var(bexpr.get_ident().clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_parser::parse;
+
+    fn edges(cfg: &Cfg) -> Vec<(usize, usize)> {
+        let mut edges: Vec<_> = cfg
+            .succs
+            .iter()
+            .enumerate()
+            .flat_map(|(node, succs)| succs.iter().map(move |succ| (node, *succ)))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn loops_with_break_and_continue() {
+        let program = "i := 0; while (i < 10) {if (i == 5) {break} else {continue}}; x := i";
+        let cfgs = Cfg::build(&parse(program));
+
+        assert_eq!(1, cfgs.len());
+        let cfg = &cfgs[0];
+        let offsets: Vec<_> = cfg.nodes.iter().map(|node| node.offset).collect();
+        // entry, i := 0, while, if, break, continue, x := i, exit
+        assert_eq!(vec![0, 0, 8, 24, 37, 50, 62, 0], offsets);
+        assert_eq!(
+            vec![
+                (0, 1),
+                (1, 2),
+                (2, 3),
+                (2, 6),
+                (3, 4),
+                (3, 5),
+                (4, 6),
+                (5, 2),
+                (6, 7)
+            ],
+            edges(cfg)
+        );
+    }
+
+    #[test]
+    fn do_while_and_for() {
+        let program = "do {x := x + 1} while (x < 10); for (i := 0; i < 5; i := i + 1) {skip}";
+        let cfgs = Cfg::build(&parse(program));
+
+        let cfg = &cfgs[0];
+        let kinds: Vec<_> = cfg.nodes.iter().map(|node| node.kind).collect();
+        assert_eq!(
+            vec![
+                Kind::Entry,
+                Kind::Guard,
+                Kind::Statement,
+                Kind::Statement,
+                Kind::Guard,
+                Kind::Statement,
+                Kind::Statement,
+                Kind::Exit
+            ],
+            kinds
+        );
+        assert_eq!(
+            vec![
+                (0, 2),
+                (1, 2),
+                (1, 3),
+                (2, 1),
+                (3, 4),
+                (4, 5),
+                (4, 7),
+                (5, 6),
+                (6, 4)
+            ],
+            edges(cfg)
+        );
+    }
+
+    #[test]
+    fn procedures_and_arrays() {
+        let program =
+            "proc f(a) {b := a * 2; return b + a}; array c[3]; c[1] := c[0] + 1; y := f(c[1])";
+        let cfgs = Cfg::build(&parse(program));
+
+        assert_eq!(2, cfgs.len());
+        let main = &cfgs[0];
+        assert!(main.nodes[2].weak);
+        assert_eq!(vec!["c".to_string()], main.nodes[2].defs);
+        let body = &cfgs[1];
+        assert_eq!(vec!["a".to_string()], body.nodes[body.entry].defs);
+        assert_eq!(
+            BTreeSet::from(["a".to_string(), "b".to_string()]),
+            body.nodes[body.exit].uses
+        );
+        let exprs: Vec<_> = body.nodes[1].exprs.iter().map(|e| e.to_string()).collect();
+        assert_eq!(vec!["a * 2"], exprs);
+    }
+}
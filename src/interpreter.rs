@@ -1,8 +1,33 @@
 use core::f64;
-use std::fmt::Debug;
-use std::{cmp, collections::HashMap, convert::From, ops};
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+use std::{cmp, collections::HashMap, collections::HashSet, convert::From, ops};
 
-use crate::command_parser::{BExpr, Command, Const, SExpr, Var};
+use rand::Rng;
+
+use crate::command_parser::{BExpr, Command, Const, IntType, SExpr, Type, Var};
+
+use self::arrays::ArrayAbstraction;
+pub use self::backward::{Precondition, Target};
+pub use self::cost::{Bound, Cost};
+use self::dead_code::Outcomes;
+pub use self::dead_code::{DeadCode, Guarded};
+use self::loops::{Flow, Jumps};
+use self::procedures::Procedures;
+use self::refinement::Trace;
+pub use self::refinement::Verdict;
+pub use self::termination::Termination;
+
+mod arrays;
+mod backward;
+mod cost;
+mod dead_code;
+mod loops;
+mod partitioning;
+mod procedures;
+mod refinement;
+mod termination;
 
 pub struct Top;
 pub struct Bottom;
@@ -13,11 +38,99 @@ pub trait AbstractProperties<A> {
     fn sat(a: &A, bexpr: &BExpr) -> bool;
     fn first_includes_second(a0: &A, a1: &A) -> bool;
     fn join(a0: &A, a1: &A) -> A;
+    /// The values that are represented by both `a0` and `a1`.
+    fn meet(a0: &A, a1: &A) -> A;
     fn refine(a: &A, bexpr: &BExpr) -> A;
     fn widen(a0: &A, a1: &A, treshold: &A) -> A;
+    /// The remainder of the integer parts of `a0` and `a1`, see `rem`.
+    fn rem(a0: &A, a1: &A) -> A;
+    /// The quotient of the integer parts of `a0` and `a1`, see `int_div`.
+    fn int_div(a0: &A, a1: &A) -> A;
+    fn min(a0: &A, a1: &A) -> A;
+    fn max(a0: &A, a1: &A) -> A;
+    fn abs(a: &A) -> A;
+    /// The values of `a` wrapped around into `[min, max]`, see `wrap`.
+    fn wrap(a: &A, min: f64, max: f64) -> A;
+    /// The integers among the values of `a`, for variables that only hold integers. `a` itself
+    /// for abstractions that do not gain precision from it.
+    fn integers(a: &A) -> A
+    where
+        A: Copy,
+    {
+        *a
+    }
+    /// Whether `a` may be NaN, which only abstractions of IEEE-754 floats represent.
+    fn may_be_nan(_a: &A) -> bool {
+        false
+    }
+    /// All exact results of an operation that may be rounded to a value of `a`, which is `a`
+    /// itself for abstractions whose operations are exact.
+    fn unround(a: &A) -> A
+    where
+        A: Copy,
+    {
+        *a
+    }
+    /// The result of `op` with IEEE-754 rounding to nearest if `a0` and `a1` are single values,
+    /// for the concrete interpretation of abstractions that round their bounds outwards. `None`
+    /// for abstractions whose operations are exact.
+    fn nearest(_a0: &A, _a1: &A, _op: fn(f64, f64) -> f64) -> Option<A> {
+        None
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// The remainder of `x % y`: both operands are truncated to integers and the remainder has the
+/// sign of `x`.
+pub fn rem(x: f64, y: f64) -> f64 {
+    x.trunc() % y.trunc()
+}
+
+/// The quotient of `x div y`: both operands are truncated to integers and the quotient is rounded
+/// towards zero.
+pub fn int_div(x: f64, y: f64) -> f64 {
+    (x.trunc() / y.trunc()).trunc()
+}
+
+/// Wraps the integer `x` around into `[min, max]` like a fixed-width integer, whose number of
+/// values `max - min + 1` is a power of two.
+pub fn wrap(x: f64, min: f64, max: f64) -> f64 {
+    min + (x - min).rem_euclid(max - min + 1.0)
+}
+
+/// The values of the variables of a state, or that no execution reaches the state.
+#[derive(Clone, PartialEq, Debug)]
+enum Env<A> {
+    Unreachable,
+    Reachable(HashMap<String, A>),
+}
+
+impl<A: Copy + PartialEq + From<Bottom>> Env<A> {
+    /// The state with the values in `state`, which is unreachable if a variable has no value.
+    fn new(state: HashMap<String, A>) -> Env<A> {
+        match state.values().any(|a| *a == Bottom.into()) {
+            true => Env::Unreachable,
+            false => Env::Reachable(state),
+        }
+    }
+
+    fn get(&self, ident: &str) -> Option<&A> {
+        match self {
+            Env::Unreachable => None,
+            Env::Reachable(state) => state.get(ident),
+        }
+    }
+
+    /// The variables and their values, none if the state is unreachable.
+    fn iter(&self) -> impl Iterator<Item = (&String, &A)> {
+        let state = match self {
+            Env::Unreachable => None,
+            Env::Reachable(state) => Some(state),
+        };
+        state.into_iter().flatten()
+    }
+}
+
+#[derive(Clone)]
 pub struct MemoryState<
     A: ops::Add<Output = A>
         + ops::Neg<Output = A>
@@ -32,14 +145,101 @@ pub struct MemoryState<
         + From<Top>
         + From<Bottom>,
 > {
-    state: HashMap<String, A>,
+    env: Env<A>,
+    arrays: HashMap<String, ArrayAbstraction<A>>,
+    procedures: Rc<RefCell<Procedures<A>>>,
+    call_string: Vec<usize>,
+    alarms: Rc<RefCell<Vec<Alarm>>>,
+    /// The outcomes of the guards by the offsets of their statements, see `record_guard`.
+    guards: Rc<RefCell<HashMap<usize, Outcomes>>>,
+    jumps: Rc<RefCell<Vec<Jumps<A>>>>,
+    /// The variables and arrays that may hold values that are not integers, all others are
+    /// integer variables.
+    floats: HashSet<String>,
+    /// The declared types of the variables in scope.
+    types: HashMap<String, Type>,
+    /// The variables that are not initialized on some of the paths that reach the state.
+    uninitialized: HashSet<String>,
+    /// Whether reading a variable that may not be initialized is an error, see
+    /// `Params::strict_initialization`.
+    strict: bool,
+    /// Whether the state is a concrete execution, whose arithmetic is IEEE-754 arithmetic with
+    /// rounding to nearest, see `interprete_command`.
+    concrete: bool,
+    /// The offset of the statement that is interpreted or analyzed.
+    location: usize,
+    /// The states before the located statements, if they are collected, see `visit`.
+    trace: Option<Rc<RefCell<Trace<A>>>>,
+    /// The number of loop iterations and calls that a bounded concrete execution may still
+    /// make, see `step`.
+    steps: Option<Rc<Cell<usize>>>,
+}
+
+/// A possible runtime error that was found while interpreting or analyzing a program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alarm {
+    /// The access of an array with an index that may be out of bounds, and the offset of the
+    /// statement with the access.
+    OutOfBounds(usize, String, SExpr),
+    /// A value that may not fit into the fixed-width integer variable it is assigned to, and the
+    /// offset of the assignment.
+    Overflow(usize, String, IntType),
+    /// An operation that may create NaN from operands that are not NaN, e.g. `0 / 0`, and the
+    /// offset of its statement.
+    NaN(usize, SExpr),
+    /// An assertion that may not hold, and the offset of the assertion.
+    Assertion(usize, BExpr),
+    /// A variable that may be read before it is initialized, and the offset of the statement
+    /// that reads it.
+    Uninitialized(usize, String),
+}
+
+impl Alarm {
+    /// The offset of the statement that raised the alarm.
+    pub fn offset(&self) -> usize {
+        match self {
+            Alarm::OutOfBounds(offset, _, _)
+            | Alarm::Overflow(offset, _, _)
+            | Alarm::NaN(offset, _)
+            | Alarm::Assertion(offset, _)
+            | Alarm::Uninitialized(offset, _) => *offset,
+        }
+    }
 }
 
+impl fmt::Display for Alarm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alarm::OutOfBounds(_, ident, index) => {
+                write!(f, "index {:?} of {} may be out of bounds", index, ident)
+            }
+            Alarm::Overflow(_, ident, typ) => write!(f, "possible overflow of {} ({})", ident, typ),
+            Alarm::NaN(_, sexpr) => write!(f, "{:?} may be NaN", sexpr),
+            Alarm::Assertion(_, bexpr) => write!(f, "assertion {:?} may fail", bexpr),
+            Alarm::Uninitialized(_, ident) => {
+                write!(f, "{} may be read before it is initialized", ident)
+            }
+        }
+    }
+}
+
+/// The number of joins after which a fixpoint iteration widens even if widening is disabled,
+/// since it may not stabilize otherwise.
+const MAX_JOINS: usize = 100;
+
 pub struct Params<A> {
     pub loop_unrollings: u8,
     pub use_widening: bool,
     pub widening_delays: u8,
     pub widening_treshold: A,
+    pub partition_branches: bool,
+    pub partition_iterations: u8,
+    pub call_string_length: u8,
+    pub array_segmentation: bool,
+    /// Whether the analysis treats reads of variables that may not be initialized as errors,
+    /// which raise an alarm and stop the executions in which the variable is not initialized,
+    /// instead of reading Top.
+    pub strict_initialization: bool,
 }
 
 impl<A: cmp::PartialOrd + AbstractProperties<A> + From<Top>> Params<A> {
@@ -49,10 +249,96 @@ impl<A: cmp::PartialOrd + AbstractProperties<A> + From<Top>> Params<A> {
             loop_unrollings: 0,
             widening_delays: 0,
             widening_treshold: A::top().into(),
+            partition_branches: false,
+            partition_iterations: 0,
+            call_string_length: 0,
+            array_segmentation: false,
+            strict_initialization: false,
+        }
+    }
+
+    /// The parameters of `no_widening` with widening after every iteration.
+    pub fn with_widening() -> Params<A> {
+        Params {
+            use_widening: true,
+            ..Params::no_widening()
         }
     }
 }
 
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > PartialEq for MemoryState<A>
+{
+    /// Two states are equal if they map the same variables and arrays to the same abstractions,
+    /// the procedures in scope and the alarms are not compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.env == other.env && self.arrays == other.arrays
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > Debug for MemoryState<A>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("MemoryState");
+        match &self.env {
+            Env::Unreachable => debug.field("state", &format_args!("unreachable")),
+            Env::Reachable(state) => debug.field("state", state),
+        };
+        if !self.arrays.is_empty() {
+            debug.field("arrays", &self.arrays);
+        }
+        debug.finish()
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > Default for MemoryState<A>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<
         A: ops::Add<Output = A>
             + ops::Neg<Output = A>
@@ -70,128 +356,417 @@ impl<
     > MemoryState<A>
 {
     pub fn new() -> MemoryState<A> {
-        MemoryState {
-            state: HashMap::new(),
-        }
+        Self::from_state(HashMap::new())
     }
 
+    /// The state with the values in `state`, which is unreachable if a variable is bottom.
     pub fn from_state(state: HashMap<String, A>) -> MemoryState<A> {
-        MemoryState { state }
+        MemoryState {
+            env: Env::new(state),
+            arrays: HashMap::new(),
+            procedures: Rc::default(),
+            call_string: Vec::new(),
+            alarms: Rc::default(),
+            guards: Rc::default(),
+            jumps: Rc::default(),
+            floats: HashSet::new(),
+            types: HashMap::new(),
+            uninitialized: HashSet::new(),
+            strict: false,
+            concrete: false,
+            location: 0,
+            trace: None,
+            steps: None,
+        }
     }
 
     pub fn lookup_var(&self, var: &str) -> Option<&A> {
-        self.state.get(var)
+        self.env.get(var)
+    }
+
+    /// The alarms raised so far, in the order in which they were raised first.
+    pub fn alarms(&self) -> Vec<Alarm> {
+        self.alarms.borrow().clone()
+    }
+
+    fn raise(&self, alarm: Alarm) {
+        let mut alarms = self.alarms.borrow_mut();
+        if !alarms.contains(&alarm) {
+            alarms.push(alarm);
+        }
     }
 
     pub fn interprete_command(&mut self, c: &Command) -> &MemoryState<A> {
+        self.concrete = true;
+        if let Flow::Break | Flow::Continue = self.interprete_statement(c) {
+            panic!("break or continue outside of a loop");
+        }
+        self
+    }
+
+    fn interprete_statement(&mut self, c: &Command) -> Flow {
         match c {
             Command::Skip => (),
             Command::Seq(c1, c2) => {
-                self.interprete_command(c1);
-                self.interprete_command(c2);
+                let flow = self.interprete_statement(c1);
+                if flow != Flow::Normal {
+                    return flow;
+                }
+                return self.interprete_statement(c2);
             }
             Command::Assign(Var::Var(ident), sexpr) => {
-                let a = self.interprete_sexpr(sexpr);
-                self.state.insert(ident.clone(), a);
+                if !self.assign(ident, sexpr) {
+                    return Flow::Trap;
+                }
             }
             Command::Input(Var::Var(ident)) => {
-                self.state.insert(ident.clone(), A::top().into());
+                self.insert(ident, self.top(ident));
+                self.set_float(ident, false);
+                self.assign_array_bounds(ident, None);
+            }
+            Command::Declare(Var::Var(ident), typ) => self.declare(ident, *typ),
+            Command::InputRange(Var::Var(ident), Const::Const(lo), Const::Const(hi)) => {
+                let (lo, hi) = (lo.ceil() as i64, hi.floor() as i64);
+                if lo > hi {
+                    panic!("input range without integers for {}", ident);
+                }
+                let number = rand::thread_rng().gen_range(lo..=hi);
+                if !self.store(ident, (number as f64).into()) {
+                    return Flow::Trap;
+                }
+                self.set_float(ident, false);
+                self.assign_array_bounds(ident, None);
+            }
+            Command::Array(Var::Var(ident), length) => self.declare_cells(ident, length),
+            Command::ArrayAssign(Var::Var(ident), index, value) => {
+                self.write_array(ident, index, value)
             }
             Command::If(bexpr, c1, c2) => {
-                if self.interprete_bexpr(&bexpr) {
-                    self.interprete_command(c1);
+                if self.interprete_bexpr(bexpr) {
+                    return self.interprete_statement(c1);
                 } else {
-                    self.interprete_command(c2);
+                    return self.interprete_statement(c2);
                 }
             }
-            Command::While(bexpr, c) => {
-                while self.interprete_bexpr(&bexpr) {
-                    self.interprete_command(c);
+            Command::Choose(c1, c2) => {
+                if rand::random() {
+                    return self.interprete_statement(c1);
+                } else {
+                    return self.interprete_statement(c2);
+                }
+            }
+            Command::While(bexpr, c) => return self.interprete_loop(bexpr, c, None, false),
+            Command::For(init, bexpr, step, c) => {
+                if self.interprete_statement(init) == Flow::Trap {
+                    return Flow::Trap;
+                }
+                return self.interprete_loop(bexpr, c, Some(step), false);
+            }
+            Command::DoWhile(c, bexpr) => return self.interprete_loop(bexpr, c, None, true),
+            Command::Break => return Flow::Break,
+            Command::Continue => return Flow::Continue,
+            Command::Assert(bexpr) => {
+                if !self.interprete_bexpr(bexpr) {
+                    self.raise(Alarm::Assertion(self.location, bexpr.clone()));
+                }
+            }
+            Command::Partition(_, c) => return self.interprete_statement(c),
+            Command::Located(offset, c) => {
+                self.location = *offset;
+                return self.interprete_statement(c);
+            }
+            Command::Proc(procedure) => self.define(procedure),
+            Command::Call(Var::Var(ident), name, args, _) => {
+                let (a, float) = self.interprete_call(name, args);
+                if !self.store(ident, a) {
+                    return Flow::Trap;
                 }
+                self.set_float(ident, float);
+                self.assign_array_bounds(ident, None);
             }
         }
-        self
+        Flow::Normal
+    }
+
+    /// Assigns `sexpr` to `ident` and returns whether the assignment does not trap for all values,
+    /// see `store`.
+    fn assign(&mut self, ident: &str, sexpr: &SExpr) -> bool {
+        let a = self.interprete_sexpr(sexpr);
+        let stored = self.store(ident, a);
+        self.set_float(ident, self.is_float(sexpr));
+        self.assign_array_bounds(ident, Some(sexpr));
+        stored
+    }
+
+    /// Stores `a` in `ident`. If a value may not fit into the fixed-width integer type of `ident`,
+    /// an alarm is raised and the values wrap around or trap, in which case only the values that
+    /// fit are stored. Returns `false` if all values trap.
+    fn store(&mut self, ident: &str, a: A) -> bool {
+        let a = match self.types.get(ident) {
+            Some(Type::Machine(typ)) => self.fit(ident, a, *typ),
+            _ => a,
+        };
+        self.insert(ident, a);
+        a != A::bottom().into()
+    }
+
+    /// Sets `ident` to `a` in a reachable state, which becomes unreachable if `a` is bottom.
+    fn insert(&mut self, ident: &str, a: A) {
+        if a == A::bottom().into() {
+            self.set_unreachable();
+        } else if let Env::Reachable(state) = &mut self.env {
+            state.insert(ident.to_string(), a);
+            self.uninitialized.remove(ident);
+        }
+    }
+
+    fn fit(&self, ident: &str, a: A, typ: IntType) -> A {
+        let var = Var::Var(ident.to_string());
+        let below = BExpr::LT(var.clone(), Const::Const(typ.min()));
+        let above = BExpr::GT(var.clone(), Const::Const(typ.max()));
+        if a == A::bottom().into() || !(A::sat(&a, &below) || A::sat(&a, &above)) {
+            return a;
+        }
+
+        self.raise(Alarm::Overflow(self.location, ident.to_string(), typ));
+        match typ.wrapping {
+            true => A::wrap(&a, typ.min(), typ.max()),
+            false => {
+                let a = A::refine(&a, &BExpr::GE(var.clone(), Const::Const(typ.min())));
+                A::refine(&a, &BExpr::LE(var, Const::Const(typ.max())))
+            }
+        }
+    }
+
+    /// Whether `sexpr` may have a value that is not an integer: constants with a fractional part,
+    /// divisions and float variables are floats, `%` and `div` always yield integers.
+    fn is_float(&self, sexpr: &SExpr) -> bool {
+        match sexpr {
+            SExpr::CExpr(Const::Const(number)) => number.fract() != 0.0,
+            SExpr::VExpr(Var::Var(ident)) => self.floats.contains(ident),
+            SExpr::Neg(sexpr) => self.is_float(sexpr),
+            SExpr::Add(sexpr1, sexpr2)
+            | SExpr::Sub(sexpr1, sexpr2)
+            | SExpr::Mul(sexpr1, sexpr2) => self.is_float(sexpr1) || self.is_float(sexpr2),
+            SExpr::Div(_, _) => true,
+            SExpr::Min(sexpr1, sexpr2) | SExpr::Max(sexpr1, sexpr2) => {
+                self.is_float(sexpr1) || self.is_float(sexpr2)
+            }
+            SExpr::Abs(sexpr) => self.is_float(sexpr),
+            SExpr::Index(Var::Var(ident), _) => self.floats.contains(ident),
+            SExpr::Rem(_, _) | SExpr::IntDiv(_, _) | SExpr::Len(_) | SExpr::Bool(_) => false,
+        }
+    }
+
+    /// Marks `ident` as a float variable or not, unless its type is declared.
+    fn set_float(&mut self, ident: &str, float: bool) {
+        let float = match self.types.get(ident) {
+            Some(typ) => *typ == Type::Float,
+            None => float,
+        };
+        if float {
+            self.floats.insert(ident.to_string());
+        } else {
+            self.floats.remove(ident);
+        }
     }
 
     fn interprete_sexpr(&mut self, sexpr: &SExpr) -> A {
         match sexpr {
-            SExpr::CExpr(Const::Const(number)) => number.clone().into(),
+            SExpr::CExpr(Const::Const(number)) => (*number).into(),
             SExpr::VExpr(Var::Var(ident)) => self.get_from_state_or_insert_default(ident),
             SExpr::Neg(sexpr) => -self.interprete_sexpr(sexpr),
             SExpr::Add(sexpr1, sexpr2) => {
-                self.interprete_sexpr(sexpr1) + self.interprete_sexpr(sexpr2)
+                self.arithmetic(sexpr, sexpr1, sexpr2, |a0, a1| a0 + a1, |x0, x1| x0 + x1)
             }
             SExpr::Sub(sexpr1, sexpr2) => {
-                self.interprete_sexpr(sexpr1) - self.interprete_sexpr(sexpr2)
+                self.arithmetic(sexpr, sexpr1, sexpr2, |a0, a1| a0 - a1, |x0, x1| x0 - x1)
             }
             SExpr::Mul(sexpr1, sexpr2) => {
-                self.interprete_sexpr(sexpr1) * self.interprete_sexpr(sexpr2)
+                self.arithmetic(sexpr, sexpr1, sexpr2, |a0, a1| a0 * a1, |x0, x1| x0 * x1)
             }
             SExpr::Div(sexpr1, sexpr2) => {
-                self.interprete_sexpr(sexpr1) / self.interprete_sexpr(sexpr2)
+                self.arithmetic(sexpr, sexpr1, sexpr2, |a0, a1| a0 / a1, |x0, x1| x0 / x1)
+            }
+            SExpr::Rem(sexpr1, sexpr2) => {
+                self.binary(sexpr, sexpr1, sexpr2, |a0, a1| A::rem(&a0, &a1))
             }
+            SExpr::IntDiv(sexpr1, sexpr2) => {
+                self.binary(sexpr, sexpr1, sexpr2, |a0, a1| A::int_div(&a0, &a1))
+            }
+            SExpr::Min(sexpr1, sexpr2) => {
+                self.binary(sexpr, sexpr1, sexpr2, |a0, a1| A::min(&a0, &a1))
+            }
+            SExpr::Max(sexpr1, sexpr2) => {
+                self.binary(sexpr, sexpr1, sexpr2, |a0, a1| A::max(&a0, &a1))
+            }
+            SExpr::Abs(sexpr) => A::abs(&self.interprete_sexpr(sexpr)),
+            SExpr::Index(Var::Var(ident), index) => self.read_array(ident, index),
+            SExpr::Len(Var::Var(ident)) => self.array_length(ident),
+            SExpr::Bool(true) => 1.0.into(),
+            SExpr::Bool(false) => 0.0.into(),
+        }
+    }
+
+    /// Applies `op` to the values of both operands and raises an alarm if it may create NaN.
+    fn binary(&mut self, sexpr: &SExpr, sexpr1: &SExpr, sexpr2: &SExpr, op: fn(A, A) -> A) -> A {
+        let a0 = self.interprete_sexpr(sexpr1);
+        let a1 = self.interprete_sexpr(sexpr2);
+        let a = op(a0, a1);
+        self.check_nan(sexpr, &a, &a0, &a1);
+        a
+    }
+
+    /// Like `binary` for an arithmetic operator, whose concrete execution computes the rounded
+    /// result `float_op` of single values, see `AbstractProperties::nearest`.
+    fn arithmetic(
+        &mut self,
+        sexpr: &SExpr,
+        sexpr1: &SExpr,
+        sexpr2: &SExpr,
+        op: fn(A, A) -> A,
+        float_op: fn(f64, f64) -> f64,
+    ) -> A {
+        let a0 = self.interprete_sexpr(sexpr1);
+        let a1 = self.interprete_sexpr(sexpr2);
+        let a = match A::nearest(&a0, &a1, float_op) {
+            Some(a) if self.concrete => a,
+            _ => op(a0, a1),
+        };
+        self.check_nan(sexpr, &a, &a0, &a1);
+        a
+    }
+
+    /// Raises an alarm if `sexpr` may create NaN from the operands `a0` and `a1` that are not
+    /// NaN.
+    fn check_nan(&self, sexpr: &SExpr, a: &A, a0: &A, a1: &A) {
+        if A::may_be_nan(a) && !A::may_be_nan(a0) && !A::may_be_nan(a1) {
+            self.raise(Alarm::NaN(self.location, sexpr.clone()));
         }
     }
 
     fn interprete_bexpr(&mut self, bexpr: &BExpr) -> bool {
         match bexpr {
             BExpr::GE(Var::Var(ident), Const::Const(number)) => {
-                self.get_from_state_or_insert_default(ident) >= number.clone().into()
+                self.get_from_state_or_insert_default(ident) >= (*number).into()
             }
             BExpr::GT(Var::Var(ident), Const::Const(number)) => {
-                self.get_from_state_or_insert_default(ident) > number.clone().into()
+                self.get_from_state_or_insert_default(ident) > (*number).into()
             }
             BExpr::LE(Var::Var(ident), Const::Const(number)) => {
-                self.get_from_state_or_insert_default(ident) <= number.clone().into()
+                self.get_from_state_or_insert_default(ident) <= (*number).into()
             }
             BExpr::LT(Var::Var(ident), Const::Const(number)) => {
-                self.get_from_state_or_insert_default(ident) < number.clone().into()
+                self.get_from_state_or_insert_default(ident) < (*number).into()
             }
             BExpr::EQ(Var::Var(ident), Const::Const(number)) => {
-                self.get_from_state_or_insert_default(ident) == number.clone().into()
+                self.get_from_state_or_insert_default(ident) == (*number).into()
             }
             BExpr::NE(Var::Var(ident), Const::Const(number)) => {
-                self.get_from_state_or_insert_default(ident) != number.clone().into()
+                self.get_from_state_or_insert_default(ident) != (*number).into()
             }
         }
     }
 
-    fn get_from_state_or_insert_default(&mut self, ident: &String) -> A {
-        if let Some(&number) = self.state.get(ident) {
+    fn declare(&mut self, ident: &str, typ: Type) {
+        self.types.insert(ident.to_string(), typ);
+        self.set_float(ident, typ == Type::Float);
+    }
+
+    /// The abstraction of all values of the type of `ident`, which are only 0 and 1 for booleans
+    /// and the range of a fixed-width integer type. Inputs and uninitialized variables are
+    /// integers, so they are finite and not NaN in abstractions that represent NaN.
+    fn top(&self, ident: &str) -> A {
+        let top = A::top().into();
+        let (min, max) = match self.types.get(ident) {
+            Some(Type::Bool) => (0.0, 1.0),
+            Some(Type::Machine(typ)) => (typ.min(), typ.max()),
+            _ if A::may_be_nan(&top) => (-f64::MAX, f64::MAX),
+            _ => return top,
+        };
+        let var = Var::Var(ident.to_string());
+        let a = A::refine(&top, &BExpr::GE(var.clone(), Const::Const(min)));
+        A::refine(&a, &BExpr::LE(var, Const::Const(max)))
+    }
+
+    fn get_from_state_or_insert_default(&mut self, ident: &str) -> A {
+        if self.is_unreachable() {
+            A::bottom().into()
+        } else if let Some(&number) = self.env.get(ident) {
+            if self.strict && self.uninitialized.remove(ident) {
+                self.raise(Alarm::Uninitialized(self.location, ident.to_string()));
+            }
             number
+        } else if self.strict {
+            self.raise(Alarm::Uninitialized(self.location, ident.to_string()));
+            self.set_unreachable();
+            A::bottom().into()
         } else {
-            let default = A::top().into();
-            self.state.insert(ident.clone(), default);
+            let default = self.top(ident);
+            self.insert(ident, default);
             default
         }
     }
 
     pub fn analyze_command(&mut self, c: &Command, params: &Params<A>) -> &MemoryState<A> {
-        if !self.all_vars_are_bottom() {
+        self.strict = params.strict_initialization;
+        let partitions = self.analyze_partitions(c, params);
+        *self = Self::merge(partitions, params);
+        self
+    }
+
+    fn analyze_statement(&mut self, c: &Command, params: &Params<A>) -> &MemoryState<A> {
+        if !self.is_unreachable() {
             match c {
                 Command::Skip => (),
                 Command::Seq(c1, c2) => {
-                    self.analyze_command(c1, params);
-                    self.analyze_command(c2, params);
+                    self.analyze_statement(c1, params);
+                    self.analyze_statement(c2, params);
                 }
                 Command::Assign(Var::Var(ident), sexpr) => {
-                    let a = self.interprete_sexpr(sexpr);
-                    self.state.insert(ident.clone(), a);
+                    if !self.assign(ident, sexpr) {
+                        self.set_unreachable();
+                    }
                 }
                 Command::Input(Var::Var(ident)) => {
-                    self.state.insert(ident.clone(), A::top().into());
+                    self.insert(ident, self.top(ident));
+                    self.set_float(ident, false);
+                    self.assign_array_bounds(ident, None);
+                }
+                Command::Declare(Var::Var(ident), typ) => self.declare(ident, *typ),
+                Command::InputRange(var, lo, hi) => {
+                    let Var::Var(ident) = var;
+                    let top = A::top().into();
+                    let a = A::refine(&top, &BExpr::GE(var.clone(), lo.clone()));
+                    let a = A::refine(&a, &BExpr::LE(var.clone(), hi.clone()));
+                    self.set_float(ident, false);
+                    if !self.store(ident, a) {
+                        self.set_unreachable();
+                    }
+                    self.assign_array_bounds(ident, None);
+                }
+                Command::Array(Var::Var(ident), length) => {
+                    self.declare_array(ident, length, params.array_segmentation)
+                }
+                Command::ArrayAssign(Var::Var(ident), index, value) => {
+                    self.write_array(ident, index, value)
                 }
                 Command::If(bexpr, c1, c2) => {
-                    let m1 = self
-                        .clone()
-                        .filter(bexpr)
-                        .to_owned()
-                        .analyze_command(c1, params)
-                        .to_owned();
+                    let mut m1 = self.clone();
+                    m1.filter(bexpr);
+                    self.filter(&bexpr.negate());
+                    self.record_guard(
+                        self.location,
+                        Guarded::If,
+                        !m1.is_unreachable(),
+                        !self.is_unreachable(),
+                    );
+
+                    m1.analyze_command(c1, params);
 
                     //println!("if: {:?}", &m1);
 
-                    self.filter(&bexpr.negate()).analyze_command(c2, params);
+                    self.analyze_command(c2, params);
 
                     //println!("else: {:?}", &self);
 
@@ -199,42 +774,37 @@ impl<
 
                     //println!("joined: {:?}", &self);
                 }
-                Command::While(bexpr, c) => {
-                    let mut i = 0;
-
-                    for _ in 0..params.loop_unrollings {
-                        self.analyze_command(c, params);
-                    }
-
-                    let mut nr_of_joins = 0;
-                    loop {
-                        let prev_m = self.clone();
-                        println!("prev m: {:?}", &prev_m);
-
-                        self.filter(bexpr);
-                        println!("filtered: {:?}", &self);
-
-                        self.analyze_command(c, params);
-                        println!("analyzed: {:?}", &self);
-
-                        if params.use_widening && nr_of_joins >= params.widening_delays {
-                            self.join_state(&prev_m, true, &params.widening_treshold);
-                            println!("widened: {:?}", &self);
-                        } else {
-                            self.join_state(&prev_m, false, &params.widening_treshold);
-                            println!("joined: {:?}", &self);
-                            nr_of_joins += 1;
-                        }
-
-                        if prev_m.includes(self) {
-                            break;
-                        }
-
-                        i += 1;
+                Command::Choose(c1, c2) => {
+                    let m1 = self.clone().analyze_command(c1, params).to_owned();
+                    self.analyze_command(c2, params);
+                    self.join_state(&m1, false, &params.widening_treshold);
+                }
+                Command::While(bexpr, c) => self.analyze_loop(bexpr, c, None, false, params),
+                Command::For(init, bexpr, step, c) => {
+                    let offset = self.location;
+                    self.analyze_statement(init, params);
+                    self.location = offset;
+                    self.analyze_loop(bexpr, c, Some(step), false, params);
+                }
+                Command::DoWhile(c, bexpr) => self.analyze_loop(bexpr, c, None, true, params),
+                Command::Break => self.jump(Flow::Break, params),
+                Command::Continue => self.jump(Flow::Continue, params),
+                Command::Assert(bexpr) => self.assert(bexpr),
+                Command::Partition(_, c) => {
+                    self.analyze_statement(c, params);
+                }
+                Command::Located(offset, c) => {
+                    self.visit(*offset, false);
+                    self.analyze_statement(c, params);
+                }
+                Command::Proc(procedure) => self.define(procedure),
+                Command::Call(Var::Var(ident), name, args, site) => {
+                    let (a, float) = self.analyze_call(name, args, *site, params);
+                    self.set_float(ident, float);
+                    if !self.store(ident, a) {
+                        self.set_unreachable();
                     }
-
-                    self.filter(&bexpr.negate());
-                    println!("negation filtered: {:?}", &self);
+                    self.assign_array_bounds(ident, None);
                 }
             }
         }
@@ -247,53 +817,268 @@ impl<
         use_widening: bool,
         widening_treshold: &A,
     ) -> &mut Self {
-        for (ident, a_other) in &other.state {
-            self.state
+        let state = match (&mut self.env, &other.env) {
+            (_, Env::Unreachable) => return self,
+            (Env::Unreachable, _) => {
+                self.env = other.env.clone();
+                self.arrays = other.arrays.clone();
+                self.floats = other.floats.clone();
+                self.uninitialized = other.uninitialized.clone();
+                self.types
+                    .extend(other.types.iter().map(|(ident, typ)| (ident.clone(), *typ)));
+                return self;
+            }
+            (Env::Reachable(state), Env::Reachable(_)) => state,
+        };
+        // the variables that only one of the states initializes
+        let uninitialized: Vec<_> = state
+            .keys()
+            .filter(|ident| other.env.get(ident).is_none())
+            .chain(
+                other
+                    .env
+                    .iter()
+                    .map(|(ident, _)| ident)
+                    .filter(|ident| !state.contains_key(*ident)),
+            )
+            .cloned()
+            .collect();
+        self.uninitialized.extend(uninitialized);
+        self.uninitialized
+            .extend(other.uninitialized.iter().cloned());
+        for (ident, a_other) in other.env.iter() {
+            state
                 .entry(ident.clone())
                 .and_modify(|a_self| {
                     *a_self = match use_widening {
-                        true => A::widen(&a_other, a_self, widening_treshold),
-                        false => A::join(a_self, &a_other),
+                        true => A::widen(a_other, a_self, widening_treshold),
+                        false => A::join(a_self, a_other),
                     }
                 })
-                .or_insert(a_other.clone());
+                .or_insert(*a_other);
+        }
+        for (ident, array_other) in &other.arrays {
+            self.arrays
+                .entry(ident.clone())
+                .and_modify(|array_self| {
+                    *array_self = array_self.join(array_other, use_widening, widening_treshold)
+                })
+                .or_insert(array_other.clone());
         }
+        self.floats.extend(other.floats.iter().cloned());
+        self.types
+            .extend(other.types.iter().map(|(ident, typ)| (ident.clone(), *typ)));
         self
     }
 
     fn filter(&mut self, bexpr: &BExpr) -> &mut Self {
+        if self.is_unreachable() {
+            return self;
+        }
         let ident = bexpr.get_ident();
         let a = self.get_from_state_or_insert_default(ident);
+        let (a, guard) = match self.floats.contains(ident) {
+            true => (a, bexpr.clone()),
+            false => (A::integers(&a), bexpr.for_integers()),
+        };
 
-        if A::sat(&a, bexpr) {
-            let a_filtered = A::refine(&a, bexpr);
-            self.state.insert(ident.clone(), a_filtered);
+        if A::sat(&a, &guard) {
+            let a_filtered = A::refine(&a, &guard);
+            self.insert(ident, a_filtered);
+            self.filter_array_bounds(bexpr);
         } else {
-            self.set_all_vars_to_bottom();
+            self.set_unreachable();
         }
 
         self
     }
 
+    /// Raises an alarm if the state may violate the assertion `bexpr`, and keeps only the states
+    /// that satisfy it.
+    fn assert(&mut self, bexpr: &BExpr) {
+        let mut violated = self.clone();
+        violated.filter(&bexpr.negate());
+        if !violated.is_unreachable() {
+            self.raise(Alarm::Assertion(self.location, bexpr.clone()));
+        }
+        self.filter(bexpr);
+    }
+
     fn includes(&self, other: &MemoryState<A>) -> bool {
-        for (ident, a_other) in &other.state {
-            if let Some(a_self) = self.state.get(ident) {
+        match (&self.env, &other.env) {
+            (_, Env::Unreachable) => return true,
+            (Env::Unreachable, _) => return false,
+            _ => (),
+        }
+        for (ident, a_other) in other.env.iter() {
+            if let Some(a_self) = self.env.get(ident) {
                 if !A::first_includes_second(a_self, a_other) {
                     return false;
                 }
             }
         }
+        for (ident, array_other) in &other.arrays {
+            if let Some(array_self) = self.arrays.get(ident) {
+                if !array_self.includes(array_other) {
+                    return false;
+                }
+            }
+        }
+
+        self.floats.is_superset(&other.floats)
+            && self.uninitialized.is_superset(&other.uninitialized)
+    }
+
+    fn set_unreachable(&mut self) {
+        self.env = Env::Unreachable;
+        self.arrays.clear();
+        self.uninitialized.clear();
+    }
+
+    /// Counts a loop iteration or a call of a bounded concrete execution and returns whether
+    /// the execution may make it.
+    fn step(&self) -> bool {
+        match &self.steps {
+            Some(steps) if steps.get() == 0 => false,
+            Some(steps) => {
+                steps.set(steps.get() - 1);
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Whether no execution reaches the state.
+    pub fn is_unreachable(&self) -> bool {
+        self.env == Env::Unreachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::parse;
+
+    fn unreachable() -> MemoryState<IntervalAbstraction> {
+        let mut m = MemoryState::new();
+        m.set_unreachable();
+        m
+    }
+
+    #[test]
+    fn unreachable_state_without_variables() {
+        let command = parse("y := 1; if (z > 0) {w := 1} else {w := 2}");
+
+        let mut m = unreachable();
+        let post_analyzed = m.analyze_command(&command, &Params::no_widening());
+
+        assert!(post_analyzed.is_unreachable());
+        assert_eq!(None, post_analyzed.lookup_var("y"));
+        assert_eq!(None, post_analyzed.lookup_var("w"));
+    }
+
+    #[test]
+    fn code_after_non_terminating_loop() {
+        let program =
+            "x := 0; while (x >= 0) {x := x + 1}; @partition if (z > 0) {y := 1} else {y := 2}";
+        let command = parse(program);
+
+        let mut m: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = m.analyze_command(&command, &Params::no_widening());
+
+        assert!(post_analyzed.is_unreachable());
+        assert_eq!(None, post_analyzed.lookup_var("y"));
+    }
+
+    #[test]
+    fn variables_assigned_after_break() {
+        let command = parse("x := 1; do {break; y := 1} while (y < 5)");
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = m.analyze_command(&command, &Params::no_widening());
+
+        assert!(!post_analyzed.is_unreachable());
+        assert_eq!(None, post_analyzed.lookup_var("y"));
+    }
+
+    #[test]
+    fn join_with_unreachable_state() {
+        let reachable =
+            MemoryState::from_state(HashMap::from([("x".to_string(), (0.0, 1.0).into())]));
+        let treshold = IntervalAbstraction::from(Top);
+
+        let mut m = reachable.clone();
+        m.join_state(&unreachable(), false, &treshold);
+        assert_eq!(reachable.env, m.env);
+
+        let mut m = unreachable();
+        m.join_state(&reachable, false, &treshold);
+        assert_eq!(reachable.env, m.env);
 
-        true
+        let mut m = MemoryState::new();
+        m.join_state(&unreachable(), false, &treshold);
+        assert!(!m.is_unreachable());
     }
 
-    fn set_all_vars_to_bottom(&mut self) {
-        for value in self.state.values_mut() {
-            *value = A::bottom().into();
+    #[test]
+    fn inclusion_of_unreachable_state() {
+        let empty: MemoryState<IntervalAbstraction> = MemoryState::new();
+
+        assert!(empty.includes(&unreachable()));
+        assert!(unreachable().includes(&unreachable()));
+        assert!(!unreachable().includes(&empty));
+    }
+
+    fn strict() -> Params<IntervalAbstraction> {
+        Params {
+            strict_initialization: true,
+            ..Params::no_widening()
         }
     }
 
-    fn all_vars_are_bottom(&self) -> bool {
-        self.state.len() != 0 && self.state.values().all(|&x| x == A::bottom().into())
+    #[test]
+    fn strict_reads_of_variables_initialized_on_some_paths() {
+        let program = "input(x); if (x > 0) {y := 1} else {skip}; z := y; w := y";
+        let command = parse(program);
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = m.analyze_command(&command, &strict());
+
+        assert_eq!(
+            vec![Alarm::Uninitialized(43, "y".to_string())],
+            post_analyzed.alarms()
+        );
+        assert_eq!(Some(&(1.0, 1.0).into()), post_analyzed.lookup_var("z"));
+    }
+
+    #[test]
+    fn strict_reads_of_variables_that_are_never_initialized() {
+        let command = parse("x := 1; if (y > 0) {x := 2} else {skip}");
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = m.analyze_command(&command, &strict());
+
+        assert_eq!(
+            vec![Alarm::Uninitialized(8, "y".to_string())],
+            post_analyzed.alarms()
+        );
+        assert!(post_analyzed.is_unreachable());
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = m.analyze_command(&command, &Params::no_widening());
+
+        assert!(post_analyzed.alarms().is_empty());
+        assert_eq!(Some(&(1.0, 2.0).into()), post_analyzed.lookup_var("x"));
+    }
+
+    #[test]
+    fn filter_unreachable_state() {
+        let mut m = unreachable();
+        m.filter(&BExpr::GT(Var::Var("x".to_string()), Const::Const(0.0)));
+
+        assert!(m.is_unreachable());
+        assert_eq!(None, m.lookup_var("x"));
     }
 }
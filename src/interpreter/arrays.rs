@@ -0,0 +1,396 @@
+use std::fmt::Debug;
+use std::{cmp, ops};
+
+use super::{AbstractProperties, Alarm, Bottom, MemoryState, Top};
+use crate::command_parser::{BExpr, Const, SExpr, Var};
+
+use self::segmentation::{symbolic, Expr, Segmentation};
+
+mod segmentation;
+
+/// An array abstracted by its length and one abstract value for all of its cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmashedArray<A> {
+    pub length: A,
+    pub cells: A,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayAbstraction<A> {
+    Smashed(SmashedArray<A>),
+    Segmented(Segmentation<A>),
+    /// An array of the interpreter with one value per cell, whose cells are updated strongly.
+    Cells(Vec<A>),
+}
+
+impl<A: AbstractProperties<A> + From<Bottom> + From<f64> + PartialEq + Copy + Debug>
+    ArrayAbstraction<A>
+{
+    pub(super) fn join(&self, other: &Self, use_widening: bool, widening_treshold: &A) -> Self {
+        let join = |a_self: &A, a_other: &A| match use_widening {
+            true => A::widen(a_other, a_self, widening_treshold),
+            false => A::join(a_self, a_other),
+        };
+
+        match (self, other) {
+            (Self::Smashed(array_self), Self::Smashed(array_other)) => {
+                Self::Smashed(SmashedArray {
+                    length: join(&array_self.length, &array_other.length),
+                    cells: join(&array_self.cells, &array_other.cells),
+                })
+            }
+            (Self::Segmented(array_self), Self::Segmented(array_other)) => {
+                Self::Segmented(array_self.join(array_other, use_widening, widening_treshold))
+            }
+            (Self::Cells(cells_self), Self::Cells(cells_other))
+                if cells_self.len() == cells_other.len() =>
+            {
+                Self::Cells(
+                    cells_self
+                        .iter()
+                        .zip(cells_other)
+                        .map(|(a_self, a_other)| join(a_self, a_other))
+                        .collect(),
+                )
+            }
+            _ => panic!("arrays with different abstractions"),
+        }
+    }
+
+    pub(super) fn includes(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Smashed(array_self), Self::Smashed(array_other)) => {
+                A::first_includes_second(&array_self.length, &array_other.length)
+                    && A::first_includes_second(&array_self.cells, &array_other.cells)
+            }
+            (Self::Segmented(array_self), Self::Segmented(array_other)) => {
+                array_self.includes(array_other)
+            }
+            (Self::Cells(cells_self), Self::Cells(cells_other)) => {
+                cells_self.len() == cells_other.len()
+                    && cells_self
+                        .iter()
+                        .zip(cells_other)
+                        .all(|(a_self, a_other)| A::first_includes_second(a_self, a_other))
+            }
+            _ => panic!("arrays with different abstractions"),
+        }
+    }
+
+    fn length(&self) -> A {
+        match self {
+            Self::Smashed(array) => array.length,
+            Self::Segmented(array) => array.length(),
+            Self::Cells(cells) => (cells.len() as f64).into(),
+        }
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Declares an array whose cells are initialized with 0, which is segmented if `segmented`
+    /// is set and smashed otherwise.
+    pub(super) fn declare_array(&mut self, ident: &str, length: &SExpr, segmented: bool) {
+        let end = symbolic(length);
+        let length = self.interprete_sexpr(length);
+        let array = match segmented {
+            true => ArrayAbstraction::Segmented(Segmentation::new(length, end, 0.0.into())),
+            false => ArrayAbstraction::Smashed(SmashedArray {
+                length,
+                cells: 0.0.into(),
+            }),
+        };
+        self.arrays.insert(ident.to_string(), array);
+        self.set_float(ident, false);
+    }
+
+    /// Declares an array of the interpreter with one cell per element, which is smashed if the
+    /// length is not a single integer.
+    pub(super) fn declare_cells(&mut self, ident: &str, length: &SExpr) {
+        let a = self.interprete_sexpr(length);
+        match integer_bounds(&a) {
+            Some((lo, hi)) if lo == hi && lo >= 0 => {
+                let cells = vec![0.0.into(); lo as usize];
+                self.arrays
+                    .insert(ident.to_string(), ArrayAbstraction::Cells(cells));
+                self.set_float(ident, false);
+            }
+            _ => self.declare_array(ident, length, false),
+        }
+    }
+
+    pub(super) fn read_array(&mut self, ident: &str, index: &SExpr) -> A {
+        let array = self.lookup_array(ident);
+        self.check_bounds(ident, index, &array);
+        match array {
+            ArrayAbstraction::Smashed(array) => array.cells,
+            ArrayAbstraction::Segmented(array) => array.read(symbolic(index).as_ref()),
+            ArrayAbstraction::Cells(cells) => {
+                let a = self.interprete_sexpr(index);
+                let cells = &cells[cell_range(&a, cells.len())];
+                match cells.split_first() {
+                    Some((first, rest)) => rest.iter().fold(*first, |a, cell| A::join(&a, cell)),
+                    None => A::top().into(),
+                }
+            }
+        }
+    }
+
+    /// Writes `value` to the cells that `index` may refer to. A smashed array keeps the old
+    /// values of its cells as well, as do the cells of the interpreter if the index is not a
+    /// single integer.
+    pub(super) fn write_array(&mut self, ident: &str, index: &SExpr, value: &SExpr) {
+        let mut array = self.lookup_array(ident);
+        self.check_bounds(ident, index, &array);
+        if self.is_float(value) {
+            self.set_float(ident, true);
+        }
+        let value = self.interprete_sexpr(value);
+        match &mut array {
+            ArrayAbstraction::Smashed(array) => array.cells = A::join(&array.cells, &value),
+            ArrayAbstraction::Segmented(array) => array.write(symbolic(index).as_ref(), value),
+            ArrayAbstraction::Cells(cells) => {
+                let a = self.interprete_sexpr(index);
+                let range = cell_range(&a, cells.len());
+                let strong =
+                    range.len() == 1 && integer_bounds(&a).is_some_and(|(lo, hi)| lo == hi);
+                for cell in &mut cells[range] {
+                    *cell = match strong {
+                        true => value,
+                        false => A::join(cell, &value),
+                    };
+                }
+            }
+        }
+        self.arrays.insert(ident.to_string(), array);
+    }
+
+    pub(super) fn array_length(&self, ident: &str) -> A {
+        self.lookup_array(ident).length()
+    }
+
+    /// Updates the bounds of the segmented arrays after `ident` was assigned the value of
+    /// `sexpr`, or an unknown value if there is no expression.
+    pub(super) fn assign_array_bounds(&mut self, ident: &str, sexpr: Option<&SExpr>) {
+        let e = sexpr.and_then(symbolic);
+        for array in self.arrays.values_mut() {
+            if let ArrayAbstraction::Segmented(array) = array {
+                array.assign(ident, e.as_ref());
+            }
+        }
+    }
+
+    pub(super) fn filter_array_bounds(&mut self, bexpr: &BExpr) {
+        for array in self.arrays.values_mut() {
+            if let ArrayAbstraction::Segmented(array) = array {
+                array.filter(bexpr);
+            }
+        }
+    }
+
+    /// The abstraction of the array, where segments are not empty if the values of their bounds
+    /// show it.
+    fn lookup_array(&self, ident: &str) -> ArrayAbstraction<A> {
+        let mut array = match self.arrays.get(ident) {
+            Some(array) => array.clone(),
+            None => panic!("access of undeclared array {}", ident),
+        };
+
+        if let ArrayAbstraction::Segmented(array) = &mut array {
+            let value = |(var, offset): &Expr| {
+                let a = match var {
+                    Some(var) => self.env.get(var).copied().unwrap_or(A::top().into()),
+                    None => 0.0.into(),
+                };
+                a + (*offset as f64).into()
+            };
+            let x = Var::Var("difference".to_string());
+            array.reduce(|lower, upper| {
+                !A::sat(
+                    &(value(upper) - value(lower)),
+                    &BExpr::LE(x.clone(), Const::Const(0.0)),
+                )
+            });
+        }
+        array
+    }
+
+    /// Raises an alarm unless `0 <= index < length` holds for all values of the index and the
+    /// length. The bounds of a segmented array may show this even if the values do not.
+    fn check_bounds(&mut self, ident: &str, index: &SExpr, array: &ArrayAbstraction<A>) {
+        if let (ArrayAbstraction::Segmented(array), Some(e)) = (array, symbolic(index)) {
+            if array.in_bounds(&e) {
+                return;
+            }
+        }
+
+        let a = self.interprete_sexpr(index);
+        let x = || Var::Var("index".to_string());
+
+        let below = A::sat(&a, &BExpr::LT(x(), Const::Const(0.0)));
+        let above = A::sat(&(a - array.length()), &BExpr::GE(x(), Const::Const(0.0)));
+
+        if below || above {
+            self.raise(Alarm::OutOfBounds(
+                self.location,
+                ident.to_string(),
+                index.clone(),
+            ));
+        }
+    }
+}
+
+/// The smallest and the largest integer that `a` may represent, `None` if there is none or
+/// the values are not bounded.
+pub(super) fn integer_bounds<A: AbstractProperties<A>>(a: &A) -> Option<(i64, i64)> {
+    // all integers between the bounds are exact floats
+    const MAX: i64 = 1 << 53;
+    let x = || Var::Var("bound".to_string());
+    let at_most = |k: i64| A::sat(a, &BExpr::LE(x(), Const::Const(k as f64)));
+    let at_least = |k: i64| A::sat(a, &BExpr::GE(x(), Const::Const(k as f64)));
+    if at_most(-MAX - 1) || at_least(MAX + 1) || !at_most(MAX) {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (-MAX, MAX);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match at_most(mid) {
+            true => hi = mid,
+            false => lo = mid + 1,
+        }
+    }
+    let min = lo;
+    let (mut lo, mut hi) = (min, MAX);
+    while lo < hi {
+        let mid = hi - (hi - lo) / 2;
+        match at_least(mid) {
+            true => lo = mid,
+            false => hi = mid - 1,
+        }
+    }
+    Some((min, lo))
+}
+
+/// The indices of the cells of an array of `length` cells that the index `a` may refer to, all
+/// cells if the index is not bounded.
+fn cell_range<A: AbstractProperties<A>>(a: &A, length: usize) -> std::ops::Range<usize> {
+    match integer_bounds(a) {
+        Some((lo, hi)) => {
+            let clamp = |k: i64| k.clamp(0, length as i64) as usize;
+            clamp(lo)..clamp(hi + 1)
+        }
+        None => 0..length,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayAbstraction, SmashedArray};
+    use crate::abstractions::interval_abstraction::{Interval, IntervalAbstraction};
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::{parse, SExpr, Var};
+    use crate::interpreter::{Alarm, MemoryState, Params};
+
+    #[test]
+    fn initialization_loop() {
+        let program = "array a[10]; i := 0; while (i <= 9) {a[i] := 1; i := i + 1}; x := a[9]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            ArrayAbstraction::Smashed(SmashedArray {
+                length: IntervalAbstraction::Interval(Interval::new(10.0, 10.0)),
+                // the initial value of the cells is kept by the weak updates
+                cells: IntervalAbstraction::Interval(Interval::new(0.0, 1.0)),
+            }),
+            post_analyzed.lookup_array("a")
+        );
+        assert_eq!(Vec::<Alarm>::new(), post_analyzed.alarms());
+    }
+
+    #[test]
+    fn off_by_one() {
+        let program = "array a[10]; i := 0; while (i <= 10) {a[i] := 1; i := i + 1}";
+        let command = parse(program);
+
+        let params = Params::with_widening();
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &params);
+
+        assert_eq!(
+            vec![Alarm::OutOfBounds(
+                38,
+                "a".to_string(),
+                SExpr::VExpr(Var::Var("i".to_string()))
+            )],
+            post_analyzed.alarms()
+        );
+    }
+
+    #[test]
+    fn dynamic_length() {
+        let program = "input(n); array a[n]; x := a[len(a) - 1]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+        assert_eq!(1, post_analyzed.alarms().len());
+
+        let program = "input(n); if (n > 0) {array a[n]; x := a[0]} else {x := 0}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+        assert_eq!(Vec::<Alarm>::new(), post_analyzed.alarms());
+        assert_eq!(
+            SignAbstraction::Zero,
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+    }
+
+    #[test]
+    fn negative_index() {
+        let program = "array a[3]; a[0 - 1] := 5";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(1, post.alarms().len());
+    }
+
+    #[test]
+    fn interpretation_with_strong_updates() {
+        let program = "array a[2]; a[0] := 5; a[0] := 7; x := a[1]; y := a[0]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(
+            ArrayAbstraction::Cells(vec![(7.0, 7.0).into(), (0.0, 0.0).into()]),
+            post.lookup_array("a")
+        );
+        assert_eq!(Some(&(0.0, 0.0).into()), post.lookup_var("x"));
+        assert_eq!(Some(&(7.0, 7.0).into()), post.lookup_var("y"));
+        assert_eq!(Vec::<Alarm>::new(), post.alarms());
+    }
+}
@@ -0,0 +1,561 @@
+use std::fmt::{self, Debug};
+
+use super::super::{AbstractProperties, Bottom};
+use crate::command_parser::{BExpr, Const, SExpr, Var};
+
+/// A symbolic expression `var + offset`, or the constant `offset` if there is no variable.
+pub type Expr = (Option<String>, i64);
+
+/// The symbolic form of `sexpr`, if it is a variable, an integer constant or a variable plus or
+/// minus an integer constant.
+pub fn symbolic(sexpr: &SExpr) -> Option<Expr> {
+    let integer = |number: f64| match number.fract() == 0.0 && number.abs() < 1e15 {
+        true => Some(number as i64),
+        false => None,
+    };
+
+    match sexpr {
+        SExpr::CExpr(Const::Const(number)) => Some((None, integer(*number)?)),
+        SExpr::VExpr(Var::Var(ident)) => Some((Some(ident.clone()), 0)),
+        SExpr::Add(sexpr1, sexpr2) => match (sexpr1.as_ref(), sexpr2.as_ref()) {
+            (SExpr::VExpr(Var::Var(ident)), SExpr::CExpr(Const::Const(number)))
+            | (SExpr::CExpr(Const::Const(number)), SExpr::VExpr(Var::Var(ident))) => {
+                Some((Some(ident.clone()), integer(*number)?))
+            }
+            _ => None,
+        },
+        SExpr::Sub(sexpr1, sexpr2) => match (sexpr1.as_ref(), sexpr2.as_ref()) {
+            (SExpr::VExpr(Var::Var(ident)), SExpr::CExpr(Const::Const(number))) => {
+                Some((Some(ident.clone()), -integer(*number)?))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn shift((var, offset): &Expr, by: i64) -> Expr {
+    (var.clone(), offset + by)
+}
+
+/// Whether `e` is definitely smaller than one of the expressions of `bound`, which holds if one
+/// of them has the same variable and a larger offset.
+fn below((var, offset): &Expr, bound: &[Expr]) -> bool {
+    bound.iter().any(|e| e.0 == *var && e.1 > *offset)
+}
+
+/// An array split into consecutive segments `{b0} v0 {b1}? v1 ... {bn}`. Each bound is a set of
+/// symbolic expressions that are equal, the bounds are in increasing order, the first one is 0
+/// and the last one is the length of the array. All cells of the segment between two bounds are
+/// abstracted by the same value. A segment that may be empty is marked with `?` after its upper
+/// bound.
+#[derive(Clone, PartialEq)]
+pub struct Segmentation<A> {
+    length: A,
+    bounds: Vec<Vec<Expr>>,
+    values: Vec<A>,
+    maybe_empty: Vec<bool>,
+}
+
+impl<A: AbstractProperties<A> + From<Bottom> + From<f64> + PartialEq + Copy + Debug>
+    Segmentation<A>
+{
+    /// An array with one segment from 0 to `length` whose cells have the value `init`.
+    pub fn new(length: A, end: Option<Expr>, init: A) -> Self {
+        let zero = (None, 0);
+        Segmentation {
+            length,
+            bounds: vec![vec![zero], end.into_iter().collect()],
+            values: vec![init],
+            maybe_empty: vec![A::sat(&length, &le(0))],
+        }
+    }
+
+    pub fn length(&self) -> A {
+        self.length
+    }
+
+    fn last(&self) -> usize {
+        self.bounds.len() - 1
+    }
+
+    fn find(&self, e: &Expr) -> Option<usize> {
+        self.bounds.iter().position(|bound| bound.contains(e))
+    }
+
+    /// The segments from `j` up to the first one that is not empty, one of which contains the
+    /// cell at bound `j`.
+    fn segments_from(&self, j: usize) -> std::ops::Range<usize> {
+        let end = (j..self.values.len())
+            .find(|&k| !self.maybe_empty[k])
+            .map_or(self.values.len(), |k| k + 1);
+        j..end
+    }
+
+    /// Whether an index equal to `e` is definitely in the bounds of the array, i.e. it is one of
+    /// the bounds and there is a segment after it that is not empty.
+    pub fn in_bounds(&self, e: &Expr) -> bool {
+        match self.find(e) {
+            Some(j) if j < self.last() => self.maybe_empty[j..].iter().any(|empty| !empty),
+            _ => false,
+        }
+    }
+
+    pub fn read(&self, index: Option<&Expr>) -> A {
+        let segments = match index.and_then(|e| self.find(e)) {
+            Some(j) if j < self.last() => self.segments_from(j),
+            _ => 0..self.values.len(),
+        };
+        self.values[segments]
+            .iter()
+            .fold(A::bottom().into(), |acc, a| A::join(&acc, a))
+    }
+
+    /// Writes the value to the cell at `index`. The segment of the cell is split if the index is
+    /// a bound, otherwise all cells that the index may refer to keep their old values as well.
+    pub fn write(&mut self, index: Option<&Expr>, value: A) {
+        let j = match index.and_then(|e| self.find(e)) {
+            Some(j) if j < self.last() => j,
+            _ => {
+                for a in self.values.iter_mut() {
+                    *a = A::join(a, &value);
+                }
+                return;
+            }
+        };
+        let next = shift(index.unwrap(), 1);
+
+        if self.bounds[j + 1].contains(&next) {
+            self.values[j] = value;
+            self.maybe_empty[j] = false;
+        } else if !self.maybe_empty[j] {
+            self.maybe_empty[j] = !below(&next, &self.bounds[j + 1]);
+            self.bounds.insert(j + 1, vec![next]);
+            self.values.insert(j, value);
+            self.maybe_empty.insert(j, false);
+        } else {
+            for k in self.segments_from(j) {
+                self.values[k] = A::join(&self.values[k], &value);
+            }
+        }
+    }
+
+    /// Updates the bounds after the assignment `var := e`, where `e` is the symbolic form of the
+    /// assigned expression if it has one.
+    pub fn assign(&mut self, var: &str, e: Option<&Expr>) {
+        match e {
+            Some((Some(ident), offset)) if ident == var => {
+                for bound in self.bounds.iter_mut() {
+                    for expr in bound.iter_mut() {
+                        if expr.0.as_deref() == Some(var) {
+                            expr.1 -= offset;
+                        }
+                    }
+                }
+            }
+            _ => {
+                for bound in self.bounds.iter_mut() {
+                    bound.retain(|expr| expr.0.as_deref() != Some(var));
+                }
+                if let Some((ident, offset)) = e {
+                    for bound in self.bounds.iter_mut() {
+                        let equal: Vec<Expr> = bound
+                            .iter()
+                            .filter(|expr| expr.0 == *ident)
+                            .map(|expr| (Some(var.to_string()), expr.1 - offset))
+                            .collect();
+                        bound.extend(equal);
+                    }
+                }
+                self.remove_empty_bounds();
+            }
+        }
+        self.normalize();
+    }
+
+    /// Marks the segments as not empty for which `non_empty` holds for an expression of the lower
+    /// and one of the upper bound.
+    pub fn reduce(&mut self, non_empty: impl Fn(&Expr, &Expr) -> bool) {
+        for j in 0..self.values.len() {
+            if self.maybe_empty[j]
+                && self.bounds[j].iter().any(|lower| {
+                    self.bounds[j + 1]
+                        .iter()
+                        .any(|upper| non_empty(lower, upper))
+                })
+            {
+                self.maybe_empty[j] = false;
+            }
+        }
+    }
+
+    /// Uses a guard `var op c` to find segments that are not empty or that must be empty.
+    pub fn filter(&mut self, bexpr: &BExpr) {
+        let var = Some(bexpr.get_ident().clone());
+        match *bexpr {
+            BExpr::LT(_, Const::Const(c)) => self.less_than(&var, c),
+            BExpr::LE(_, Const::Const(c)) => self.less_than(&var, c + 1.0),
+            BExpr::GE(_, Const::Const(c)) => self.at_least(&var, c),
+            BExpr::GT(_, Const::Const(c)) => self.at_least(&var, c + 1.0),
+            BExpr::EQ(_, Const::Const(c)) if c.fract() == 0.0 => {
+                for bound in self.bounds.iter_mut() {
+                    let equal: Vec<Expr> = bound
+                        .iter()
+                        .filter(|expr| expr.0.is_none())
+                        .map(|expr| (var.clone(), expr.1 - c as i64))
+                        .collect();
+                    bound.extend(equal);
+                }
+                self.normalize();
+            }
+            _ => (),
+        }
+    }
+
+    /// The segments `j` with `var + k` in the lower and `c + k` in the upper bound.
+    fn segments_between(&self, var: &Option<String>, c: f64) -> Vec<usize> {
+        if c.fract() != 0.0 {
+            return Vec::new();
+        }
+        (0..self.values.len())
+            .filter(|&j| {
+                self.bounds[j].iter().any(|expr| {
+                    expr.0 == *var && self.bounds[j + 1].contains(&(None, c as i64 + expr.1))
+                })
+            })
+            .collect()
+    }
+
+    fn less_than(&mut self, var: &Option<String>, c: f64) {
+        for j in self.segments_between(var, c) {
+            self.maybe_empty[j] = false;
+        }
+    }
+
+    fn at_least(&mut self, var: &Option<String>, c: f64) {
+        for j in self.segments_between(var, c).into_iter().rev() {
+            if self.maybe_empty[j] {
+                let upper = self.bounds.remove(j + 1);
+                self.bounds[j].extend(upper);
+                self.values.remove(j);
+                self.maybe_empty.remove(j);
+            }
+        }
+        self.normalize();
+    }
+
+    /// Removes the bound `j`, which is neither the first nor the last one, by joining the
+    /// segments below and above it.
+    fn remove_bound(&mut self, j: usize) {
+        self.values[j - 1] = A::join(&self.values[j - 1], &self.values[j]);
+        self.maybe_empty[j - 1] = self.maybe_empty[j - 1] && self.maybe_empty[j];
+        self.bounds.remove(j);
+        self.values.remove(j);
+        self.maybe_empty.remove(j);
+    }
+
+    fn remove_empty_bounds(&mut self) {
+        for j in (1..self.last()).rev() {
+            if self.bounds[j].is_empty() {
+                self.remove_bound(j);
+            }
+        }
+    }
+
+    fn normalize(&mut self) {
+        for bound in self.bounds.iter_mut() {
+            bound.sort();
+            bound.dedup();
+        }
+    }
+
+    /// Inserts an empty segment below the bound `j` whose lower bound are the expressions
+    /// `exprs`, which are removed from the bound `j`.
+    fn split_bound(&mut self, j: usize, exprs: Vec<Expr>) {
+        self.bounds[j].retain(|expr| !exprs.contains(expr));
+        self.bounds.insert(j + 1, exprs);
+        self.values.insert(j, A::bottom().into());
+        self.maybe_empty.insert(j, true);
+    }
+
+    /// Whether an expression of the bound `j` of `self` occurs in a bound after `j` of `other`.
+    fn occurs_later(&self, j: usize, other: &Self) -> Vec<Expr> {
+        self.bounds[j]
+            .iter()
+            .filter(|expr| {
+                other.bounds[j + 1..]
+                    .iter()
+                    .any(|bound| bound.contains(expr))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Modifies both segmentations until they have the same bounds, by removing bounds and by
+    /// inserting empty segments, and joins the values of their segments with `join`.
+    fn unify(&self, other: &Self, join: impl Fn(&A, &A) -> A) -> Self {
+        let mut s0 = self.clone();
+        let mut s1 = other.clone();
+
+        let mut j = 0;
+        loop {
+            let last0 = j == s0.last();
+            let last1 = j == s1.last();
+
+            if last0 != last1 {
+                match last0 {
+                    true => s1.remove_bound(j),
+                    false => s0.remove_bound(j),
+                }
+                continue;
+            }
+
+            let common: Vec<Expr> = s0.bounds[j]
+                .iter()
+                .filter(|expr| s1.bounds[j].contains(expr))
+                .cloned()
+                .collect();
+
+            if !last0 {
+                let later0 = s0.occurs_later(j, &s1);
+                let later1 = s1.occurs_later(j, &s0);
+
+                if common.is_empty() && j > 0 {
+                    match (later0.is_empty(), later1.is_empty()) {
+                        (false, true) => s1.remove_bound(j),
+                        _ => s0.remove_bound(j),
+                    }
+                    continue;
+                }
+
+                let later0: Vec<Expr> = later0
+                    .into_iter()
+                    .filter(|expr| !common.contains(expr))
+                    .collect();
+                let later1: Vec<Expr> = later1
+                    .into_iter()
+                    .filter(|expr| !common.contains(expr))
+                    .collect();
+                if !later0.is_empty() {
+                    s0.split_bound(j, later0);
+                }
+                if !later1.is_empty() {
+                    s1.split_bound(j, later1);
+                }
+            }
+
+            s0.bounds[j] = common.clone();
+            s1.bounds[j] = common;
+
+            if last0 {
+                break;
+            }
+            j += 1;
+        }
+
+        Segmentation {
+            length: join(&s0.length, &s1.length),
+            values: s0
+                .values
+                .iter()
+                .zip(&s1.values)
+                .map(|(a0, a1)| join(a0, a1))
+                .collect(),
+            maybe_empty: s0
+                .maybe_empty
+                .iter()
+                .zip(&s1.maybe_empty)
+                .map(|(empty0, empty1)| *empty0 || *empty1)
+                .collect(),
+            bounds: s0.bounds,
+        }
+    }
+
+    pub fn join(&self, other: &Self, use_widening: bool, widening_treshold: &A) -> Self {
+        match use_widening {
+            true => self.unify(other, |a_self, a_other| {
+                A::widen(a_other, a_self, widening_treshold)
+            }),
+            false => self.unify(other, |a_self, a_other| A::join(a_self, a_other)),
+        }
+    }
+
+    pub fn includes(&self, other: &Self) -> bool {
+        self.unify(other, |a_self, a_other| A::join(a_self, a_other)) == *self
+    }
+}
+
+fn le(number: i64) -> BExpr {
+    BExpr::LE(Var::Var("length".to_string()), Const::Const(number as f64))
+}
+
+impl<A: Debug> Debug for Segmentation<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound = |bound: &Vec<Expr>| {
+            let exprs: Vec<String> = bound
+                .iter()
+                .map(|(var, offset)| match (var, offset) {
+                    (None, offset) => offset.to_string(),
+                    (Some(var), 0) => var.clone(),
+                    (Some(var), offset) if *offset < 0 => format!("{}{}", var, offset),
+                    (Some(var), offset) => format!("{}+{}", var, offset),
+                })
+                .collect();
+            format!("{{{}}}", exprs.join(", "))
+        };
+
+        write!(
+            f,
+            "Segmentation({:?}: {}",
+            self.length,
+            bound(&self.bounds[0])
+        )?;
+        for j in 0..self.values.len() {
+            write!(f, " {:?} {}", self.values[j], bound(&self.bounds[j + 1]))?;
+            if self.maybe_empty[j] {
+                write!(f, "?")?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Segmentation;
+    use crate::abstractions::interval_abstraction::{Interval, IntervalAbstraction};
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::parse;
+    use crate::interpreter::arrays::ArrayAbstraction;
+    use crate::interpreter::{Alarm, MemoryState, Params};
+
+    fn segmentation() -> Params<IntervalAbstraction> {
+        Params {
+            array_segmentation: true,
+            ..Params::no_widening()
+        }
+    }
+
+    #[test]
+    fn initialization_loop() {
+        let program = "array a[10]; i := 0; while (i < 10) {a[i] := 1; i := i + 1}; x := a[9]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &segmentation());
+
+        assert_eq!(
+            ArrayAbstraction::Segmented(Segmentation {
+                length: IntervalAbstraction::Interval(Interval::new(10.0, 10.0)),
+                bounds: vec![
+                    vec![(None, 0)],
+                    vec![(None, 10), (Some("i".to_string()), 0)]
+                ],
+                values: vec![IntervalAbstraction::Interval(Interval::new(1.0, 1.0))],
+                maybe_empty: vec![false],
+            }),
+            post_analyzed.lookup_array("a")
+        );
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(1.0, 1.0)),
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+        assert_eq!(Vec::<Alarm>::new(), post_analyzed.alarms());
+    }
+
+    #[test]
+    fn initialization_loop_with_widening() {
+        let program = "array a[10]; i := 0; while (i < 10) {a[i] := 1; i := i + 1}; x := a[5]";
+        let command = parse(program);
+
+        let params = Params {
+            use_widening: true,
+            ..segmentation()
+        };
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &params);
+
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(1.0, 1.0)),
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+        assert_eq!(Vec::<Alarm>::new(), post_analyzed.alarms());
+    }
+
+    #[test]
+    fn partial_initialization() {
+        let program =
+            "array a[10]; i := 0; while (i < 5) {a[i] := 1; i := i + 1}; x := a[0]; y := a[i]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &segmentation());
+
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(1.0, 1.0)),
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(0.0, 0.0)),
+            *post_analyzed.lookup_var("y").unwrap()
+        );
+    }
+
+    #[test]
+    fn sign_of_cells() {
+        let program = "array a[10]; i := 0; while (i < 10) {a[i] := 0 - 1; i := i + 1}; x := a[3]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(
+            &command,
+            &Params {
+                array_segmentation: true,
+                ..Params::no_widening()
+            },
+        );
+
+        assert_eq!(
+            SignAbstraction::Neg,
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+    }
+
+    #[test]
+    fn split_below_the_length() {
+        let program = "array a[2]; a[0] := 5";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &segmentation());
+
+        assert_eq!(
+            Some(&ArrayAbstraction::Segmented(Segmentation {
+                length: IntervalAbstraction::Interval(Interval::new(2.0, 2.0)),
+                bounds: vec![vec![(None, 0)], vec![(None, 1)], vec![(None, 2)]],
+                values: vec![
+                    IntervalAbstraction::Interval(Interval::new(5.0, 5.0)),
+                    IntervalAbstraction::Interval(Interval::new(0.0, 0.0)),
+                ],
+                maybe_empty: vec![false, false],
+            })),
+            post_analyzed.arrays.get("a")
+        );
+    }
+
+    #[test]
+    fn unknown_index() {
+        let program = "array a[10]; input(i); if (i >= 0) {if (i <= 9) {a[i] := 1} else {skip}} else {skip}; x := a[0]";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &segmentation());
+
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(0.0, 1.0)),
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+        assert_eq!(Vec::<Alarm>::new(), post_analyzed.alarms());
+    }
+}
@@ -0,0 +1,650 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::{cmp, ops};
+
+use super::{AbstractProperties, Bottom, MemoryState, Params, Top, Trace, MAX_JOINS};
+use crate::command_parser::{BExpr, Command, Const, SExpr, Type, Var};
+
+/// The states that the backward analysis starts from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// The final states of the program that satisfy all guards.
+    Post(Vec<BExpr>),
+    /// The states that violate the assertion at the offset, or any assertion.
+    Failure(Option<usize>),
+}
+
+/// A necessary precondition of a target: every execution that reaches the target starts in a
+/// state that satisfies it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precondition<A> {
+    /// Whether any state reaches the target. If not, the other fields are meaningless.
+    pub reachable: bool,
+    /// The values of the variables that are read before they are assigned, unless they may have
+    /// any value.
+    pub initial: Vec<(String, A)>,
+    /// The values that `input` has to read into each variable, joined over all of its inputs.
+    pub inputs: Vec<(String, A)>,
+}
+
+/// The context of the backward analysis of a command.
+struct Backward<
+    'a,
+    A: ops::Add<Output = A>
+        + ops::Neg<Output = A>
+        + ops::Sub<Output = A>
+        + ops::Mul<Output = A>
+        + ops::Div<Output = A>
+        + cmp::PartialEq
+        + cmp::PartialOrd
+        + From<f64>
+        + Copy
+        + AbstractProperties<A>
+        + From<Top>
+        + From<Bottom>,
+> {
+    target: &'a Target,
+    params: &'a Params<A>,
+    /// The state in which every variable may have any value of its type.
+    top: MemoryState<A>,
+    /// For every enclosing loop, the state after the loop, where `break` leads to, and the state
+    /// before its step, where `continue` leads to.
+    loops: Vec<(MemoryState<A>, MemoryState<A>)>,
+    inputs: HashMap<String, A>,
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Infers a necessary precondition of `target` by analyzing `c` backwards: starting from the
+    /// target, every command is replaced by the states before it that may lead to the states
+    /// after it. Loops are iterated until their heads are stable, with widening if `params` say
+    /// so. Arrays and procedure calls are not tracked, so their results may have any value.
+    pub fn precondition(c: &Command, target: &Target, params: &Params<A>) -> Precondition<A> {
+        let (m, inputs) = Self::analyze_backward(c, target, params, None);
+
+        let constrained = |values: HashMap<String, A>| {
+            let mut values: Vec<_> = values
+                .into_iter()
+                .filter(|(ident, a)| *a != m.top(ident))
+                .collect();
+            values.sort_by(|(ident0, _), (ident1, _)| ident0.cmp(ident1));
+            values
+        };
+        Precondition {
+            reachable: !m.is_unreachable(),
+            initial: constrained(m.env.iter().map(|(ident, a)| (ident.clone(), *a)).collect()),
+            inputs: constrained(inputs),
+        }
+    }
+
+    /// Analyzes `c` backwards from `target` and returns the state at the start of `c` and the
+    /// values of the inputs. The states before the located statements are restricted to and
+    /// collected in `trace`, if there is one.
+    pub(super) fn analyze_backward(
+        c: &Command,
+        target: &Target,
+        params: &Params<A>,
+        trace: Option<Rc<RefCell<Trace<A>>>>,
+    ) -> (MemoryState<A>, HashMap<String, A>) {
+        let mut top = MemoryState::new();
+        top.trace = trace;
+        let mut vars = HashSet::new();
+        top.collect_variables(c, &mut vars);
+        while top.collect_floats(c) {}
+        for ident in vars {
+            top.insert(&ident, top.top(&ident));
+        }
+
+        let mut m = top.clone();
+        match target {
+            Target::Post(guards) => {
+                for bexpr in guards {
+                    m.filter(bexpr);
+                }
+            }
+            Target::Failure(_) => m.set_unreachable(),
+        }
+
+        let mut backward = Backward {
+            target,
+            params,
+            top: top.clone(),
+            loops: Vec::new(),
+            inputs: HashMap::new(),
+        };
+        m.backward_statement(c, &mut backward);
+        (m, backward.inputs)
+    }
+
+    /// Collects the variables of `c` outside of procedure bodies and declares their types.
+    fn collect_variables(&mut self, c: &Command, vars: &mut HashSet<String>) {
+        let sexpr_vars = |sexpr: &SExpr, vars: &mut HashSet<String>| {
+            vars.extend(variables(sexpr).into_iter().cloned())
+        };
+        match c {
+            Command::Seq(c1, c2) | Command::Choose(c1, c2) => {
+                self.collect_variables(c1, vars);
+                self.collect_variables(c2, vars);
+            }
+            Command::Assign(Var::Var(ident), sexpr) => {
+                vars.insert(ident.clone());
+                sexpr_vars(sexpr, vars);
+            }
+            Command::Input(Var::Var(ident)) | Command::InputRange(Var::Var(ident), _, _) => {
+                vars.insert(ident.clone());
+            }
+            Command::Declare(Var::Var(ident), typ) => {
+                vars.insert(ident.clone());
+                self.declare(ident, *typ);
+            }
+            Command::Array(_, length) => sexpr_vars(length, vars),
+            Command::ArrayAssign(_, index, value) => {
+                sexpr_vars(index, vars);
+                sexpr_vars(value, vars);
+            }
+            Command::If(bexpr, c1, c2) => {
+                vars.insert(bexpr.get_ident().clone());
+                self.collect_variables(c1, vars);
+                self.collect_variables(c2, vars);
+            }
+            Command::While(bexpr, c) | Command::DoWhile(c, bexpr) => {
+                vars.insert(bexpr.get_ident().clone());
+                self.collect_variables(c, vars);
+            }
+            Command::For(init, bexpr, step, c) => {
+                vars.insert(bexpr.get_ident().clone());
+                self.collect_variables(init, vars);
+                self.collect_variables(step, vars);
+                self.collect_variables(c, vars);
+            }
+            Command::Assert(bexpr) => {
+                vars.insert(bexpr.get_ident().clone());
+            }
+            Command::Call(Var::Var(ident), _, args, _) => {
+                vars.insert(ident.clone());
+                for arg in args {
+                    sexpr_vars(arg, vars);
+                }
+            }
+            Command::Partition(_, c) | Command::Located(_, c) => self.collect_variables(c, vars),
+            Command::Skip | Command::Break | Command::Continue | Command::Proc(_) => (),
+        }
+    }
+
+    /// Marks the variables and arrays that may hold floats anywhere in `c`, and returns whether
+    /// a new one was found. Results of procedures may be floats.
+    fn collect_floats(&mut self, c: &Command) -> bool {
+        match c {
+            Command::Seq(c1, c2) | Command::Choose(c1, c2) => {
+                // both sides have to be visited
+                let changed = self.collect_floats(c1);
+                self.collect_floats(c2) || changed
+            }
+            Command::Assign(Var::Var(ident), sexpr) => {
+                let float = self.is_float(sexpr);
+                self.mark_float(ident, float)
+            }
+            Command::ArrayAssign(Var::Var(ident), _, value) => {
+                let float = self.is_float(value);
+                self.mark_float(ident, float)
+            }
+            Command::Call(Var::Var(ident), _, _, _) => self.mark_float(ident, true),
+            Command::If(_, c1, c2) => {
+                let changed = self.collect_floats(c1);
+                self.collect_floats(c2) || changed
+            }
+            Command::For(init, _, step, c) => {
+                let changed = self.collect_floats(init);
+                let changed = self.collect_floats(step) || changed;
+                self.collect_floats(c) || changed
+            }
+            Command::While(_, c)
+            | Command::DoWhile(c, _)
+            | Command::Partition(_, c)
+            | Command::Located(_, c) => self.collect_floats(c),
+            _ => false,
+        }
+    }
+
+    /// Marks `ident` as a float variable if `float` is set and its type allows it, and returns
+    /// whether it was not marked before.
+    fn mark_float(&mut self, ident: &str, float: bool) -> bool {
+        let nr_of_floats = self.floats.len();
+        if float {
+            self.set_float(ident, true);
+        }
+        self.floats.len() > nr_of_floats
+    }
+
+    /// Replaces the state after `c` by the states before `c` that may lead to it.
+    fn backward_statement(&mut self, c: &Command, backward: &mut Backward<A>) {
+        match c {
+            Command::Skip
+            | Command::Declare(_, _)
+            | Command::Array(_, _)
+            | Command::ArrayAssign(_, _, _)
+            | Command::Proc(_) => (),
+            Command::Seq(c1, c2) => {
+                self.backward_statement(c2, backward);
+                self.backward_statement(c1, backward);
+            }
+            Command::Assign(Var::Var(ident), sexpr) => self.backward_assign(ident, Some(sexpr)),
+            Command::Call(Var::Var(ident), _, _, _) => self.backward_assign(ident, None),
+            Command::Input(Var::Var(ident)) => {
+                let a = A::meet(
+                    &self.get_from_state_or_insert_default(ident),
+                    &self.top(ident),
+                );
+                self.backward_input(ident, a, backward);
+            }
+            Command::InputRange(var, lo, hi) => {
+                let Var::Var(ident) = var;
+                let a = self.get_from_state_or_insert_default(ident);
+                let a = A::refine(&a, &BExpr::GE(var.clone(), lo.clone()));
+                let a = A::refine(&a, &BExpr::LE(var.clone(), hi.clone()));
+                self.backward_input(ident, a, backward);
+            }
+            Command::If(bexpr, c1, c2) => {
+                let mut m1 = self.clone();
+                m1.backward_statement(c1, backward);
+                m1.filter(bexpr);
+
+                self.backward_statement(c2, backward);
+                self.filter(&bexpr.negate());
+                self.join_state(&m1, false, &backward.params.widening_treshold);
+            }
+            Command::Choose(c1, c2) => {
+                let mut m1 = self.clone();
+                m1.backward_statement(c1, backward);
+                self.backward_statement(c2, backward);
+                self.join_state(&m1, false, &backward.params.widening_treshold);
+            }
+            Command::While(bexpr, c) => self.backward_loop(bexpr, c, None, false, backward),
+            Command::For(init, bexpr, step, c) => {
+                self.backward_loop(bexpr, c, Some(step), false, backward);
+                self.backward_statement(init, backward);
+            }
+            Command::DoWhile(c, bexpr) => self.backward_loop(bexpr, c, None, true, backward),
+            Command::Break | Command::Continue => {
+                let (after_loop, before_step) = match backward.loops.last() {
+                    Some(targets) => targets,
+                    None => panic!("break or continue outside of a loop"),
+                };
+                *self = match c {
+                    Command::Break => after_loop.clone(),
+                    _ => before_step.clone(),
+                };
+            }
+            Command::Assert(bexpr) => {
+                let failing = match backward.target {
+                    Target::Failure(offset) => offset.is_none_or(|offset| offset == self.location),
+                    Target::Post(_) => false,
+                };
+                self.filter(bexpr);
+                if failing {
+                    let mut m = backward.top.clone();
+                    m.filter(&bexpr.negate());
+                    self.join_state(&m, false, &backward.params.widening_treshold);
+                }
+            }
+            Command::Partition(_, c) => self.backward_statement(c, backward),
+            Command::Located(offset, c) => {
+                self.location = *offset;
+                self.backward_statement(c, backward);
+                self.visit(*offset, true);
+            }
+        }
+    }
+
+    /// Replaces the state after `ident := sexpr` by the states before it: `ident` may have had
+    /// any value, and `sexpr` has to evaluate to a value of `ident` after the assignment. Without
+    /// an expression, or if the value wraps around, any value may lead to the state after it.
+    fn backward_assign(&mut self, ident: &str, sexpr: Option<&SExpr>) {
+        let a = self.get_from_state_or_insert_default(ident);
+        if a == A::bottom().into() {
+            self.set_unreachable();
+            return;
+        }
+
+        self.insert(ident, self.top(ident));
+        let wrapping = matches!(self.types.get(ident), Some(Type::Machine(typ)) if typ.wrapping);
+        if let (Some(sexpr), false) = (sexpr, wrapping) {
+            if !self.refine_sexpr(sexpr, a) {
+                self.set_unreachable();
+            }
+        }
+    }
+
+    /// Records that an input has to read a value of `a` into `ident`, which may have had any
+    /// value before.
+    fn backward_input(&mut self, ident: &str, a: A, backward: &mut Backward<A>) {
+        if a == A::bottom().into() {
+            self.set_unreachable();
+            return;
+        }
+
+        backward
+            .inputs
+            .entry(ident.to_string())
+            .and_modify(|input| *input = A::join(input, &a))
+            .or_insert(a);
+        self.insert(ident, self.top(ident));
+    }
+
+    /// Replaces the state after `while (bexpr) {body; step}` by the states before it, see
+    /// `analyze_loop`. The state at the loop head is the fixpoint of the states that leave the
+    /// loop and the states before another iteration, which is widened after `MAX_JOINS` joins
+    /// even if widening is disabled. `break` leads to the state after the loop and `continue` to
+    /// the state before the step.
+    fn backward_loop(
+        &mut self,
+        bexpr: &BExpr,
+        body: &Command,
+        step: Option<&Command>,
+        body_first: bool,
+        backward: &mut Backward<A>,
+    ) {
+        let params = backward.params;
+        let after_loop = self.clone();
+        let mut exit = self.clone();
+        exit.filter(&bexpr.negate());
+
+        let mut nr_of_joins: usize = 0;
+        let mut head = exit.clone();
+        loop {
+            let prev_head = head.clone();
+
+            head.backward_iteration(body, step, &after_loop, backward);
+            head.filter(bexpr);
+            head.join_state(&exit, false, &params.widening_treshold);
+
+            if nr_of_joins >= MAX_JOINS
+                || params.use_widening && nr_of_joins >= params.widening_delays as usize
+            {
+                head.join_state(&prev_head, true, &params.widening_treshold);
+            } else {
+                head.join_state(&prev_head, false, &params.widening_treshold);
+                nr_of_joins += 1;
+            }
+
+            if prev_head.includes(&head) {
+                break;
+            }
+        }
+
+        if body_first {
+            head.backward_iteration(body, step, &after_loop, backward);
+        }
+        *self = head;
+    }
+
+    /// Replaces the state after `body; step` by the states before the body.
+    fn backward_iteration(
+        &mut self,
+        body: &Command,
+        step: Option<&Command>,
+        after_loop: &MemoryState<A>,
+        backward: &mut Backward<A>,
+    ) {
+        if let Some(step) = step {
+            self.backward_statement(step, backward);
+        }
+
+        backward.loops.push((after_loop.clone(), self.clone()));
+        self.backward_statement(body, backward);
+        backward.loops.pop();
+    }
+
+    /// Refines the variables in `sexpr` to the values for which it may evaluate to a value of `a`,
+    /// and returns `false` if there are none. The result of an operation determines each operand
+    /// from the other one, e.g. `x` is in `a - y` if `x + y` is in `a`. Operations without
+    /// inverse leave their operands unchanged.
+    fn refine_sexpr(&mut self, sexpr: &SExpr, a: A) -> bool {
+        let a = A::meet(&self.value(sexpr), &a);
+        if a == A::bottom().into() {
+            return false;
+        }
+
+        match sexpr {
+            SExpr::VExpr(Var::Var(ident)) => {
+                self.insert(ident, a);
+                true
+            }
+            SExpr::Neg(sexpr) => self.refine_sexpr(sexpr, -a),
+            SExpr::Add(sexpr1, sexpr2) => {
+                let a = A::unround(&a);
+                self.refine_sexpr(sexpr1, a - self.value(sexpr2))
+                    && self.refine_sexpr(sexpr2, a - self.value(sexpr1))
+            }
+            SExpr::Sub(sexpr1, sexpr2) => {
+                let a = A::unround(&a);
+                self.refine_sexpr(sexpr1, a + self.value(sexpr2))
+                    && self.refine_sexpr(sexpr2, self.value(sexpr1) - a)
+            }
+            SExpr::Mul(sexpr1, sexpr2) => {
+                let a = A::unround(&a);
+                self.refine_factor(sexpr1, a, sexpr2) && self.refine_factor(sexpr2, a, sexpr1)
+            }
+            SExpr::Div(sexpr1, sexpr2) => {
+                // a division by zero may have any result
+                let a = A::unround(&a);
+                if contains_zero(&self.value(sexpr2)) {
+                    return true;
+                }
+                self.refine_sexpr(sexpr1, a * self.value(sexpr2))
+                    && self.refine_divisor(sexpr2, a, sexpr1)
+            }
+            SExpr::Abs(sexpr) => self.refine_sexpr(sexpr, A::join(&a, &-a)),
+            _ => true,
+        }
+    }
+
+    /// Refines the factor `sexpr` of a product with a value of `a`, whose other factor is
+    /// `other`. Any factor leads to zero if the other one may be zero.
+    fn refine_factor(&mut self, sexpr: &SExpr, a: A, other: &SExpr) -> bool {
+        let other = self.value(other);
+        if contains_zero(&a) && contains_zero(&other) {
+            return true;
+        }
+        self.refine_sexpr(sexpr, a / other)
+    }
+
+    /// Refines the divisor `sexpr` of a quotient with a value of `a`, whose dividend is
+    /// `dividend`.
+    fn refine_divisor(&mut self, sexpr: &SExpr, a: A, dividend: &SExpr) -> bool {
+        let dividend = self.value(dividend);
+        if contains_zero(&a) && contains_zero(&dividend) {
+            return true;
+        }
+        self.refine_sexpr(sexpr, dividend / a)
+    }
+
+    /// The value of `sexpr` without raising alarms, where array cells and lengths may have any
+    /// value.
+    fn value(&self, sexpr: &SExpr) -> A {
+        let value = |sexpr: &SExpr| self.value(sexpr);
+        match sexpr {
+            SExpr::CExpr(Const::Const(number)) => (*number).into(),
+            SExpr::VExpr(Var::Var(ident)) => match self.env.get(ident) {
+                Some(a) => *a,
+                None => self.top(ident),
+            },
+            SExpr::Neg(sexpr) => -value(sexpr),
+            SExpr::Add(sexpr1, sexpr2) => value(sexpr1) + value(sexpr2),
+            SExpr::Sub(sexpr1, sexpr2) => value(sexpr1) - value(sexpr2),
+            SExpr::Mul(sexpr1, sexpr2) => value(sexpr1) * value(sexpr2),
+            SExpr::Div(sexpr1, sexpr2) => value(sexpr1) / value(sexpr2),
+            SExpr::Rem(sexpr1, sexpr2) => A::rem(&value(sexpr1), &value(sexpr2)),
+            SExpr::IntDiv(sexpr1, sexpr2) => A::int_div(&value(sexpr1), &value(sexpr2)),
+            SExpr::Min(sexpr1, sexpr2) => A::min(&value(sexpr1), &value(sexpr2)),
+            SExpr::Max(sexpr1, sexpr2) => A::max(&value(sexpr1), &value(sexpr2)),
+            SExpr::Abs(sexpr) => A::abs(&value(sexpr)),
+            SExpr::Index(_, _) | SExpr::Len(_) => A::top().into(),
+            SExpr::Bool(true) => 1.0.into(),
+            SExpr::Bool(false) => 0.0.into(),
+        }
+    }
+}
+
+fn contains_zero<A: AbstractProperties<A> + From<f64>>(a: &A) -> bool {
+    A::first_includes_second(a, &0.0.into())
+}
+
+/// The scalar variables that `sexpr` reads, without the arrays.
+fn variables(sexpr: &SExpr) -> Vec<&String> {
+    match sexpr {
+        SExpr::VExpr(Var::Var(ident)) => vec![ident],
+        SExpr::Neg(sexpr) | SExpr::Abs(sexpr) | SExpr::Index(_, sexpr) => variables(sexpr),
+        SExpr::Add(sexpr1, sexpr2)
+        | SExpr::Sub(sexpr1, sexpr2)
+        | SExpr::Mul(sexpr1, sexpr2)
+        | SExpr::Div(sexpr1, sexpr2)
+        | SExpr::Rem(sexpr1, sexpr2)
+        | SExpr::IntDiv(sexpr1, sexpr2)
+        | SExpr::Min(sexpr1, sexpr2)
+        | SExpr::Max(sexpr1, sexpr2) => {
+            let mut vars = variables(sexpr1);
+            vars.extend(variables(sexpr2));
+            vars
+        }
+        SExpr::CExpr(_) | SExpr::Len(_) | SExpr::Bool(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::abstractions::parity_abstraction::ParityAbstraction;
+    use crate::abstractions::product_abstraction::{IntervalParityAbstraction, ProductAbstraction};
+    use crate::command_parser::{parse, parse_guard};
+
+    #[test]
+    fn postcondition_through_branches() {
+        let program = "input(x); y := x + 1; if (y > 10) {z := 1} else {z := 0}";
+        let command = parse(program);
+
+        let target = Target::Post(vec![parse_guard("z == 1")]);
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &target, &Params::no_widening());
+
+        assert_eq!(
+            Precondition {
+                reachable: true,
+                initial: vec![],
+                inputs: vec![("x".to_string(), (10.0, f64::MAX).into())],
+            },
+            precondition
+        );
+    }
+
+    #[test]
+    fn precondition_on_variables_read_before_assignment() {
+        let program = "y := x - 3; z := -y";
+        let command = parse(program);
+
+        let target = Target::Post(vec![parse_guard("z >= 4")]);
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &target, &Params::no_widening());
+
+        assert_eq!(
+            vec![("x".to_string(), (f64::MIN, -1.0).into())],
+            precondition.initial
+        );
+    }
+
+    #[test]
+    fn failing_assertion_after_loop() {
+        let program = "input(x); while (x < 10) {x := x + 2}; assert(x != 11)";
+        let command = parse(program);
+
+        let precondition: Precondition<IntervalParityAbstraction> =
+            MemoryState::precondition(&command, &Target::Failure(None), &Params::with_widening());
+
+        // only odd inputs reach 11
+        assert_eq!(
+            vec![(
+                "x".to_string(),
+                ProductAbstraction((f64::MIN, 11.0).into(), ParityAbstraction::Odd)
+            )],
+            precondition.inputs
+        );
+    }
+
+    #[test]
+    fn long_loop_without_widening() {
+        let program = "input(x); while (x <= 1000) {x := x + 1}";
+        let command = parse(program);
+
+        let target = Target::Post(vec![parse_guard("x == 1001")]);
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &target, &Params::no_widening());
+
+        // the head grows by one per iteration and is widened after a bounded number of joins
+        assert_eq!(
+            vec![("x".to_string(), (f64::MIN, 1001.0).into())],
+            precondition.inputs
+        );
+    }
+
+    #[test]
+    fn break_leads_to_the_state_after_the_loop() {
+        let program = "input(x); while (x > 0) {if (x == 5) {break}; x := x - 1}; assert(x != 5)";
+        let command = parse(program);
+
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &Target::Failure(None), &Params::with_widening());
+
+        assert_eq!(
+            vec![("x".to_string(), (5.0, f64::MAX).into())],
+            precondition.inputs
+        );
+    }
+
+    #[test]
+    fn failure_of_one_assertion() {
+        let program = "input(x); assert(x > 0); assert(x > 5)";
+        let command = parse(program);
+        let params = Params::no_widening();
+
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &Target::Failure(Some(25)), &params);
+        assert_eq!(
+            vec![("x".to_string(), (1.0, 5.0).into())],
+            precondition.inputs
+        );
+
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &Target::Failure(None), &params);
+        assert_eq!(
+            vec![("x".to_string(), (f64::MIN, 5.0).into())],
+            precondition.inputs
+        );
+    }
+
+    #[test]
+    fn unreachable_failure() {
+        let program = "x := 5; assert(x < 10)";
+        let command = parse(program);
+
+        let precondition: Precondition<IntervalAbstraction> =
+            MemoryState::precondition(&command, &Target::Failure(None), &Params::no_widening());
+        assert!(!precondition.reachable);
+    }
+}
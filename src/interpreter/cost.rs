@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+use std::{cmp, ops};
+
+use super::termination::{nested_loops, seq, var};
+use super::{AbstractProperties, Bottom, MemoryState, Params, Top, Trace};
+use crate::command_parser::{BExpr, Command, Const, SExpr};
+
+/// The hidden variable that counts all loop iterations of the program.
+const TOTAL: &str = "#total";
+
+/// Upper bounds of the numbers of loop iterations, `None` where no bound was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cost {
+    /// The offsets of the loops outside of procedure bodies with the bounds of their numbers of
+    /// iterations per execution of the loop.
+    pub loops: Vec<(usize, Option<Bound>)>,
+    /// The bound of the number of all loop iterations of the program.
+    pub total: Option<Bound>,
+}
+
+/// An upper bound of a number of loop iterations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bound {
+    Const(f64),
+    /// An expression in the input values of the variables, where `x0` is the value that
+    /// `input(x)` reads, e.g. `10 - x0`. It bounds the number of iterations where it is positive,
+    /// which is 0 elsewhere.
+    Symbolic(String),
+}
+
+impl Bound {
+    /// The bound of a loop whose ranking function `x` starts at the input value `x0`, decreases
+    /// by at least `step` and stays at least `lo`, that is `(x0 - lo) / step + 1`, or if
+    /// `decreases` is not set, whose ranking function `-x` stays at most `hi`, that is
+    /// `(hi - x0) / step + 1`.
+    pub(super) fn from_input(ident: &str, bound: f64, step: f64, decreases: bool) -> Bound {
+        let input = format!("{}0", ident);
+        Bound::Symbolic(match (decreases, step == 1.0) {
+            (true, true) => difference(&input, bound - 1.0),
+            (true, false) => format!("({}) / {} + 1", difference(&input, bound), step),
+            (false, true) if bound + 1.0 == 0.0 => format!("-{}", input),
+            (false, true) => format!("{} - {}", bound + 1.0, input),
+            (false, false) => format!("({} - {}) / {} + 1", bound, input, step),
+        })
+    }
+
+    /// The sum of `bounds`, where the symbolic ones are only added where they are positive.
+    fn sum(bounds: Vec<Bound>) -> Bound {
+        let mut constant = 0.0;
+        let mut terms = vec![];
+        for bound in bounds {
+            match bound {
+                Bound::Const(number) => constant += number,
+                Bound::Symbolic(expr) => terms.push(expr),
+            }
+        }
+        match terms.as_slice() {
+            [] => Bound::Const(constant),
+            [expr] if constant == 0.0 => Bound::Symbolic(expr.clone()),
+            _ => {
+                let terms = terms.iter().map(|expr| format!("max(0, {})", expr));
+                let constant = (constant != 0.0).then(|| constant.to_string());
+                Bound::Symbolic(terms.chain(constant).collect::<Vec<_>>().join(" + "))
+            }
+        }
+    }
+
+    /// The bound as a factor of a product.
+    fn factor(&self) -> String {
+        match self {
+            Bound::Symbolic(expr) if expr.contains(' ') => format!("({})", expr),
+            bound => bound.to_string(),
+        }
+    }
+}
+
+/// `expr - number`, without subtracting 0 or a negative number.
+fn difference(expr: &str, number: f64) -> String {
+    match number {
+        _ if number == 0.0 => expr.to_string(),
+        _ if number < 0.0 => format!("{} + {}", expr, -number),
+        _ => format!("{} - {}", expr, number),
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bound::Const(number) => write!(f, "{}", number),
+            Bound::Symbolic(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl ops::Mul for Bound {
+    type Output = Bound;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Bound::Const(b0), Bound::Const(b1)) => Bound::Const(b0 * b1),
+            (Bound::Const(b), _) | (_, Bound::Const(b)) if b == 0.0 => Bound::Const(0.0),
+            (Bound::Const(b), bound) | (bound, Bound::Const(b)) if b == 1.0 => bound,
+            (b0, b1) => Bound::Symbolic(format!("{} * {}", b0.factor(), b1.factor())),
+        }
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Bounds the numbers of iterations of the loops of `c`. Every loop is instrumented with a
+    /// hidden counter that is reset before the loop and incremented in every iteration, and a
+    /// hidden counter of all iterations is incremented as well. The bound of a loop is the
+    /// smaller one of the largest value of its counter and the bound that follows from its
+    /// ranking function, see `check_loops`, which may be symbolic in the input values. The total
+    /// is the smaller one of the largest value of the total counter and the sum of the bounds of
+    /// the loops, each multiplied by the bounds of the loops that enclose it.
+    ///
+    /// The analysis always widens, since the counters of loops that do not terminate grow
+    /// forever and those of loops that do would take as many joins as the loops iterate.
+    pub fn cost(c: &Command, params: &Params<A>) -> Cost {
+        let params = &Params {
+            use_widening: true,
+            ..*params
+        };
+        let mut loops = vec![];
+        nested_loops(c, &mut vec![], &mut loops);
+
+        let trace = Trace::shared(None);
+        let mut m = MemoryState::new();
+        m.trace = Some(Rc::clone(&trace));
+        m.analyze_command(&seq(reset(TOTAL), count(c)), params);
+        m.trace = None;
+        for state in trace.borrow().states.values() {
+            m.join_state(state, false, &params.widening_treshold);
+        }
+        let largest = |ident: &str| match m.env.get(ident).is_some() {
+            true => m.upper_bound(ident).map(Bound::Const),
+            false => Some(Bound::Const(0.0)),
+        };
+
+        let by_ranking: HashMap<_, _> = Self::check_loops(c, params)
+            .into_iter()
+            .map(|(offset, _, bound)| (offset, bound))
+            .collect();
+        let bounds: HashMap<_, _> = loops
+            .iter()
+            .map(|(offset, _)| {
+                let bound = smaller(largest(&counter(*offset)), by_ranking[offset].clone());
+                (*offset, bound)
+            })
+            .collect();
+
+        let by_loops = loops
+            .iter()
+            .map(|(offset, enclosing)| {
+                let factors = enclosing
+                    .iter()
+                    .chain([offset])
+                    .map(|offset| bounds[offset].clone());
+                match factors
+                    .clone()
+                    .any(|bound| bound == Some(Bound::Const(0.0)))
+                {
+                    true => Some(Bound::Const(0.0)),
+                    false => factors
+                        .collect::<Option<Vec<_>>>()
+                        .map(|factors| factors.into_iter().fold(Bound::Const(1.0), |p, b| p * b)),
+                }
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Bound::sum);
+
+        Cost {
+            loops: loops
+                .iter()
+                .map(|(offset, _)| (*offset, bounds[offset].clone()))
+                .collect(),
+            total: smaller(largest(TOTAL), by_loops),
+        }
+    }
+
+    /// The smallest upper bound of the values of `ident`, `None` if it is not bounded from
+    /// above. The bound is searched among all floats by bisection.
+    pub(super) fn upper_bound(&self, ident: &str) -> Option<f64> {
+        let above = |bound| self.may_satisfy(BExpr::GT(var(ident), Const::Const(bound)));
+        if self.may_satisfy(BExpr::GE(var(ident), Const::Const(f64::MAX))) {
+            return None;
+        }
+        let (mut lo, mut hi) = (key(f64::MIN), key(f64::MAX));
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match above(from_key(mid)) {
+                true => lo = mid + 1,
+                false => hi = mid,
+            }
+        }
+        Some(from_key(lo))
+    }
+
+    /// The greatest lower bound of the values of `ident`, `None` if it is not bounded from
+    /// below.
+    pub(super) fn lower_bound(&self, ident: &str) -> Option<f64> {
+        let below = |bound| self.may_satisfy(BExpr::LT(var(ident), Const::Const(bound)));
+        if self.may_satisfy(BExpr::LE(var(ident), Const::Const(f64::MIN))) {
+            return None;
+        }
+        let (mut lo, mut hi) = (key(f64::MIN), key(f64::MAX));
+        while lo < hi {
+            let mid = hi - (hi - lo) / 2;
+            match below(from_key(mid)) {
+                true => hi = mid - 1,
+                false => lo = mid,
+            }
+        }
+        Some(from_key(lo))
+    }
+
+    fn may_satisfy(&self, bexpr: BExpr) -> bool {
+        !self.clone().filter(&bexpr).is_unreachable()
+    }
+}
+
+/// Maps the floats to integers in the same order, where `-0.0` precedes `0.0`.
+fn key(x: f64) -> u64 {
+    let bits = x.to_bits();
+    match bits >> 63 {
+        1 => !bits,
+        _ => bits | 1 << 63,
+    }
+}
+
+fn from_key(key: u64) -> f64 {
+    match key >> 63 {
+        1 => f64::from_bits(key & !(1 << 63)),
+        _ => f64::from_bits(!key),
+    }
+}
+
+/// The smaller one of two bounds, where a number is preferred to a symbolic bound.
+fn smaller(bound0: Option<Bound>, bound1: Option<Bound>) -> Option<Bound> {
+    match (bound0, bound1) {
+        (Some(Bound::Const(b0)), Some(Bound::Const(b1))) => Some(Bound::Const(b0.min(b1))),
+        (Some(Bound::Symbolic(_)), bound @ Some(_))
+        | (bound @ Some(_), Some(Bound::Symbolic(_))) => bound,
+        (bound, None) | (None, bound) => bound,
+    }
+}
+
+/// The hidden variable that counts the iterations of the loop at `offset`.
+fn counter(offset: usize) -> String {
+    format!("#{}", offset)
+}
+
+fn reset(ident: &str) -> Command {
+    Command::Assign(var(ident), SExpr::CExpr(Const::Const(0.0)))
+}
+
+fn increment(ident: &str) -> Command {
+    let sum = SExpr::Add(
+        Box::new(SExpr::VExpr(var(ident))),
+        Box::new(SExpr::CExpr(Const::Const(1.0))),
+    );
+    Command::Assign(var(ident), sum)
+}
+
+/// Instruments the loops outside of procedure bodies with counters.
+fn count(c: &Command) -> Command {
+    let recurse = |c: &Command| Box::new(count(c));
+    let iteration = |offset, body: &Command| {
+        let increments = seq(increment(&counter(offset)), increment(TOTAL));
+        Box::new(seq(increments, count(body)))
+    };
+    match c {
+        Command::Located(offset, c) => {
+            let located = |c| Command::Located(*offset, Box::new(c));
+            match &**c {
+                Command::While(bexpr, body) => seq(
+                    reset(&counter(*offset)),
+                    located(Command::While(bexpr.clone(), iteration(*offset, body))),
+                ),
+                Command::DoWhile(body, bexpr) => seq(
+                    reset(&counter(*offset)),
+                    located(Command::DoWhile(iteration(*offset, body), bexpr.clone())),
+                ),
+                Command::For(init, bexpr, step, body) => seq(
+                    reset(&counter(*offset)),
+                    located(Command::For(
+                        recurse(init),
+                        bexpr.clone(),
+                        recurse(step),
+                        iteration(*offset, body),
+                    )),
+                ),
+                c => located(count(c)),
+            }
+        }
+        Command::Seq(c1, c2) => Command::Seq(recurse(c1), recurse(c2)),
+        Command::If(bexpr, c1, c2) => Command::If(bexpr.clone(), recurse(c1), recurse(c2)),
+        Command::Choose(c1, c2) => Command::Choose(recurse(c1), recurse(c2)),
+        Command::While(bexpr, c) => Command::While(bexpr.clone(), recurse(c)),
+        Command::DoWhile(c, bexpr) => Command::DoWhile(recurse(c), bexpr.clone()),
+        Command::For(init, bexpr, step, c) => {
+            Command::For(recurse(init), bexpr.clone(), recurse(step), recurse(c))
+        }
+        Command::Partition(partitioning, c) => Command::Partition(*partitioning, recurse(c)),
+        c => c.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::command_parser::parse;
+
+    #[test]
+    fn bounds_of_values() {
+        let m: MemoryState<IntervalAbstraction> = MemoryState::from_state(HashMap::from([
+            ("x".to_string(), (-3.0, 10.0).into()),
+            ("y".to_string(), (0.0, f64::MAX).into()),
+        ]));
+
+        assert_eq!(Some(-3.0), m.lower_bound("x"));
+        assert_eq!(Some(10.0), m.upper_bound("x"));
+        assert_eq!(Some(0.0), m.lower_bound("y"));
+        assert_eq!(None, m.upper_bound("y"));
+    }
+
+    #[test]
+    fn bounds_by_ranking_functions() {
+        let program = "input(n, 0, 20); while (n > 0) {n := n - 3}; for (i := 0; i < 10; i := i + 1) {j := 0; while (j < 5) {j := j + 1}}";
+        let command = parse(program);
+
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &Params::with_widening());
+
+        assert_eq!(
+            Cost {
+                loops: vec![
+                    (17, Some(Bound::Const(7.0))),
+                    (45, Some(Bound::Const(10.0))),
+                    (87, Some(Bound::Const(5.0)))
+                ],
+                total: Some(Bound::Const(67.0)),
+            },
+            cost
+        );
+    }
+
+    #[test]
+    fn bounds_by_counters() {
+        let program = "x := 1; while (x < 100) {x := x * 2}";
+        let command = parse(program);
+
+        let params = Params {
+            partition_iterations: 10,
+            ..Params::with_widening()
+        };
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &params);
+
+        assert_eq!(
+            Cost {
+                loops: vec![(8, Some(Bound::Const(7.0)))],
+                total: Some(Bound::Const(7.0)),
+            },
+            cost
+        );
+    }
+
+    #[test]
+    fn widening_is_forced() {
+        let program = "x := 0; while (x < 10) {x := x + 1}";
+        let command = parse(program);
+
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &Params::no_widening());
+
+        assert_eq!(
+            Cost {
+                loops: vec![(8, Some(Bound::Const(10.0)))],
+                total: Some(Bound::Const(10.0)),
+            },
+            cost
+        );
+    }
+
+    #[test]
+    fn bounds_in_inputs() {
+        let program = "input(x); while (x < 10) {x := x + 1}";
+        let command = parse(program);
+
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &Params::with_widening());
+
+        let bound = Bound::Symbolic("10 - x0".to_string());
+        assert_eq!(
+            Cost {
+                loops: vec![(10, Some(bound.clone()))],
+                total: Some(bound),
+            },
+            cost
+        );
+
+        let program =
+            "input(n); while (n > 0) {n := n - 2}; for (i := 0; i < 3; i := i + 1) {skip}";
+        let command = parse(program);
+
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &Params::with_widening());
+
+        assert_eq!(
+            Cost {
+                loops: vec![
+                    (10, Some(Bound::Symbolic("(n0 - 1) / 2 + 1".to_string()))),
+                    (38, Some(Bound::Const(3.0))),
+                ],
+                total: Some(Bound::Symbolic("max(0, (n0 - 1) / 2 + 1) + 3".to_string())),
+            },
+            cost
+        );
+
+        let program = "input(x); x := x - 1; while (x > 0) {x := x - 1}";
+        let command = parse(program);
+
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &Params::with_widening());
+
+        assert_eq!(
+            Cost {
+                loops: vec![(22, None)],
+                total: None,
+            },
+            cost
+        );
+    }
+
+    #[test]
+    fn unreachable_loops() {
+        let program =
+            "input(x); while (x > 0) {x := x - 1}; if (x > 0) {while (x > 0) {skip}} else {skip}";
+        let command = parse(program);
+
+        let cost = MemoryState::<IntervalAbstraction>::cost(&command, &Params::with_widening());
+
+        assert_eq!(
+            Cost {
+                loops: vec![
+                    (10, Some(Bound::Symbolic("x0".to_string()))),
+                    (50, Some(Bound::Const(0.0))),
+                ],
+                total: Some(Bound::Symbolic("x0".to_string())),
+            },
+            cost
+        );
+    }
+}
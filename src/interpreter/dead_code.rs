@@ -0,0 +1,182 @@
+use std::fmt::{self, Debug};
+use std::{cmp, ops};
+
+use super::{AbstractProperties, Bottom, MemoryState, Top};
+
+/// A statement with a guard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Guarded {
+    If,
+    /// A `while` or `for` loop.
+    Loop,
+    DoWhile,
+}
+
+/// Whether the guard of a statement may hold and may fail in the states that reach it.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Outcomes {
+    guarded: Guarded,
+    may_hold: bool,
+    may_fail: bool,
+}
+
+/// A guard that has the same outcome in all states that reach it, and the code that is
+/// unreachable because of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadCode {
+    /// The offset of the statement with the guard.
+    pub offset: usize,
+    pub guarded: Guarded,
+    /// The outcome of the guard.
+    pub always: bool,
+}
+
+impl fmt::Display for DeadCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.guarded, self.always) {
+            (Guarded::If, true) => {
+                write!(f, "guard is always true, the else branch is unreachable")
+            }
+            (Guarded::If, false) => {
+                write!(f, "guard is always false, the then branch is unreachable")
+            }
+            (Guarded::Loop, false) => {
+                write!(f, "guard is always false, the loop body is unreachable")
+            }
+            (Guarded::DoWhile, false) => {
+                write!(f, "guard is always false, the loop body runs once")
+            }
+            (_, true) => write!(f, "guard is always true, the loop is only left by break"),
+        }
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// The guards that the analysis found to have the same outcome whenever they are checked,
+    /// ordered by their offsets. Guards of statements that are never reached are not listed.
+    pub fn dead_code(&self) -> Vec<DeadCode> {
+        let mut dead_code: Vec<_> = self
+            .guards
+            .borrow()
+            .iter()
+            .filter(|(_, outcomes)| outcomes.may_hold != outcomes.may_fail)
+            .map(|(offset, outcomes)| DeadCode {
+                offset: *offset,
+                guarded: outcomes.guarded,
+                always: outcomes.may_hold,
+            })
+            .collect();
+        dead_code.sort_by_key(|dead_code| dead_code.offset);
+        dead_code
+    }
+
+    /// Records that the guard of the statement at `offset` may hold or fail in a state that
+    /// reaches it.
+    pub(super) fn record_guard(
+        &self,
+        offset: usize,
+        guarded: Guarded,
+        may_hold: bool,
+        may_fail: bool,
+    ) {
+        let mut guards = self.guards.borrow_mut();
+        let outcomes = guards.entry(offset).or_insert(Outcomes {
+            guarded,
+            may_hold: false,
+            may_fail: false,
+        });
+        outcomes.may_hold |= may_hold;
+        outcomes.may_fail |= may_fail;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::parse;
+    use crate::interpreter::Params;
+
+    fn dead_code(offset: usize, guarded: Guarded, always: bool) -> DeadCode {
+        DeadCode {
+            offset,
+            guarded,
+            always,
+        }
+    }
+
+    #[test]
+    fn unreachable_branches_and_loop_bodies() {
+        let program = "x := 5; if (x > 0) {y := 1} else {y := 2}; while (y > 3) {y := y - 1}; for (i := 0; i < 0; i := i + 1) {skip}; if (y == 1) {skip} else {skip}";
+        let command = parse(program);
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let dead = m
+            .analyze_command(&command, &Params::no_widening())
+            .dead_code();
+
+        assert_eq!(
+            vec![
+                dead_code(8, Guarded::If, true),
+                dead_code(43, Guarded::Loop, false),
+                dead_code(71, Guarded::Loop, false),
+                dead_code(111, Guarded::If, true),
+            ],
+            dead
+        );
+    }
+
+    #[test]
+    fn guards_of_loops_that_are_only_left_by_break() {
+        let program = "x := 0; while (x >= 0) {x := x + 1; if (x > 10) {break} else {skip}}; do {x := 0 - 1} while (x > 100)";
+        let command = parse(program);
+
+        let mut m: MemoryState<SignAbstraction> = MemoryState::new();
+        let dead = m
+            .analyze_command(&command, &Params::no_widening())
+            .dead_code();
+
+        assert_eq!(
+            vec![
+                dead_code(8, Guarded::Loop, true),
+                dead_code(70, Guarded::DoWhile, false),
+            ],
+            dead
+        );
+    }
+
+    #[test]
+    fn guards_in_partitions_and_procedures() {
+        let program = "proc f(a) {if (a < 0) {b := 0} else {b := a}; return b}; input(x); y := f(1); @partition if (x > 0) {z := 1} else {z := 2}; if (z == 3) {skip} else {skip}";
+        let command = parse(program);
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let dead = m
+            .analyze_command(&command, &Params::no_widening())
+            .dead_code();
+
+        assert_eq!(
+            vec![
+                dead_code(11, Guarded::If, false),
+                dead_code(124, Guarded::If, false),
+            ],
+            dead
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt::Debug;
+use std::{cmp, ops};
+
+use super::{AbstractProperties, Bottom, Guarded, MemoryState, Params, Top, MAX_JOINS};
+use crate::command_parser::{BExpr, Command};
+
+/// The states that left the body of a loop by `break` and `continue` in the current iteration.
+pub(super) struct Jumps<
+    A: ops::Add<Output = A>
+        + ops::Neg<Output = A>
+        + ops::Sub<Output = A>
+        + ops::Mul<Output = A>
+        + ops::Div<Output = A>
+        + cmp::PartialEq
+        + cmp::PartialOrd
+        + From<f64>
+        + Copy
+        + AbstractProperties<A>
+        + From<Top>
+        + From<Bottom>,
+> {
+    pub(super) breaks: Option<MemoryState<A>>,
+    pub(super) continues: Option<MemoryState<A>>,
+}
+
+/// How the execution continues after a command.
+#[derive(PartialEq)]
+pub(super) enum Flow {
+    Normal,
+    Break,
+    Continue,
+    /// The concrete execution stopped at an overflow of a trapping integer or after its last
+    /// step, see `step`, and the state is unreachable.
+    Trap,
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Analyzes `while (bexpr) {body; step}`, where `step` is not skipped by `continue`. If
+    /// `body_first` is set, the body is analyzed once before the guard is checked the first time.
+    /// The states that leave the body by `continue` are joined at the loop head, the ones that
+    /// leave it by `break` at the loop exit. The loop head is widened after `MAX_JOINS` joins
+    /// even if widening is disabled.
+    pub(super) fn analyze_loop(
+        &mut self,
+        bexpr: &BExpr,
+        body: &Command,
+        step: Option<&Command>,
+        body_first: bool,
+        params: &Params<A>,
+    ) {
+        let offset = self.location;
+        let guarded = match body_first {
+            true => Guarded::DoWhile,
+            false => Guarded::Loop,
+        };
+        self.enter_loop();
+
+        if body_first {
+            self.analyze_iteration(body, step, params);
+        }
+
+        for _ in 0..params.loop_unrollings {
+            self.analyze_iteration(body, step, params);
+        }
+
+        let mut nr_of_joins: usize = 0;
+        loop {
+            let prev_m = self.clone();
+
+            self.filter(bexpr);
+            self.record_guard(offset, guarded, !self.is_unreachable(), false);
+
+            self.analyze_iteration(body, step, params);
+
+            if nr_of_joins >= MAX_JOINS
+                || params.use_widening && nr_of_joins >= params.widening_delays as usize
+            {
+                self.join_state(&prev_m, true, &params.widening_treshold);
+            } else {
+                self.join_state(&prev_m, false, &params.widening_treshold);
+                nr_of_joins += 1;
+            }
+
+            if prev_m.includes(self) {
+                break;
+            }
+        }
+
+        self.filter(&bexpr.negate());
+        self.record_guard(offset, guarded, false, !self.is_unreachable());
+
+        if let Some(breaks) = self.leave_loop().breaks {
+            self.join_state(&breaks, false, &params.widening_treshold);
+        }
+    }
+
+    fn analyze_iteration(&mut self, body: &Command, step: Option<&Command>, params: &Params<A>) {
+        self.analyze_command(body, params);
+
+        let continues = self.jumps.borrow_mut().last_mut().unwrap().continues.take();
+        if let Some(continues) = continues {
+            self.join_state(&continues, false, &params.widening_treshold);
+        }
+
+        if let Some(step) = step {
+            self.analyze_command(step, params);
+        }
+    }
+
+    /// Joins the state into the states that leave the innermost loop by `break` or `continue`,
+    /// and makes it unreachable.
+    pub(super) fn jump(&mut self, flow: Flow, params: &Params<A>) {
+        {
+            let mut jumps = self.jumps.borrow_mut();
+            let jumps = match jumps.last_mut() {
+                Some(jumps) => jumps,
+                None => panic!("break or continue outside of a loop"),
+            };
+            let target = match flow {
+                Flow::Break => &mut jumps.breaks,
+                _ => &mut jumps.continues,
+            };
+            match target {
+                Some(m) => {
+                    m.join_state(self, false, &params.widening_treshold);
+                }
+                None => *target = Some(self.clone()),
+            }
+        }
+        self.set_unreachable();
+    }
+
+    pub(super) fn enter_loop(&self) {
+        self.jumps.borrow_mut().push(Jumps {
+            breaks: None,
+            continues: None,
+        });
+    }
+
+    pub(super) fn leave_loop(&self) -> Jumps<A> {
+        self.jumps.borrow_mut().pop().unwrap()
+    }
+
+    /// Interprets `while (bexpr) {body; step}`, where `step` is not skipped by `continue`.
+    pub(super) fn interprete_loop(
+        &mut self,
+        bexpr: &BExpr,
+        body: &Command,
+        step: Option<&Command>,
+        body_first: bool,
+    ) -> Flow {
+        let mut first = body_first;
+        while first || self.interprete_bexpr(bexpr) {
+            first = false;
+            if !self.step() {
+                self.set_unreachable();
+                return Flow::Trap;
+            }
+            match self.interprete_statement(body) {
+                Flow::Break => break,
+                Flow::Trap => return Flow::Trap,
+                Flow::Normal | Flow::Continue => (),
+            }
+            if let Some(step) = step {
+                if self.interprete_statement(step) == Flow::Trap {
+                    return Flow::Trap;
+                }
+            }
+        }
+        Flow::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::abstractions::interval_abstraction::{Interval, IntervalAbstraction};
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::parse;
+    use crate::interpreter::{MemoryState, Params};
+
+    #[test]
+    fn interprete_for_with_break_and_continue() {
+        let program = "s := 0; for (i := 0; i < 10; i := i + 1) {if (i == 3) {continue} else {skip}; if (i == 6) {break} else {skip}; s := s + i}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(12.0, 12.0)),
+            *post.lookup_var("s").unwrap()
+        );
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(6.0, 6.0)),
+            *post.lookup_var("i").unwrap()
+        );
+    }
+
+    #[test]
+    fn long_loop_without_widening() {
+        let program = "x := 0; while (x < 1000) {x := x + 1}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.analyze_command(&command, &Params::no_widening());
+
+        // the loop head is widened after a bounded number of joins
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(1000.0, f64::MAX)),
+            *post.lookup_var("x").unwrap()
+        );
+    }
+
+    #[test]
+    fn interprete_do_while() {
+        let program = "x := 10; do {x := x + 1} while (x < 5)";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post = pre.interprete_command(&command);
+
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(11.0, 11.0)),
+            *post.lookup_var("x").unwrap()
+        );
+    }
+
+    #[test]
+    fn break_states_are_joined_at_the_exit() {
+        let program = "i := 0; while (i <= 99) {if (i > 9) {break} else {skip}; i := i + 1}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            IntervalAbstraction::Interval(Interval::new(10.0, 10.0)),
+            *post_analyzed.lookup_var("i").unwrap()
+        );
+    }
+
+    #[test]
+    fn continue_states_are_joined_at_the_head() {
+        let program = "x := 1; input(i); for (j := 0; i > 0; input(i)) {if (i == 5) {x := 0 - 1; continue} else {skip}; x := 1}";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            SignAbstraction::NonZero,
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+    }
+
+    #[test]
+    fn do_while_runs_the_body_first() {
+        let program = "x := 0 - 1; do {x := x + 1} while (x < 0)";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        assert_eq!(
+            SignAbstraction::NonNeg,
+            *post_analyzed.lookup_var("x").unwrap()
+        );
+    }
+}
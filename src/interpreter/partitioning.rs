@@ -0,0 +1,319 @@
+use std::fmt::Debug;
+use std::{cmp, ops};
+
+use super::{AbstractProperties, Bottom, Guarded, MemoryState, Params, Top};
+use crate::command_parser::{BExpr, Command, Partitioning};
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Analyzes `c` like `analyze_command`, but returns the partitions that are still kept
+    /// separated at the end of `c` instead of merging them.
+    pub fn analyze_partitions(&self, c: &Command, params: &Params<A>) -> Vec<MemoryState<A>> {
+        Self::partitions_after(vec![self.clone()], c, params)
+    }
+
+    fn partitions_after(
+        partitions: Vec<MemoryState<A>>,
+        c: &Command,
+        params: &Params<A>,
+    ) -> Vec<MemoryState<A>> {
+        match c {
+            Command::Seq(c1, c2) => {
+                let partitions = Self::partitions_after(partitions, c1, params);
+                Self::partitions_after(partitions, c2, params)
+            }
+            Command::Located(offset, c) => {
+                let partitions = partitions
+                    .into_iter()
+                    .map(|mut m| {
+                        m.visit(*offset, false);
+                        m
+                    })
+                    .collect();
+                Self::partitions_after(partitions, c, params)
+            }
+            Command::Partition(Partitioning::Branches, c) => match c.as_ref() {
+                Command::If(bexpr, c1, c2) => {
+                    Self::split_branches(partitions, bexpr, c1, c2, params)
+                }
+                _ => Self::partitions_after(partitions, c, params),
+            },
+            Command::Partition(Partitioning::Iterations(k), c) => match c.as_ref() {
+                Command::While(bexpr, body) => {
+                    Self::split_iterations(partitions, c, bexpr, body, *k, params)
+                }
+                _ => Self::partitions_after(partitions, c, params),
+            },
+            Command::If(bexpr, c1, c2) if params.partition_branches => {
+                Self::split_branches(partitions, bexpr, c1, c2, params)
+            }
+            Command::While(bexpr, body) if params.partition_iterations > 0 => {
+                Self::split_iterations(
+                    partitions,
+                    c,
+                    bexpr,
+                    body,
+                    params.partition_iterations,
+                    params,
+                )
+            }
+            _ => partitions
+                .into_iter()
+                .map(|mut m| {
+                    m.analyze_statement(c, params);
+                    m
+                })
+                .collect(),
+        }
+    }
+
+    /// Keeps the states of both branches separated instead of joining them.
+    fn split_branches(
+        partitions: Vec<MemoryState<A>>,
+        bexpr: &BExpr,
+        c1: &Command,
+        c2: &Command,
+        params: &Params<A>,
+    ) -> Vec<MemoryState<A>> {
+        let mut split = Vec::new();
+
+        for m in partitions {
+            let mut m1 = m.clone();
+            m1.filter(bexpr);
+            let mut m2 = m;
+            m2.filter(&bexpr.negate());
+            m2.record_guard(
+                m2.location,
+                Guarded::If,
+                !m1.is_unreachable(),
+                !m2.is_unreachable(),
+            );
+
+            m1.analyze_command(c1, params);
+            split.push(m1);
+            m2.analyze_command(c2, params);
+            split.push(m2);
+        }
+
+        Self::prune(split)
+    }
+
+    /// Keeps the states that leave the loop after 0, 1, ..., k - 1 iterations separated from each
+    /// other and from the states that leave the loop after k or more iterations.
+    fn split_iterations(
+        partitions: Vec<MemoryState<A>>,
+        c: &Command,
+        bexpr: &BExpr,
+        body: &Command,
+        k: u8,
+        params: &Params<A>,
+    ) -> Vec<MemoryState<A>> {
+        let mut split = Vec::new();
+
+        for mut m in partitions {
+            let offset = m.location;
+            for _ in 0..k {
+                let mut exit = m.clone();
+                exit.filter(&bexpr.negate());
+                m.filter(bexpr);
+                m.record_guard(
+                    offset,
+                    Guarded::Loop,
+                    !m.is_unreachable(),
+                    !exit.is_unreachable(),
+                );
+                split.push(exit);
+
+                m.enter_loop();
+                m.analyze_command(body, params);
+                let jumps = m.leave_loop();
+                if let Some(continues) = jumps.continues {
+                    m.join_state(&continues, false, &params.widening_treshold);
+                }
+                if let Some(breaks) = jumps.breaks {
+                    split.push(breaks);
+                }
+            }
+
+            m.location = offset;
+            m.analyze_statement(c, params);
+            split.push(m);
+        }
+
+        Self::prune(split)
+    }
+
+    /// Drops unreachable partitions, but keeps one if no partition is reachable at all.
+    fn prune(partitions: Vec<MemoryState<A>>) -> Vec<MemoryState<A>> {
+        if partitions.iter().all(|m| m.is_unreachable()) {
+            partitions.into_iter().take(1).collect()
+        } else {
+            partitions
+                .into_iter()
+                .filter(|m| !m.is_unreachable())
+                .collect()
+        }
+    }
+
+    /// Joins all partitions into a single state, as it happens at the end of a scope.
+    pub(super) fn merge(partitions: Vec<MemoryState<A>>, params: &Params<A>) -> MemoryState<A> {
+        let mut partitions = partitions.into_iter();
+        let mut merged = partitions.next().unwrap_or_default();
+
+        for m in partitions {
+            merged.join_state(&m, false, &params.widening_treshold);
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::parse;
+
+    #[test]
+    fn without_partitioning() {
+        let program = "if (x < 0) {y := -1} else {y := 1}; z := y * y";
+        let command = parse(program);
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let z_analyzed = post_analyzed.lookup_var("z").unwrap();
+        assert_eq!(SignAbstraction::NonZero, *z_analyzed);
+    }
+
+    #[test]
+    fn partitioned_branches() {
+        let program = "@partition if (x < 0) {y := -1} else {y := 1}; z := y * y";
+        let command = parse(program);
+
+        let pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let partitions = pre.analyze_partitions(&command, &Params::no_widening());
+
+        assert_eq!(2, partitions.len());
+        assert_eq!(Some(&SignAbstraction::Neg), partitions[0].lookup_var("y"));
+        assert_eq!(Some(&SignAbstraction::Pos), partitions[1].lookup_var("y"));
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &Params::no_widening());
+
+        let z_analyzed = post_analyzed.lookup_var("z").unwrap();
+        assert_eq!(SignAbstraction::Pos, *z_analyzed);
+    }
+
+    #[test]
+    fn partitioned_branches_by_params() {
+        let program = "if (x < 0) {y := -1} else {y := 1}; z := y * y";
+        let command = parse(program);
+
+        let params = Params {
+            partition_branches: true,
+            ..Params::no_widening()
+        };
+
+        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &params);
+
+        let z_analyzed = post_analyzed.lookup_var("z").unwrap();
+        assert_eq!(SignAbstraction::Pos, *z_analyzed);
+    }
+
+    #[test]
+    fn unreachable_branch_is_dropped() {
+        let program = "x := 1; @partition if (x < 0) {y := -1} else {y := 1}";
+        let command = parse(program);
+
+        let pre: MemoryState<SignAbstraction> = MemoryState::new();
+        let partitions = pre.analyze_partitions(&command, &Params::no_widening());
+
+        assert_eq!(1, partitions.len());
+        assert_eq!(Some(&SignAbstraction::Pos), partitions[0].lookup_var("y"));
+    }
+
+    #[test]
+    fn partitions_are_merged_at_the_end_of_the_scope() {
+        let program =
+            "if (x > 0) {@partition if (x > 5) {y := 1} else {y := 2}} else {y := 3}; z := y";
+        let command = parse(program);
+
+        let pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let partitions = pre.analyze_partitions(&command, &Params::no_widening());
+
+        assert_eq!(1, partitions.len());
+        assert_eq!(Some(&(1.0, 3.0).into()), partitions[0].lookup_var("z"));
+    }
+
+    #[test]
+    fn partitioned_iterations() {
+        let program = "input(i); x := 0; @partition(2) while (i > 0) {x := x + 1; input(i)}";
+        let command = parse(program);
+
+        let params = Params::with_widening();
+
+        let pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let partitions = pre.analyze_partitions(&command, &params);
+
+        assert_eq!(3, partitions.len());
+        assert_eq!(Some(&(0.0, 0.0).into()), partitions[0].lookup_var("x"));
+        assert_eq!(Some(&(1.0, 1.0).into()), partitions[1].lookup_var("x"));
+        assert_eq!(Some(&(2.0, f64::MAX).into()), partitions[2].lookup_var("x"));
+
+        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let post_analyzed = pre.analyze_command(&command, &params);
+
+        let x_analyzed = post_analyzed.lookup_var("x").unwrap();
+        let x_truth: IntervalAbstraction = (0.0, f64::MAX).into();
+        assert_eq!(x_truth, *x_analyzed);
+    }
+
+    #[test]
+    fn partitioned_iterations_by_params() {
+        let program = "input(i); x := 0; while (i > 0) {x := x + 1; input(i)}";
+        let command = parse(program);
+
+        let params = Params {
+            partition_iterations: 1,
+            ..Params::with_widening()
+        };
+
+        let pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let partitions = pre.analyze_partitions(&command, &params);
+
+        assert_eq!(2, partitions.len());
+        assert_eq!(Some(&(0.0, 0.0).into()), partitions[0].lookup_var("x"));
+        assert_eq!(Some(&(1.0, f64::MAX).into()), partitions[1].lookup_var("x"));
+    }
+
+    #[test]
+    fn break_is_a_separate_exit() {
+        let program = "input(i); x := 0; @partition(1) while (i > 0) {if (i == 5) {x := 5; break} else {skip}; input(i)}";
+        let command = parse(program);
+
+        let pre: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let partitions = pre.analyze_partitions(&command, &Params::no_widening());
+
+        assert_eq!(3, partitions.len());
+        assert_eq!(Some(&(0.0, 0.0).into()), partitions[0].lookup_var("x"));
+        assert_eq!(Some(&(5.0, 5.0).into()), partitions[1].lookup_var("x"));
+        assert_eq!(Some(&(0.0, 5.0).into()), partitions[2].lookup_var("x"));
+    }
+}
@@ -0,0 +1,399 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+use std::{cmp, ops};
+
+use super::arrays::integer_bounds;
+use super::termination::{seq, var};
+use super::{AbstractProperties, Alarm, Bottom, Env, MemoryState, Params, Target, Top};
+use crate::abstractions::interval_abstraction::IntervalAbstraction;
+use crate::command_parser::{BExpr, Command, Const, Procedure, SExpr, Var};
+use crate::dataflow::uninitialized_reads;
+
+/// The maximal number of rounds of a forward and a backward analysis that refine an alarm.
+const MAX_ROUNDS: usize = 10;
+
+/// The maximal number of loop iterations and calls of an execution that looks for a witness of
+/// an alarm.
+const MAX_STEPS: usize = 1000;
+
+/// The states before the located statements that an analysis reaches, joined over all visits,
+/// and the states of the analysis in the other direction that restrict them.
+pub(super) struct Trace<
+    A: ops::Add<Output = A>
+        + ops::Neg<Output = A>
+        + ops::Sub<Output = A>
+        + ops::Mul<Output = A>
+        + ops::Div<Output = A>
+        + cmp::PartialEq
+        + cmp::PartialOrd
+        + From<f64>
+        + Copy
+        + AbstractProperties<A>
+        + From<Top>
+        + From<Bottom>,
+> {
+    pub(super) states: HashMap<usize, MemoryState<A>>,
+    restriction: Option<HashMap<usize, MemoryState<A>>>,
+}
+
+/// What the refinement of an alarm found out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// No execution violates the assertion.
+    Spurious,
+    /// An execution violates the assertion, which was found by running the program on inputs
+    /// that satisfy the precondition of the violation.
+    Definite,
+    /// The refinement could neither rule out nor confirm a violation.
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Spurious => write!(f, "spurious"),
+            Verdict::Definite => write!(f, "definite"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Refines the alarm of the assertion `bexpr` at `offset` by abstract debugging: a forward
+    /// analysis that is restricted to the states that may lead to a violation of the assertion,
+    /// and a backward analysis from the violation that is restricted to the states that the
+    /// forward analysis reaches, alternate until the forward analysis is stable.
+    ///
+    /// The alarm is spurious if no violation remains. It is definite if a concrete execution
+    /// from the precondition of the violation that the backward analysis infers violates the
+    /// assertion, see `witness`.
+    pub fn refine_assertion(
+        c: &Command,
+        offset: usize,
+        bexpr: &BExpr,
+        params: &Params<A>,
+    ) -> Verdict {
+        let target = Target::Failure(Some(offset));
+        let mut restriction = None;
+        let mut prev_forward = None;
+
+        for _ in 0..MAX_ROUNDS {
+            let trace = Trace::shared(restriction.take());
+            let mut m = MemoryState::new();
+            m.trace = Some(Rc::clone(&trace));
+            m.analyze_command(c, params);
+            let forward = std::mem::take(&mut trace.borrow_mut().states);
+
+            let at_assertion = match forward.get(&offset) {
+                Some(m) if !m.is_unreachable() => m.clone(),
+                _ => return Verdict::Spurious,
+            };
+            let mut fails = at_assertion;
+            fails.filter(&bexpr.negate());
+            if fails.is_unreachable() {
+                return Verdict::Spurious;
+            }
+            if prev_forward.as_ref() == Some(&forward) {
+                return Verdict::Unknown;
+            }
+
+            let trace = Trace::shared(Some(forward.clone()));
+            let (m, inputs) = Self::analyze_backward(c, &target, params, Some(Rc::clone(&trace)));
+            if m.is_unreachable() {
+                return Verdict::Spurious;
+            }
+            if Self::witness(c, offset, bexpr, &m, &inputs) {
+                return Verdict::Definite;
+            }
+            restriction = Some(std::mem::take(&mut trace.borrow_mut().states));
+            prev_forward = Some(forward);
+        }
+
+        Verdict::Unknown
+    }
+
+    /// Whether a concrete execution of `c` violates the assertion `bexpr` at `offset`. The inputs
+    /// and the variables that are read before they are initialized get the value closest to 0,
+    /// the lowest or the highest integer of the precondition `m` and `inputs` of the violation,
+    /// and every choice is tried. The execution interpretes intervals, which are exact for single
+    /// values, and stops after `MAX_STEPS` loop iterations and calls.
+    fn witness(
+        c: &Command,
+        offset: usize,
+        bexpr: &BExpr,
+        m: &MemoryState<A>,
+        inputs: &HashMap<String, A>,
+    ) -> bool {
+        let top = A::top().into();
+        (0..3).any(|choice| {
+            let input = |ident: &str| candidate(inputs.get(ident).unwrap_or(&top), choice);
+            let initial = |ident: &str| candidate(m.env.get(ident).unwrap_or(&top), choice);
+
+            let program = seq(
+                initialize(c, &[], &initial),
+                concretize(c, &input, &initial),
+            );
+            let mut execution: MemoryState<IntervalAbstraction> = MemoryState::new();
+            execution.steps = Some(Rc::new(Cell::new(MAX_STEPS)));
+            execution.interprete_command(&program);
+            execution
+                .alarms()
+                .contains(&Alarm::Assertion(offset, bexpr.clone()))
+        })
+    }
+
+    /// Sets the location to the statement at `offset` and, if there is a trace, restricts the
+    /// state to the states of the other analysis before the statement and collects it. A
+    /// statement that the other analysis did not reach is unreachable if
+    /// `unvisited_are_unreachable` is set, otherwise its state is not restricted.
+    pub(super) fn visit(&mut self, offset: usize, unvisited_are_unreachable: bool) {
+        self.location = offset;
+        let trace = match &self.trace {
+            Some(trace) => Rc::clone(trace),
+            None => return,
+        };
+        let mut trace = trace.borrow_mut();
+
+        match trace.restriction.as_ref().map(|states| states.get(&offset)) {
+            Some(Some(m)) => self.meet_state(m),
+            Some(None) if unvisited_are_unreachable => self.set_unreachable(),
+            _ => (),
+        }
+
+        let mut m = self.clone();
+        m.trace = None;
+        let treshold = A::top().into();
+        trace
+            .states
+            .entry(offset)
+            .and_modify(|states| {
+                states.join_state(&m, false, &treshold);
+            })
+            .or_insert(m);
+    }
+
+    /// Keeps only the values of the variables that are values in `other` as well. Variables that
+    /// are missing in either state may have any value.
+    fn meet_state(&mut self, other: &MemoryState<A>) {
+        let state = match (&mut self.env, &other.env) {
+            (Env::Reachable(state), Env::Reachable(_)) => state,
+            (Env::Reachable(_), Env::Unreachable) => return self.set_unreachable(),
+            (Env::Unreachable, _) => return,
+        };
+        for (ident, a_other) in other.env.iter() {
+            state
+                .entry(ident.clone())
+                .and_modify(|a_self| *a_self = A::meet(a_self, a_other))
+                .or_insert(*a_other);
+        }
+        if state.values().any(|a| *a == A::bottom().into()) {
+            self.set_unreachable();
+        }
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>,
+    > Trace<A>
+{
+    pub(super) fn shared(
+        restriction: Option<HashMap<usize, MemoryState<A>>>,
+    ) -> Rc<RefCell<Trace<A>>> {
+        Rc::new(RefCell::new(Trace {
+            states: HashMap::new(),
+            restriction,
+        }))
+    }
+}
+
+/// The integer of `a` that is closest to 0 for `choice` 0, the lowest one for 1 and the highest
+/// one otherwise, limited to the integers that floats represent exactly. 0 if `a` has no
+/// integer.
+fn candidate<A: AbstractProperties<A>>(a: &A, choice: usize) -> f64 {
+    const MAX: f64 = 9007199254740991.0; // 2^53 - 1
+    let x = || Var::Var("candidate".to_string());
+    let a = A::refine(a, &BExpr::GE(x(), Const::Const(-MAX)));
+    let a = A::refine(&a, &BExpr::LE(x(), Const::Const(MAX)));
+    match (integer_bounds(&a), choice) {
+        (Some((lo, hi)), 0) => 0.clamp(lo, hi) as f64,
+        (Some((lo, _)), 1) => lo as f64,
+        (Some((_, hi)), _) => hi as f64,
+        (None, _) => 0.0,
+    }
+}
+
+/// Assignments of the values of `initial` to the variables of `c` that may be read before they
+/// are initialized, except for `params`.
+fn initialize(c: &Command, params: &[Var], initial: &impl Fn(&str) -> f64) -> Command {
+    let idents: BTreeSet<_> = uninitialized_reads(c)
+        .into_iter()
+        .map(|(_, ident)| ident)
+        .filter(|ident| !params.contains(&var(ident)))
+        .collect();
+    idents.iter().fold(Command::Skip, |c, ident| {
+        let value = SExpr::CExpr(Const::Const(initial(ident)));
+        seq(c, Command::Assign(var(ident), value))
+    })
+}
+
+/// `c` with every input replaced by an assignment of the value of `input`, clamped to the range
+/// of the input, and every procedure body preceded by the initialization of its variables.
+fn concretize(
+    c: &Command,
+    input: &impl Fn(&str) -> f64,
+    initial: &impl Fn(&str) -> f64,
+) -> Command {
+    let go = |c: &Command| Box::new(concretize(c, input, initial));
+    let assign =
+        |ident: &str, value: f64| Command::Assign(var(ident), SExpr::CExpr(Const::Const(value)));
+    match c {
+        Command::Input(Var::Var(ident)) => assign(ident, input(ident)),
+        Command::InputRange(Var::Var(ident), Const::Const(lo), Const::Const(hi)) => {
+            assign(ident, input(ident).clamp(lo.ceil(), hi.floor()))
+        }
+        Command::Seq(c1, c2) => Command::Seq(go(c1), go(c2)),
+        Command::Choose(c1, c2) => Command::Choose(go(c1), go(c2)),
+        Command::If(bexpr, c1, c2) => Command::If(bexpr.clone(), go(c1), go(c2)),
+        Command::While(bexpr, c) => Command::While(bexpr.clone(), go(c)),
+        Command::DoWhile(c, bexpr) => Command::DoWhile(go(c), bexpr.clone()),
+        Command::For(init, bexpr, step, c) => {
+            Command::For(go(init), bexpr.clone(), go(step), go(c))
+        }
+        Command::Partition(partitioning, c) => Command::Partition(*partitioning, go(c)),
+        Command::Located(offset, c) => Command::Located(*offset, go(c)),
+        Command::Proc(procedure) => Command::Proc(Procedure {
+            body: Box::new(seq(
+                initialize(&procedure.body, &procedure.params, initial),
+                concretize(&procedure.body, input, initial),
+            )),
+            ..procedure.clone()
+        }),
+        c => c.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::command_parser::{parse, parse_guard};
+    use crate::interpreter::Alarm;
+
+    #[test]
+    fn spurious_alarm_of_non_relational_analysis() {
+        let program = "input(x); y := x + 1; if (x >= 0) {assert(y > 0)}";
+        let command = parse(program);
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let alarms = m.analyze_command(&command, &Params::no_widening()).alarms();
+        assert_eq!(vec![Alarm::Assertion(35, parse_guard("y > 0"))], alarms);
+
+        let verdict = MemoryState::<IntervalAbstraction>::refine_assertion(
+            &command,
+            35,
+            &parse_guard("y > 0"),
+            &Params::no_widening(),
+        );
+        assert_eq!(Verdict::Spurious, verdict);
+    }
+
+    #[test]
+    fn definite_alarms_have_a_witness() {
+        let program = "input(x); if (x > 5) {assert(x < 3)}; assert(x > 0); var y: u8; input(y, 3, 7); while (y < 10) {y := y + 2}; assert(y != 11)";
+        let command = parse(program);
+
+        let refine = |offset, guard| {
+            MemoryState::<IntervalAbstraction>::refine_assertion(
+                &command,
+                offset,
+                &parse_guard(guard),
+                &Params::with_widening(),
+            )
+        };
+        assert_eq!(Verdict::Definite, refine(22, "x < 3"));
+        assert_eq!(Verdict::Definite, refine(38, "x > 0"));
+        assert_eq!(Verdict::Definite, refine(109, "y != 11"));
+    }
+
+    #[test]
+    fn unreachable_violation_is_not_definite() {
+        // `y` is 0, which the intervals lose, so no execution reaches the assertion
+        let program = "input(x); y := x - x; if (y > 0) {assert(y < 0)}";
+        let command = parse(program);
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let alarms = m.analyze_command(&command, &Params::no_widening()).alarms();
+        assert_eq!(vec![Alarm::Assertion(34, parse_guard("y < 0"))], alarms);
+
+        let verdict = MemoryState::<IntervalAbstraction>::refine_assertion(
+            &command,
+            35,
+            &parse_guard("y < 0"),
+            &Params::no_widening(),
+        );
+        assert_eq!(Verdict::Spurious, verdict);
+    }
+
+    #[test]
+    fn nonterminating_execution_is_no_witness() {
+        // `x` stays even, so the loop never terminates
+        let program = "x := 0; y := 0; while (x != 5) {x := x + 2; y := y + 1}; assert(y < 0)";
+        let command = parse(program);
+
+        let verdict = MemoryState::<IntervalAbstraction>::refine_assertion(
+            &command,
+            57,
+            &parse_guard("y < 0"),
+            &Params::with_widening(),
+        );
+        assert_eq!(Verdict::Unknown, verdict);
+    }
+
+    #[test]
+    fn spurious_alarm_after_widening() {
+        let program = "x := 10; while (x > 0) {x := x - 1}; assert(x >= 0)";
+        let command = parse(program);
+
+        let mut m: MemoryState<IntervalAbstraction> = MemoryState::new();
+        let alarms = m
+            .analyze_command(&command, &Params::with_widening())
+            .alarms();
+        assert_eq!(vec![Alarm::Assertion(37, parse_guard("x >= 0"))], alarms);
+
+        let verdict = MemoryState::<IntervalAbstraction>::refine_assertion(
+            &command,
+            37,
+            &parse_guard("x >= 0"),
+            &Params::with_widening(),
+        );
+        assert_eq!(Verdict::Spurious, verdict);
+    }
+}
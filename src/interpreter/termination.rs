@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+use std::{cmp, ops};
+
+use super::cost::Bound;
+use super::{AbstractProperties, Bottom, MemoryState, Params, Top, Trace};
+use crate::command_parser::{BExpr, Command, Const, SExpr, Type, Var};
+
+/// The offsets of the statements that observe the states at the start and at the end of an
+/// iteration and after the loop that is checked. They lie beyond every offset in the source.
+const START: usize = usize::MAX;
+const END: usize = usize::MAX - 1;
+const EXIT: usize = usize::MAX - 2;
+
+/// Whether a loop terminates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Termination {
+    /// The loop iterates finitely often, e.g. because the ranking function `x` or `-x`
+    /// decreases in every iteration and is bounded from below whenever the loop iterates.
+    Terminates(Option<String>),
+    /// No ranking function was found.
+    MayNotTerminate,
+    /// The loop is reached and no execution leaves it.
+    NonTerminating,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Terminates(Some(ranking)) => {
+                write!(f, "terminates, ranking function {}", ranking)
+            }
+            Termination::Terminates(None) => write!(f, "terminates"),
+            Termination::MayNotTerminate => write!(f, "may not terminate"),
+            Termination::NonTerminating => write!(f, "definitely non-terminating"),
+        }
+    }
+}
+
+impl<
+        A: ops::Add<Output = A>
+            + ops::Neg<Output = A>
+            + ops::Sub<Output = A>
+            + ops::Mul<Output = A>
+            + ops::Div<Output = A>
+            + cmp::PartialEq
+            + cmp::PartialOrd
+            + From<f64>
+            + Copy
+            + AbstractProperties<A>
+            + From<Top>
+            + From<Bottom>
+            + Debug,
+    > MemoryState<A>
+{
+    /// Checks every loop outside of procedure bodies for termination and returns the results by
+    /// the offsets of the loops. Each loop is checked on its own, a loop whose body contains a
+    /// loop that does not terminate terminates if it iterates finitely often.
+    ///
+    /// The candidates for ranking functions are the variables that the loop assigns. The loop is
+    /// analyzed with a hidden variable per candidate that holds its difference since the start
+    /// of the iteration. A candidate `x` is a ranking function if the difference is negative at
+    /// the end of every iteration and `x` is bounded from below at its start, and `-x` if the
+    /// difference is positive and `x` is bounded from above.
+    pub fn termination(c: &Command, params: &Params<A>) -> Vec<(usize, Termination)> {
+        Self::check_loops(c, params)
+            .into_iter()
+            .map(|(offset, termination, _)| (offset, termination))
+            .collect()
+    }
+
+    /// Checks every loop like `termination` and also returns the bound of its number of
+    /// iterations per execution that follows from its ranking function: `x` takes values in
+    /// `[lo, hi]` at the start of the iterations and decreases by at least `step`, so the loop
+    /// iterates at most `(hi - lo) / step + 1` times. If `hi` is unknown but `x` still holds its
+    /// input value `x0` when the loop is entered, the bound is `(x0 - lo) / step + 1`.
+    pub(super) fn check_loops(
+        c: &Command,
+        params: &Params<A>,
+    ) -> Vec<(usize, Termination, Option<Bound>)> {
+        let mut loops = vec![];
+        nested_loops(c, &mut vec![], &mut loops);
+        let mut wrapping = HashSet::new();
+        wrapping_variables(c, &mut wrapping);
+
+        loops
+            .into_iter()
+            .map(|(offset, _)| {
+                let instrumented = instrument(c, offset, &wrapping);
+                let inputs = inputs(c, offset);
+                let (termination, bound) =
+                    Self::loop_termination(&instrumented, offset, &inputs, params);
+                (offset, termination, bound)
+            })
+            .collect()
+    }
+
+    fn loop_termination(
+        c: &Command,
+        offset: usize,
+        inputs: &HashSet<String>,
+        params: &Params<A>,
+    ) -> (Termination, Option<Bound>) {
+        let trace = Trace::shared(None);
+        let mut m = MemoryState::new();
+        m.trace = Some(Rc::clone(&trace));
+        m.analyze_command(c, params);
+        let mut states = std::mem::take(&mut trace.borrow_mut().states);
+        let mut reached = |offset| states.remove(&offset).filter(|m| !m.is_unreachable());
+
+        if reached(offset).is_none() {
+            return (Termination::Terminates(None), Some(Bound::Const(0.0)));
+        }
+        if reached(EXIT).is_none() {
+            return (Termination::NonTerminating, None);
+        }
+        let (start, end) = match (reached(START), reached(END)) {
+            (Some(start), Some(end)) => (start, end),
+            (Some(_), None) => return (Termination::Terminates(None), Some(Bound::Const(1.0))),
+            (None, _) => return (Termination::Terminates(None), Some(Bound::Const(0.0))),
+        };
+        let iterations = |ident: &str, step: Option<f64>, decreases: bool| {
+            let step = step?;
+            match (start.lower_bound(ident), start.upper_bound(ident)) {
+                (Some(lo), Some(hi)) => Some(Bound::Const(((hi - lo) / step).floor() + 1.0)),
+                (Some(lo), None) if decreases && inputs.contains(ident) => {
+                    Some(Bound::from_input(ident, lo, step, decreases))
+                }
+                (None, Some(hi)) if !decreases && inputs.contains(ident) => {
+                    Some(Bound::from_input(ident, hi, step, decreases))
+                }
+                _ => None,
+            }
+        };
+
+        let never = |m: &MemoryState<A>, bexpr: BExpr| m.clone().filter(&bexpr).is_unreachable();
+        let guard = |ident: &str, bexpr: fn(Var, Const) -> BExpr, bound: f64| {
+            bexpr(var(ident), Const::Const(bound))
+        };
+        for (ident, _) in start.env.iter().collect::<BTreeMap<_, _>>() {
+            if end.env.get(&delta(ident)).is_none() {
+                continue;
+            }
+            let delta = delta(ident);
+            if never(&end, guard(&delta, BExpr::GE, -f64::MIN_POSITIVE))
+                && never(&start, guard(ident, BExpr::LE, f64::MIN))
+            {
+                let step = end.upper_bound(&delta).map(|step| -step);
+                return (
+                    Termination::Terminates(Some(ident.to_string())),
+                    iterations(ident, step, true),
+                );
+            }
+            if never(&end, guard(&delta, BExpr::LE, f64::MIN_POSITIVE))
+                && never(&start, guard(ident, BExpr::GE, f64::MAX))
+            {
+                let step = end.lower_bound(&delta);
+                return (
+                    Termination::Terminates(Some(format!("-{}", ident))),
+                    iterations(ident, step, false),
+                );
+            }
+        }
+
+        (Termination::MayNotTerminate, None)
+    }
+}
+
+/// The hidden variable that holds the difference of `ident` since the start of the iteration.
+fn delta(ident: &str) -> String {
+    format!("Δ{}", ident)
+}
+
+fn probe(offset: usize) -> Command {
+    Command::Located(offset, Box::new(Command::Skip))
+}
+
+pub(super) fn seq(c1: Command, c2: Command) -> Command {
+    Command::Seq(Box::new(c1), Box::new(c2))
+}
+
+pub(super) fn var(ident: &str) -> Var {
+    Var::Var(ident.to_string())
+}
+
+/// Collects the offsets of the loops outside of procedure bodies, each with the offsets of the
+/// loops that enclose it.
+pub(super) fn nested_loops(
+    c: &Command,
+    enclosing: &mut Vec<usize>,
+    loops: &mut Vec<(usize, Vec<usize>)>,
+) {
+    match c {
+        Command::Located(offset, c)
+            if matches!(
+                **c,
+                Command::While(..) | Command::For(..) | Command::DoWhile(..)
+            ) =>
+        {
+            loops.push((*offset, enclosing.clone()));
+            enclosing.push(*offset);
+            nested_loops(c, enclosing, loops);
+            enclosing.pop();
+        }
+        Command::Seq(c1, c2)
+        | Command::If(_, c1, c2)
+        | Command::Choose(c1, c2)
+        | Command::For(_, _, c1, c2) => {
+            nested_loops(c1, enclosing, loops);
+            nested_loops(c2, enclosing, loops);
+        }
+        Command::While(_, c)
+        | Command::DoWhile(c, _)
+        | Command::Partition(_, c)
+        | Command::Located(_, c) => nested_loops(c, enclosing, loops),
+        _ => (),
+    }
+}
+
+/// The variables that still hold their input values when the loop at `offset` is entered: they
+/// are read by `input` on the top level of `c` and not assigned before the loop. Empty if the
+/// loop is not on the top level.
+fn inputs(c: &Command, offset: usize) -> HashSet<String> {
+    let mut inputs = HashSet::new();
+    for c in top_level(c) {
+        match c {
+            Command::Located(o, _) if *o == offset => return inputs,
+            Command::Input(Var::Var(ident)) | Command::InputRange(Var::Var(ident), ..) => {
+                inputs.insert(ident.clone());
+            }
+            c => {
+                let mut vars = BTreeSet::new();
+                assigned(c, &mut vars);
+                inputs.retain(|ident| !vars.contains(ident));
+            }
+        }
+    }
+    HashSet::new()
+}
+
+/// The statements of the top level of `c` in order, where loops keep their locations.
+fn top_level(c: &Command) -> Vec<&Command> {
+    match c {
+        Command::Seq(c1, c2) => [top_level(c1), top_level(c2)].concat(),
+        Command::Located(_, c)
+            if !matches!(
+                **c,
+                Command::While(..) | Command::For(..) | Command::DoWhile(..)
+            ) =>
+        {
+            top_level(c)
+        }
+        c => vec![c],
+    }
+}
+
+/// Collects the variables that are declared with a wrapping machine type, whose differences
+/// are unknown because of the wrap-around.
+fn wrapping_variables(c: &Command, vars: &mut HashSet<String>) {
+    match c {
+        Command::Declare(Var::Var(ident), Type::Machine(typ)) if typ.wrapping => {
+            vars.insert(ident.clone());
+        }
+        Command::Seq(c1, c2) | Command::If(_, c1, c2) | Command::Choose(c1, c2) => {
+            wrapping_variables(c1, vars);
+            wrapping_variables(c2, vars);
+        }
+        Command::For(init, _, step, c) => {
+            wrapping_variables(init, vars);
+            wrapping_variables(step, vars);
+            wrapping_variables(c, vars);
+        }
+        Command::While(_, c)
+        | Command::DoWhile(c, _)
+        | Command::Partition(_, c)
+        | Command::Located(_, c) => wrapping_variables(c, vars),
+        Command::Proc(procedure) => wrapping_variables(&procedure.body, vars),
+        _ => (),
+    }
+}
+
+/// Collects the variables that `c` assigns outside of procedure bodies.
+fn assigned(c: &Command, vars: &mut BTreeSet<String>) {
+    match c {
+        Command::Assign(Var::Var(ident), _)
+        | Command::Input(Var::Var(ident))
+        | Command::InputRange(Var::Var(ident), ..)
+        | Command::Declare(Var::Var(ident), _)
+        | Command::Call(Var::Var(ident), ..) => {
+            vars.insert(ident.clone());
+        }
+        Command::Seq(c1, c2)
+        | Command::If(_, c1, c2)
+        | Command::Choose(c1, c2)
+        | Command::For(_, _, c1, c2) => {
+            assigned(c1, vars);
+            assigned(c2, vars);
+        }
+        Command::While(_, c)
+        | Command::DoWhile(c, _)
+        | Command::Partition(_, c)
+        | Command::Located(_, c) => assigned(c, vars),
+        _ => (),
+    }
+}
+
+/// Instruments the loop at `offset` in `c`: the differences are set to 0 at the start of every
+/// iteration and updated by every assignment, and the states at the start and the end of the
+/// iterations and after the loop are observed.
+fn instrument(c: &Command, offset: usize, wrapping: &HashSet<String>) -> Command {
+    let recurse = |c: &Command| Box::new(instrument(c, offset, wrapping));
+    match c {
+        Command::Located(o, c) if *o == offset => seq(
+            Command::Located(*o, Box::new(instrument_loop(c, wrapping))),
+            probe(EXIT),
+        ),
+        Command::Located(o, c) => Command::Located(*o, recurse(c)),
+        Command::Seq(c1, c2) => Command::Seq(recurse(c1), recurse(c2)),
+        Command::If(bexpr, c1, c2) => Command::If(bexpr.clone(), recurse(c1), recurse(c2)),
+        Command::Choose(c1, c2) => Command::Choose(recurse(c1), recurse(c2)),
+        Command::While(bexpr, c) => Command::While(bexpr.clone(), recurse(c)),
+        Command::DoWhile(c, bexpr) => Command::DoWhile(recurse(c), bexpr.clone()),
+        Command::For(init, bexpr, step, c) => {
+            Command::For(init.clone(), bexpr.clone(), recurse(step), recurse(c))
+        }
+        Command::Partition(partitioning, c) => Command::Partition(*partitioning, recurse(c)),
+        c => c.clone(),
+    }
+}
+
+fn instrument_loop(c: &Command, wrapping: &HashSet<String>) -> Command {
+    let mut vars = BTreeSet::new();
+    assigned(c, &mut vars);
+    let prologue = vars
+        .iter()
+        .map(|ident| Command::Assign(var(&delta(ident)), SExpr::CExpr(Const::Const(0.0))))
+        .fold(probe(START), |c, reset| seq(reset, c));
+
+    match c {
+        Command::While(bexpr, body) => Command::While(
+            bexpr.clone(),
+            Box::new(seq(
+                prologue,
+                seq(differences(body, wrapping, true), probe(END)),
+            )),
+        ),
+        Command::DoWhile(body, bexpr) => Command::DoWhile(
+            Box::new(seq(
+                prologue,
+                seq(differences(body, wrapping, true), probe(END)),
+            )),
+            bexpr.clone(),
+        ),
+        Command::For(init, bexpr, step, body) => Command::For(
+            init.clone(),
+            bexpr.clone(),
+            Box::new(seq(differences(step, wrapping, false), probe(END))),
+            Box::new(seq(prologue, differences(body, wrapping, false))),
+        ),
+        c => c.clone(),
+    }
+}
+
+/// Updates the differences of the variables that `c` assigns. If `continues` is set, the states
+/// that leave the iteration by `continue` are observed as ends of the iteration.
+fn differences(c: &Command, wrapping: &HashSet<String>, continues: bool) -> Command {
+    let recurse = |c: &Command, continues| Box::new(differences(c, wrapping, continues));
+    let unknown = |ident: &str| Command::Input(var(&delta(ident)));
+    match c {
+        Command::Assign(Var::Var(ident), sexpr) => {
+            let update = match sexpr {
+                _ if wrapping.contains(ident) => unknown(ident),
+                SExpr::VExpr(Var::Var(x)) if x == ident => Command::Skip,
+                SExpr::Add(e1, e2) | SExpr::Add(e2, e1)
+                    if **e1 == SExpr::VExpr(Var::Var(ident.clone())) =>
+                {
+                    let d = SExpr::VExpr(var(&delta(ident)));
+                    Command::Assign(var(&delta(ident)), SExpr::Add(Box::new(d), e2.clone()))
+                }
+                SExpr::Sub(e1, e2) if **e1 == SExpr::VExpr(Var::Var(ident.clone())) => {
+                    let d = SExpr::VExpr(var(&delta(ident)));
+                    Command::Assign(var(&delta(ident)), SExpr::Sub(Box::new(d), e2.clone()))
+                }
+                _ => unknown(ident),
+            };
+            seq(update, c.clone())
+        }
+        Command::Input(Var::Var(ident))
+        | Command::InputRange(Var::Var(ident), ..)
+        | Command::Declare(Var::Var(ident), _)
+        | Command::Call(Var::Var(ident), ..) => seq(c.clone(), unknown(ident)),
+        Command::Continue if continues => seq(probe(END), Command::Continue),
+        Command::Located(o, c) => Command::Located(*o, recurse(c, continues)),
+        Command::Seq(c1, c2) => Command::Seq(recurse(c1, continues), recurse(c2, continues)),
+        Command::If(bexpr, c1, c2) => Command::If(
+            bexpr.clone(),
+            recurse(c1, continues),
+            recurse(c2, continues),
+        ),
+        Command::Choose(c1, c2) => Command::Choose(recurse(c1, continues), recurse(c2, continues)),
+        Command::Partition(partitioning, c) => {
+            Command::Partition(*partitioning, recurse(c, continues))
+        }
+        Command::While(bexpr, c) => Command::While(bexpr.clone(), recurse(c, false)),
+        Command::DoWhile(c, bexpr) => Command::DoWhile(recurse(c, false), bexpr.clone()),
+        Command::For(init, bexpr, step, c) => Command::For(
+            recurse(init, false),
+            bexpr.clone(),
+            recurse(step, false),
+            recurse(c, false),
+        ),
+        c => c.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstractions::interval_abstraction::IntervalAbstraction;
+    use crate::abstractions::sign_abstraction::SignAbstraction;
+    use crate::command_parser::parse;
+
+    #[test]
+    fn figure_3_9_a_does_not_terminate() {
+        let program = "x := 0; while (x >= 0) {x := x + 1}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![(8, Termination::NonTerminating)],
+            MemoryState::<SignAbstraction>::termination(&command, &Params::no_widening())
+        );
+        assert_eq!(
+            vec![(8, Termination::NonTerminating)],
+            MemoryState::<IntervalAbstraction>::termination(&command, &Params::with_widening())
+        );
+        assert_eq!(
+            vec![(8, Termination::NonTerminating)],
+            MemoryState::<IntervalAbstraction>::termination(&command, &Params::no_widening())
+        );
+    }
+
+    #[test]
+    fn ranking_functions() {
+        let program = "x := 10; while (x > 0) {x := x - 1}; i := 0; while (i < 100) {i := i + 2}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                (9, Termination::Terminates(Some("x".to_string()))),
+                (45, Termination::Terminates(Some("-i".to_string())))
+            ],
+            MemoryState::<IntervalAbstraction>::termination(&command, &Params::with_widening())
+        );
+    }
+
+    #[test]
+    fn loops_without_ranking_function() {
+        let program = "input(x); while (x != 0) {x := x - 1}; while (y < 10) {y := y * 2}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                (10, Termination::MayNotTerminate),
+                (39, Termination::MayNotTerminate)
+            ],
+            MemoryState::<IntervalAbstraction>::termination(&command, &Params::with_widening())
+        );
+    }
+
+    #[test]
+    fn for_and_do_while_with_continue() {
+        let program = "s := 0; for (i := 0; i < 10; i := i + 1) {if (i == 3) {continue} else {skip}; s := s + i}; do {s := s - 3; if (s > 5) {continue} else {skip}} while (s > 0)";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                (8, Termination::Terminates(Some("-i".to_string()))),
+                (91, Termination::Terminates(Some("s".to_string())))
+            ],
+            MemoryState::<IntervalAbstraction>::termination(&command, &Params::with_widening())
+        );
+    }
+
+    #[test]
+    fn nested_and_unreachable_loops() {
+        let program = "i := 0; while (i < 10) {j := 0; while (j >= 0) {j := j + 1}; i := i + 1}; if (i < 0) {while (i < 0) {skip}} else {skip}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                (8, Termination::NonTerminating),
+                (32, Termination::NonTerminating),
+                (86, Termination::Terminates(None))
+            ],
+            MemoryState::<IntervalAbstraction>::termination(&command, &Params::with_widening())
+        );
+    }
+}
@@ -1,15 +1,29 @@
 pub mod abstractions;
 pub mod command_parser;
+pub mod dataflow;
 pub mod interpreter;
+pub mod type_checker;
 
+use crate::abstractions::float_interval_abstraction::FloatIntervalAbstraction;
 use crate::abstractions::interval_abstraction::IntervalAbstraction;
+use crate::abstractions::parity_abstraction::ParityAbstraction;
+use crate::abstractions::product_abstraction::{IntervalParityAbstraction, SignParityAbstraction};
 use crate::abstractions::sign_abstraction::SignAbstraction;
-use crate::command_parser::parse;
-use crate::interpreter::MemoryState;
+use crate::abstractions::value_set_abstraction::ValueSetAbstraction;
+use crate::command_parser::{line_and_column, parse, parse_guard, Command};
+use crate::dataflow::{
+    analyze, uninitialized_reads, Analysis, AvailableExpressions, DefiniteInitialization,
+    LiveVariables, ReachingDefinitions, VeryBusyExpressions,
+};
+use crate::interpreter::{
+    AbstractProperties, Alarm, Bottom, MemoryState, Precondition, Target, Top, Verdict,
+};
+use crate::type_checker::check;
 
 use clap::Parser;
 use interpreter::Params;
-use std::fs;
+use std::fmt::Debug;
+use std::{cmp, fs, ops};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -29,7 +43,7 @@ struct Cli {
     #[arg(
         short,
         long,
-        help = "The mode of operation. Options:\n- parse: Only parses the program.\n- interprete: Interprets the program.\n- analyze: Analyzes the program."
+        help = "The mode of operation. Options:\n- parse: Only parses the program.\n- interprete: Interprets the program.\n- analyze: Analyzes the program.\n- precondition: Infers a necessary precondition of the postconditions or of failing assertions by a backward analysis.\n- termination: Checks every loop for termination.\n- cost: Bounds the numbers of loop iterations.\n- dataflow: Runs a classic dataflow analysis and prints its facts before and after every statement."
     )]
     mode: String,
 
@@ -38,17 +52,110 @@ struct Cli {
         long,
         required_if_eq("mode", "interprete"),
         required_if_eq("mode", "analyze"),
-        help = "The abstraction to use. Options:\n- interval: Interval abstraction.\n- sign: Sign abstraction.\n(Only required for 'interprete' or 'analyze' modes.)"
+        required_if_eq("mode", "precondition"),
+        required_if_eq("mode", "termination"),
+        required_if_eq("mode", "cost"),
+        help = "The abstraction to use. Options:\n- interval: Interval abstraction.\n- float: Interval abstraction of IEEE-754 floats with outward rounding, infinities and NaN.\n- sign: Sign abstraction.\n- valueset: Finite value-set abstraction.\n- parity: Parity abstraction.\n- interval-parity: Reduced product of the interval and the parity abstraction.\n- sign-parity: Reduced product of the sign and the parity abstraction.\n(Only required for 'interprete', 'analyze', 'precondition', 'termination' or 'cost' modes.)"
     )]
     abstraction: Option<String>,
+
+    #[arg(
+        long,
+        required_if_eq("mode", "dataflow"),
+        help = "The analysis of the 'dataflow' mode. Options:\n- liveness: Live variables.\n- reaching-definitions: Reaching definitions.\n- available-expressions: Available expressions.\n- very-busy-expressions: Very busy expressions.\n- definite-initialization: Variables that are initialized on every path."
+    )]
+    analysis: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Number of loop unrollings before the fixpoint iteration of a while loop."
+    )]
+    loop_unrollings: u8,
+
+    #[arg(long, help = "Use widening to accelerate the fixpoint iteration.")]
+    widening: bool,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Number of joins before widening is applied."
+    )]
+    widening_delays: u8,
+
+    #[arg(
+        long,
+        help = "Keep the states of both branches of every if separated until the end of the enclosing block."
+    )]
+    partition_branches: bool,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Keep the states of the first k iterations of every while loop separated until the end of the enclosing block."
+    )]
+    partition_iterations: u8,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Analyze procedures separately for the last k call sites (k-CFA), 0 gives context-insensitive summaries."
+    )]
+    call_strings: u8,
+
+    #[arg(
+        long,
+        help = "Split arrays into segments with symbolic bounds instead of one abstract value for all cells."
+    )]
+    array_segmentation: bool,
+
+    #[arg(
+        long,
+        help = "A guard like 'x >= 10' that holds at the end of the program, whose precondition is inferred in the 'precondition' mode. May be given more than once, without it the precondition of failing assertions is inferred."
+    )]
+    postcondition: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Refine the alarms of assertions in the 'analyze' mode by alternating forward and backward analyses, and report whether they are spurious, definite or unknown."
+    )]
+    refine_alarms: bool,
+
+    #[arg(
+        long,
+        help = "Fail with exit code 1 if the 'analyze' mode finds unreachable branches, unreachable loop bodies or guards that are always true or false."
+    )]
+    deny_dead_code: bool,
+
+    #[arg(
+        long,
+        help = "Treat reads of variables that may not be initialized as errors in the 'analyze' mode, which raise an alarm and stop the executions that read them. Otherwise a variable that is initialized on no path reads Top and one that is initialized on some paths reads the values of these paths."
+    )]
+    strict_initialization: bool,
+}
+
+impl Cli {
+    fn params<A: PartialOrd + AbstractProperties<A> + From<Top>>(&self) -> Params<A> {
+        Params {
+            loop_unrollings: self.loop_unrollings,
+            use_widening: self.widening,
+            widening_delays: self.widening_delays,
+            widening_treshold: A::top().into(),
+            partition_branches: self.partition_branches,
+            partition_iterations: self.partition_iterations,
+            call_string_length: self.call_strings,
+            array_segmentation: self.array_segmentation,
+            strict_initialization: self.strict_initialization,
+        }
+    }
 }
 
 fn main() {
     let args = Cli::parse();
 
-    let program_path = args.program;
-    let mode = args.mode;
-    let abstraction = args.abstraction;
+    let program_path = &args.program;
+    let mode = &args.mode;
+    let abstraction = &args.abstraction;
 
     let src = fs::read_to_string(program_path).unwrap_or_else(|err| {
         eprintln!("Error reading file: {}", err);
@@ -61,59 +168,214 @@ fn main() {
             let c = parse(&src);
             println!("Parse result: {:?}", c);
         }
-        "interprete" => {
-            println!("Interpreting the program...");
+        "dataflow" => {
             let c = parse(&src);
-
-            match abstraction {
-                Some(abstraction) => match abstraction.as_str() {
-                    "interval" => {
-                        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
-                        let post = pre.interprete_command(&c);
-                        println!("Interpretation result: {:?}", post);
-                    }
-                    "sign" => {
-                        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
-                        let post = pre.interprete_command(&c);
-                        println!("Interpretation result: {:?}", post);
-                    }
-                    _ => {
-                        eprintln!("Invalid abstraction specified. Use 'sign' or 'interval'.");
-                        std::process::exit(1);
-                    }
-                },
-                None => (),
+            check_or_exit(&src, &c);
+            match args.analysis.as_deref() {
+                Some("liveness") => print_dataflow(&src, &c, &LiveVariables, |ident| ident.clone()),
+                Some("reaching-definitions") => {
+                    print_dataflow(&src, &c, &ReachingDefinitions, |definition| {
+                        let (line, column) = line_and_column(&src, definition.offset);
+                        format!("{}@{}:{}", definition.ident, line, column)
+                    })
+                }
+                Some("available-expressions") => {
+                    print_dataflow(&src, &c, &AvailableExpressions, |expr| expr.to_string())
+                }
+                Some("very-busy-expressions") => {
+                    print_dataflow(&src, &c, &VeryBusyExpressions, |expr| expr.to_string())
+                }
+                Some("definite-initialization") => {
+                    print_dataflow(&src, &c, &DefiniteInitialization, |ident| ident.clone())
+                }
+                _ => {
+                    eprintln!("Invalid analysis specified. Use 'liveness', 'reaching-definitions', 'available-expressions', 'very-busy-expressions' or 'definite-initialization'.");
+                    std::process::exit(1);
+                }
             }
         }
-        "analyze" => {
-            println!("Analyzing the program...");
+        "interprete" | "analyze" | "precondition" | "termination" | "cost" => {
+            match mode.as_str() {
+                "interprete" => println!("Interpreting the program..."),
+                "analyze" => println!("Analyzing the program..."),
+                "precondition" => println!("Inferring a precondition..."),
+                "termination" => println!("Checking the termination of loops..."),
+                _ => println!("Bounding the loop iterations..."),
+            }
             let c = parse(&src);
+            check_or_exit(&src, &c);
 
-            match abstraction {
-                Some(abstraction) => match abstraction.as_str() {
-                    "interval" => {
-                        let mut pre: MemoryState<IntervalAbstraction> = MemoryState::new();
-                        let post = pre.analyze_command(&c, &Params::no_widening());
-                        println!("Interpretation result: {:?}", post);
-                    }
-                    "sign" => {
-                        let mut pre: MemoryState<SignAbstraction> = MemoryState::new();
-                        let post = pre.analyze_command(&c, &Params::no_widening());
-                        println!("Interpretation result: {:?}", post);
-                    }
-                    _ => {
-                        eprintln!("Invalid abstraction specified. Use 'sign' or 'interval'.");
-                        std::process::exit(1);
-                    }
-                },
-                None => (),
+            match abstraction.as_deref() {
+                Some("interval") => run::<IntervalAbstraction>(mode, &src, &c, &args),
+                Some("float") => run::<FloatIntervalAbstraction>(mode, &src, &c, &args),
+                Some("sign") => run::<SignAbstraction>(mode, &src, &c, &args),
+                Some("valueset") => run::<ValueSetAbstraction>(mode, &src, &c, &args),
+                Some("parity") => run::<ParityAbstraction>(mode, &src, &c, &args),
+                Some("interval-parity") => run::<IntervalParityAbstraction>(mode, &src, &c, &args),
+                Some("sign-parity") => run::<SignParityAbstraction>(mode, &src, &c, &args),
+                _ => {
+                    eprintln!("Invalid abstraction specified. Use 'sign', 'interval', 'float', 'valueset', 'parity', 'interval-parity' or 'sign-parity'.");
+                    std::process::exit(1);
+                }
             }
         }
         _ => {
-            eprintln!("Invalid mode specified. Use 'parse', 'interprete', or 'analyze'.");
+            eprintln!(
+                "Invalid mode specified. Use 'parse', 'interprete', 'analyze', 'precondition', 'termination', 'cost' or 'dataflow'."
+            );
             std::process::exit(1);
         }
     }
+}
+
+/// Prints the type errors of `c` and exits if there are any.
+fn check_or_exit(src: &str, c: &Command) {
+    let errors = check(c);
+    if !errors.is_empty() {
+        for error in errors {
+            let (line, column) = line_and_column(src, error.offset());
+            eprintln!("Type error at {}:{}: {}", line, column, error);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run<
+    A: ops::Add<Output = A>
+        + ops::Neg<Output = A>
+        + ops::Sub<Output = A>
+        + ops::Mul<Output = A>
+        + ops::Div<Output = A>
+        + cmp::PartialEq
+        + cmp::PartialOrd
+        + From<f64>
+        + Copy
+        + AbstractProperties<A>
+        + From<Top>
+        + From<Bottom>
+        + Debug,
+>(
+    mode: &str,
+    src: &str,
+    c: &Command,
+    args: &Cli,
+) {
+    if mode == "precondition" {
+        let target = match args.postcondition.is_empty() {
+            true => Target::Failure(None),
+            false => Target::Post(args.postcondition.iter().map(|g| parse_guard(g)).collect()),
+        };
+        print_precondition(&MemoryState::<A>::precondition(c, &target, &args.params()));
+        return;
+    }
+    if mode == "termination" {
+        for (offset, termination) in MemoryState::<A>::termination(c, &args.params()) {
+            let (line, column) = line_and_column(src, offset);
+            println!("Loop at {}:{}: {}", line, column, termination);
+        }
+        return;
+    }
+    if mode == "cost" {
+        let cost = MemoryState::<A>::cost(c, &args.params());
+        for (offset, bound) in cost.loops {
+            let (line, column) = line_and_column(src, offset);
+            match bound {
+                Some(bound) => {
+                    println!("Loop at {}:{}: at most {} iterations", line, column, bound)
+                }
+                None => println!("Loop at {}:{}: no bound found", line, column),
+            }
+        }
+        match cost.total {
+            Some(total) => println!("Total: at most {} loop iterations", total),
+            None => println!("Total: no bound found"),
+        }
+        return;
+    }
 
-    todo!("read params from cli");
+    let mut pre: MemoryState<A> = MemoryState::new();
+    let post = match mode {
+        "interprete" => pre.interprete_command(c),
+        _ => pre.analyze_command(c, &args.params()),
+    };
+    println!("Interpretation result: {:?}", post);
+
+    for summary in post.procedure_summaries() {
+        println!(
+            "Summary of {}: {:?} -> {:?}",
+            summary.name, summary.params, summary.output
+        );
+    }
+
+    for alarm in post.alarms() {
+        let message = match &alarm {
+            Alarm::Assertion(offset, bexpr) if mode == "analyze" && args.refine_alarms => {
+                match MemoryState::<A>::refine_assertion(c, *offset, bexpr, &args.params()) {
+                    Verdict::Definite => format!("assertion {:?} fails (definite)", bexpr),
+                    verdict => format!("{} ({})", alarm, verdict),
+                }
+            }
+            _ => alarm.to_string(),
+        };
+        let (line, column) = line_and_column(src, alarm.offset());
+        println!("Alarm at {}:{}: {}", line, column, message);
+    }
+
+    // with strict initialization the reads are reported as alarms
+    if mode == "analyze" && !args.strict_initialization {
+        for (offset, ident) in uninitialized_reads(c) {
+            let (line, column) = line_and_column(src, offset);
+            println!(
+                "Uninitialized read at {}:{}: {} may be read before it is initialized",
+                line, column, ident
+            );
+        }
+    }
+
+    let dead_code = post.dead_code();
+    for dead_code in &dead_code {
+        let (line, column) = line_and_column(src, dead_code.offset);
+        println!("Dead code at {}:{}: {}", line, column, dead_code);
+    }
+    if args.deny_dead_code && !dead_code.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn print_precondition<A: Debug>(precondition: &Precondition<A>) {
+    if !precondition.reachable {
+        println!("Precondition: false, no execution reaches the target");
+        return;
+    }
+    if precondition.initial.is_empty() && precondition.inputs.is_empty() {
+        println!("Precondition: true");
+    }
+    for (ident, a) in &precondition.initial {
+        println!("Precondition on {}: {:?}", ident, a);
+    }
+    for (ident, a) in &precondition.inputs {
+        println!("Precondition on input({}): {:?}", ident, a);
+    }
+}
+
+fn print_dataflow<A: Analysis>(
+    src: &str,
+    c: &Command,
+    analysis: &A,
+    show: impl Fn(&A::Fact) -> String,
+) {
+    let show_all = |facts: &std::collections::BTreeSet<A::Fact>| {
+        let facts: Vec<_> = facts.iter().map(&show).collect();
+        format!("{{{}}}", facts.join(", "))
+    };
+    for (offset, facts) in analyze(analysis, c) {
+        let (line, column) = line_and_column(src, offset);
+        println!(
+            "Statement at {}:{}: before {}, after {}",
+            line,
+            column,
+            show_all(&facts.before),
+            show_all(&facts.after)
+        );
+    }
 }
@@ -0,0 +1,605 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::command_parser::{BExpr, Command, Const, SExpr, Type, Var};
+
+/// A type error and the offset in the source of the statement in which it occurs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+    /// A variable of the declared type is assigned or compared with a value of another type.
+    Mismatch(usize, String, Type),
+    /// A boolean is used as a number, e.g. in arithmetic or as an index.
+    BoolAsNumber(usize, SExpr),
+    /// A boolean is assigned to a variable that is not declared.
+    UndeclaredBool(usize, String),
+    /// A variable is declared twice in the same scope.
+    Redeclared(usize, String),
+    /// An input range contains no integer, e.g. because its bounds are in the wrong order.
+    EmptyRange(usize, String),
+    /// A procedure is called that is not defined anywhere in the program.
+    UndefinedProcedure(usize, String),
+    /// A procedure is called, directly or by the procedures that the call runs, before it is
+    /// defined on every path to the call.
+    CalledBeforeDefinition(usize, String),
+    /// A procedure is defined twice.
+    Redefined(usize, String),
+    /// A procedure is called with the wrong number of arguments, the expected one and the given
+    /// one.
+    Arity(usize, String, usize, usize),
+    /// An array is accessed that is not declared before in its scope.
+    UndeclaredArray(usize, String),
+    /// A `break` or `continue` is not inside a loop of its scope.
+    OutsideLoop(usize, String),
+}
+
+impl TypeError {
+    pub fn offset(&self) -> usize {
+        match self {
+            TypeError::Mismatch(offset, _, _)
+            | TypeError::BoolAsNumber(offset, _)
+            | TypeError::UndeclaredBool(offset, _)
+            | TypeError::Redeclared(offset, _)
+            | TypeError::EmptyRange(offset, _)
+            | TypeError::UndefinedProcedure(offset, _)
+            | TypeError::CalledBeforeDefinition(offset, _)
+            | TypeError::Redefined(offset, _)
+            | TypeError::Arity(offset, _, _, _)
+            | TypeError::UndeclaredArray(offset, _)
+            | TypeError::OutsideLoop(offset, _) => *offset,
+        }
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeError::Mismatch(_, ident, typ) => {
+                write!(f, "{} of type {} gets a value of another type", ident, typ)
+            }
+            TypeError::BoolAsNumber(_, sexpr) => write!(f, "boolean {:?} used as a number", sexpr),
+            TypeError::UndeclaredBool(_, ident) => {
+                write!(f, "boolean assigned to {}, which is not declared", ident)
+            }
+            TypeError::Redeclared(_, ident) => write!(f, "{} is declared twice", ident),
+            TypeError::EmptyRange(_, ident) => {
+                write!(f, "input range of {} contains no integer", ident)
+            }
+            TypeError::UndefinedProcedure(_, name) => {
+                write!(f, "call of undefined procedure {}", name)
+            }
+            TypeError::CalledBeforeDefinition(_, name) => {
+                write!(f, "procedure {} is called before it is defined", name)
+            }
+            TypeError::Redefined(_, name) => write!(f, "procedure {} is defined twice", name),
+            TypeError::Arity(_, name, expected, given) => write!(
+                f,
+                "procedure {} expects {} arguments, but got {}",
+                name, expected, given
+            ),
+            TypeError::UndeclaredArray(_, ident) => {
+                write!(f, "access of undeclared array {}", ident)
+            }
+            TypeError::OutsideLoop(_, keyword) => write!(f, "{} outside of a loop", keyword),
+        }
+    }
+}
+
+/// Checks the declared types of the variables in `command`. Variables without a declaration are
+/// numbers, which may be integers or floats. Procedure bodies are checked in their own scope.
+/// Also checks the calls of procedures, the accesses of arrays, the jumps out of loops and the
+/// input ranges, on which the interpreter would fail.
+pub fn check(command: &Command) -> Vec<TypeError> {
+    let mut procedures = HashMap::new();
+    collect_procedures(command, &mut procedures);
+    let mut checker = Checker {
+        procedures: Rc::new(procedures),
+        defined: Some(HashSet::new()),
+        ..Checker::default()
+    };
+    checker.command(command);
+    checker.errors
+}
+
+/// The number of parameters and the called procedures of every procedure defined in `command`.
+/// A procedure body may call a procedure that is defined after it in the source, as long as
+/// both are defined when the body runs.
+fn collect_procedures(command: &Command, procedures: &mut HashMap<String, (usize, Vec<String>)>) {
+    match command {
+        Command::Proc(procedure) => {
+            let mut callees = vec![];
+            collect_calls(&procedure.body, &mut callees);
+            procedures.insert(procedure.name.clone(), (procedure.params.len(), callees));
+        }
+        Command::Seq(c1, c2) | Command::Choose(c1, c2) | Command::If(_, c1, c2) => {
+            collect_procedures(c1, procedures);
+            collect_procedures(c2, procedures);
+        }
+        Command::While(_, c)
+        | Command::DoWhile(c, _)
+        | Command::For(_, _, _, c)
+        | Command::Partition(_, c)
+        | Command::Located(_, c) => collect_procedures(c, procedures),
+        _ => (),
+    }
+}
+
+/// The procedures that `command` calls outside of the procedures that it defines.
+fn collect_calls(command: &Command, calls: &mut Vec<String>) {
+    match command {
+        Command::Call(_, name, _, _) => calls.push(name.clone()),
+        Command::Seq(c1, c2) | Command::Choose(c1, c2) | Command::If(_, c1, c2) => {
+            collect_calls(c1, calls);
+            collect_calls(c2, calls);
+        }
+        Command::For(init, _, step, c) => {
+            collect_calls(init, calls);
+            collect_calls(step, calls);
+            collect_calls(c, calls);
+        }
+        Command::While(_, c)
+        | Command::DoWhile(c, _)
+        | Command::Partition(_, c)
+        | Command::Located(_, c) => collect_calls(c, calls),
+        _ => (),
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    types: HashMap<String, Type>,
+    arrays: HashSet<String>,
+    procedures: Rc<HashMap<String, (usize, Vec<String>)>>,
+    /// The procedures that are defined on every path to the current statement, `None` in
+    /// procedure bodies, whose calls are checked where the procedure is called.
+    defined: Option<HashSet<String>>,
+    /// The procedures whose definitions were checked.
+    definitions: HashSet<String>,
+    /// The number of loops around the current statement.
+    loops: usize,
+    /// The offset of the innermost located statement.
+    offset: usize,
+    errors: Vec<TypeError>,
+}
+
+impl Checker {
+    fn command(&mut self, c: &Command) {
+        match c {
+            Command::Skip | Command::Input(_) => (),
+            Command::Break | Command::Continue => {
+                if self.loops == 0 {
+                    let keyword = match c {
+                        Command::Break => "break",
+                        _ => "continue",
+                    };
+                    self.error(TypeError::OutsideLoop(self.offset, keyword.to_string()));
+                }
+            }
+            Command::InputRange(Var::Var(ident), Const::Const(lo), Const::Const(hi)) => {
+                // inputs are integers
+                if lo.ceil() > hi.floor() {
+                    self.error(TypeError::EmptyRange(self.offset, ident.clone()));
+                }
+            }
+            Command::Seq(c1, c2) => {
+                self.command(c1);
+                self.command(c2);
+            }
+            Command::Choose(c1, c2) => self.branches(c1, c2),
+            Command::Assign(Var::Var(ident), sexpr) => {
+                let typ = self.sexpr(sexpr);
+                self.assign(ident, typ);
+            }
+            Command::Declare(Var::Var(ident), typ) => {
+                if self.types.insert(ident.clone(), *typ).is_some() {
+                    self.error(TypeError::Redeclared(self.offset, ident.clone()));
+                }
+            }
+            Command::Array(Var::Var(ident), length) => {
+                self.number(length);
+                self.arrays.insert(ident.clone());
+            }
+            Command::ArrayAssign(Var::Var(ident), index, value) => {
+                self.array(ident);
+                self.number(index);
+                self.number(value);
+            }
+            Command::If(bexpr, c1, c2) => {
+                self.bexpr(bexpr);
+                self.branches(c1, c2);
+            }
+            Command::While(bexpr, c) => {
+                self.bexpr(bexpr);
+                // the body may not run
+                let defined = self.defined.clone();
+                self.body(c);
+                self.defined = defined;
+            }
+            Command::DoWhile(c, bexpr) => {
+                self.bexpr(bexpr);
+                self.body(c);
+            }
+            Command::For(init, bexpr, step, c) => {
+                self.command(init);
+                self.bexpr(bexpr);
+                let defined = self.defined.clone();
+                self.body(c);
+                self.command(step);
+                self.defined = defined;
+            }
+            Command::Assert(bexpr) => self.bexpr(bexpr),
+            Command::Partition(_, c) => self.command(c),
+            Command::Proc(procedure) => {
+                if !self.definitions.insert(procedure.name.clone()) {
+                    self.error(TypeError::Redefined(self.offset, procedure.name.clone()));
+                }
+                if let Some(defined) = &mut self.defined {
+                    defined.insert(procedure.name.clone());
+                }
+                let mut checker = Checker {
+                    procedures: Rc::clone(&self.procedures),
+                    defined: None,
+                    offset: self.offset,
+                    ..Checker::default()
+                };
+                checker.command(&procedure.body);
+                checker.number(&procedure.ret);
+                self.errors.append(&mut checker.errors);
+            }
+            Command::Call(Var::Var(ident), name, args, _) => {
+                match self.procedures.get(name) {
+                    None => self.error(TypeError::UndefinedProcedure(self.offset, name.clone())),
+                    Some(&(params, _)) if params != args.len() => self.error(TypeError::Arity(
+                        self.offset,
+                        name.clone(),
+                        params,
+                        args.len(),
+                    )),
+                    Some(_) => (),
+                }
+                self.called(name);
+                for arg in args {
+                    self.number(arg);
+                }
+                self.assign(ident, None);
+            }
+            Command::Located(offset, c) => {
+                let outer = self.offset;
+                self.offset = *offset;
+                self.command(c);
+                self.offset = outer;
+            }
+        }
+    }
+
+    /// Checks two alternatives, after which the procedures are defined that both define.
+    fn branches(&mut self, c1: &Command, c2: &Command) {
+        let defined = self.defined.clone();
+        self.command(c1);
+        let defined1 = std::mem::replace(&mut self.defined, defined);
+        self.command(c2);
+        if let (Some(defined), Some(defined1)) = (&mut self.defined, defined1) {
+            defined.retain(|name| defined1.contains(name));
+        }
+    }
+
+    /// Checks that the procedure `name` and all procedures that its body calls, directly or
+    /// indirectly, are defined, since the interpreter defines a procedure only when its
+    /// definition runs.
+    fn called(&mut self, name: &str) {
+        let Some(defined) = &self.defined else {
+            return;
+        };
+        let mut reached = HashSet::new();
+        let mut pending = vec![name.to_string()];
+        let mut undefined = vec![];
+        while let Some(name) = pending.pop() {
+            let Some((_, callees)) = self.procedures.get(&name) else {
+                continue;
+            };
+            if !reached.insert(name.clone()) {
+                continue;
+            }
+            if !defined.contains(&name) {
+                undefined.push(name);
+            }
+            pending.extend(callees.iter().cloned());
+        }
+        for name in undefined {
+            self.error(TypeError::CalledBeforeDefinition(self.offset, name));
+        }
+    }
+
+    /// Checks the body of a loop, in which `break` and `continue` may appear.
+    fn body(&mut self, c: &Command) {
+        self.loops += 1;
+        self.command(c);
+        self.loops -= 1;
+    }
+
+    /// Checks the assignment of a value of type `typ` to `ident`, where `None` is a number.
+    fn assign(&mut self, ident: &str, typ: Option<Type>) {
+        let ok = match (self.types.get(ident), typ) {
+            (Some(Type::Bool), typ) => typ == Some(Type::Bool),
+            (Some(Type::Int | Type::Machine(_)), typ) => {
+                typ != Some(Type::Float) && typ != Some(Type::Bool)
+            }
+            (Some(Type::Float), typ) => typ != Some(Type::Bool),
+            (None, Some(Type::Bool)) => {
+                self.error(TypeError::UndeclaredBool(self.offset, ident.to_string()));
+                true
+            }
+            (None, _) => true,
+        };
+        if !ok {
+            self.mismatch(ident);
+        }
+    }
+
+    /// A boolean may only be compared with 0 and 1 for equality.
+    fn bexpr(&mut self, bexpr: &BExpr) {
+        let ident = bexpr.get_ident();
+        if self.types.get(ident) == Some(&Type::Bool) {
+            let ok = match bexpr {
+                BExpr::EQ(_, Const::Const(number)) | BExpr::NE(_, Const::Const(number)) => {
+                    *number == 0.0 || *number == 1.0
+                }
+                _ => false,
+            };
+            if !ok {
+                self.mismatch(ident);
+            }
+        }
+    }
+
+    /// The type of `sexpr`, or `None` if it is a number whose type is not known.
+    fn sexpr(&mut self, sexpr: &SExpr) -> Option<Type> {
+        match sexpr {
+            SExpr::CExpr(Const::Const(number)) => match number.fract() == 0.0 {
+                true => Some(Type::Int),
+                false => Some(Type::Float),
+            },
+            SExpr::Bool(_) => Some(Type::Bool),
+            SExpr::VExpr(Var::Var(ident)) => match self.types.get(ident) {
+                // fixed-width integers are integers in arithmetic
+                Some(Type::Machine(_)) => Some(Type::Int),
+                typ => typ.copied(),
+            },
+            SExpr::Neg(sexpr) | SExpr::Abs(sexpr) => self.operand(sexpr),
+            SExpr::Add(sexpr1, sexpr2)
+            | SExpr::Sub(sexpr1, sexpr2)
+            | SExpr::Mul(sexpr1, sexpr2)
+            | SExpr::Min(sexpr1, sexpr2)
+            | SExpr::Max(sexpr1, sexpr2) => match (self.operand(sexpr1), self.operand(sexpr2)) {
+                (Some(Type::Float), _) | (_, Some(Type::Float)) => Some(Type::Float),
+                (Some(Type::Int), Some(Type::Int)) => Some(Type::Int),
+                _ => None,
+            },
+            SExpr::Div(sexpr1, sexpr2) => {
+                self.operand(sexpr1);
+                self.operand(sexpr2);
+                Some(Type::Float)
+            }
+            // the operands are truncated to integers
+            SExpr::Rem(sexpr1, sexpr2) | SExpr::IntDiv(sexpr1, sexpr2) => {
+                self.operand(sexpr1);
+                self.operand(sexpr2);
+                Some(Type::Int)
+            }
+            SExpr::Index(Var::Var(ident), index) => {
+                self.array(ident);
+                self.number(index);
+                None
+            }
+            SExpr::Len(Var::Var(ident)) => {
+                self.array(ident);
+                Some(Type::Int)
+            }
+        }
+    }
+
+    /// The type of an operand of an arithmetic operation, which must not be a boolean.
+    fn operand(&mut self, sexpr: &SExpr) -> Option<Type> {
+        match self.sexpr(sexpr) {
+            Some(Type::Bool) => {
+                self.error(TypeError::BoolAsNumber(self.offset, sexpr.clone()));
+                None
+            }
+            typ => typ,
+        }
+    }
+
+    fn number(&mut self, sexpr: &SExpr) {
+        self.operand(sexpr);
+    }
+
+    fn array(&mut self, ident: &str) {
+        if !self.arrays.contains(ident) {
+            self.error(TypeError::UndeclaredArray(self.offset, ident.to_string()));
+        }
+    }
+
+    fn mismatch(&mut self, ident: &str) {
+        let typ = self.types[ident];
+        self.error(TypeError::Mismatch(self.offset, ident.to_string(), typ));
+    }
+
+    fn error(&mut self, error: TypeError) {
+        self.errors.push(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_parser::parse;
+
+    #[test]
+    fn well_typed() {
+        let program = "var i: int; var f: float; var b: bool; input(i); f := i / 2; i := f div 2 + abs(i % 3); b := true; if (b) {i := i + 1} else {f := f * 1.5}; while (!b) {b := false}";
+        let command = parse(program);
+
+        assert_eq!(Vec::<TypeError>::new(), check(&command));
+    }
+
+    #[test]
+    fn float_assigned_to_int() {
+        let program = "var x: int;\nx := 7 / 2";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![TypeError::Mismatch(12, "x".to_string(), Type::Int)],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn integer_operators() {
+        let program = "var x: int; x := 7.5 % 2; x := min(x, 0.5)";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![TypeError::Mismatch(26, "x".to_string(), Type::Int)],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn machine_integers() {
+        let program = "var x: u8; var y: int; y := x + 1; x := y * 2; x := 0.5";
+        let command = parse(program);
+
+        let errors = check(&command);
+        assert_eq!(1, errors.len());
+        assert_eq!(
+            "x of type u8 gets a value of another type",
+            errors[0].to_string()
+        );
+    }
+
+    #[test]
+    fn numbers_assigned_to_bools() {
+        let program = "var b: bool; b := 1; if (b > 0) {skip}; if (b == 1) {skip}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::Mismatch(13, "b".to_string(), Type::Bool),
+                TypeError::Mismatch(21, "b".to_string(), Type::Bool)
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn bool_in_arithmetic() {
+        let program = "var b: bool; b := false; array a[2]; x := b + 1; a[b] := 0";
+        let command = parse(program);
+
+        let b = SExpr::VExpr(Var::Var("b".to_string()));
+        assert_eq!(
+            vec![
+                TypeError::BoolAsNumber(37, b.clone()),
+                TypeError::BoolAsNumber(49, b)
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn undeclared_and_redeclared() {
+        let program = "b := true; var x: int; var x: float";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::UndeclaredBool(0, "b".to_string()),
+                TypeError::Redeclared(23, "x".to_string())
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn input_ranges_without_integers() {
+        let program = "input(x, 0.2, 0.7); input(y, 5, 1); input(z, 0.5, 1.5)";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::EmptyRange(0, "x".to_string()),
+                TypeError::EmptyRange(20, "y".to_string())
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn calls_of_procedures() {
+        let program =
+            "proc f(a, b) {c := g(a); return c}; x := f(1); y := h(2); proc g(a) {return a}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::Arity(36, "f".to_string(), 2, 1),
+                TypeError::CalledBeforeDefinition(36, "g".to_string()),
+                TypeError::UndefinedProcedure(47, "h".to_string())
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn calls_before_definitions() {
+        let program = "x := f(1); proc f(n) {return n + 1}; if (x > 0) {proc g(n) {r := f(n); return r}} else {skip}; y := g(2); proc f(n) {return n}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::CalledBeforeDefinition(0, "f".to_string()),
+                TypeError::CalledBeforeDefinition(95, "g".to_string()),
+                TypeError::Redefined(106, "f".to_string())
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn undeclared_arrays() {
+        let program = "a[0] := 1; array b[2]; x := b[0] + len(c); proc f(i) {return b[i]}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::UndeclaredArray(0, "a".to_string()),
+                TypeError::UndeclaredArray(23, "c".to_string()),
+                TypeError::UndeclaredArray(43, "b".to_string())
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn jumps_outside_of_loops() {
+        let program =
+            "break; while (x > 0) {if (x > 1) {continue}; break}; proc f(a) {continue; return a}";
+        let command = parse(program);
+
+        assert_eq!(
+            vec![
+                TypeError::OutsideLoop(0, "break".to_string()),
+                TypeError::OutsideLoop(64, "continue".to_string())
+            ],
+            check(&command)
+        );
+    }
+
+    #[test]
+    fn procedures_have_their_own_scope() {
+        let program = "var x: bool; proc f(x) {x := x + 1; return x}; var y: int; y := f(1)";
+        let command = parse(program);
+
+        assert_eq!(Vec::<TypeError>::new(), check(&command));
+    }
+}